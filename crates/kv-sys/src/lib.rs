@@ -1,6 +1,6 @@
 use serde::{Serialize, Deserialize};
 use toml::Value;
-use std::{collections::HashMap, fs, path::{Path, PathBuf}};
+use std::{collections::{BTreeMap, HashMap}, fs, path::{Path, PathBuf}};
 
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct KVData {
@@ -54,6 +54,19 @@ impl KVStore {
         self.kv.data.get(key).and_then(Value::as_integer)
     }
 
+    pub fn get_as_float(&self, key: &str) -> Option<f64> {
+        self.kv.data.get(key).and_then(Value::as_float)
+    }
+
+    pub fn get_as_str_map(&self, key: &str) -> Option<BTreeMap<String, String>> {
+        self.kv.data.get(key).and_then(Value::as_table).map(|table| {
+            table
+                .iter()
+                .filter_map(|(k, v)| Some((k.clone(), v.as_str()?.to_string())))
+                .collect()
+        })
+    }
+
     pub fn get_as_bool(&self, key: &str) -> bool {
         self.get_as_bool_or_default(key, false)
     }
@@ -0,0 +1,873 @@
+//! ADS-B extended squitter (DF17/DF18) message elements.
+
+use crate::Squawk;
+use crate::altitude::{FEET_PER_METER, decode_ac12};
+use crate::commb::{NavigationModes, TargetAltitudeSource};
+use crate::frame::field;
+
+/// Decoded ME field of an extended squitter, selected by its type code.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Me {
+    /// Type codes 1 to 4.
+    Identification(Identification),
+    /// Type codes 5 to 8.
+    SurfacePosition(SurfacePosition),
+    /// Type codes 9 to 18 (barometric altitude) and 20 to 22 (GNSS height).
+    AirbornePosition(AirbornePosition),
+    /// Type code 19.
+    AirborneVelocity(AirborneVelocity),
+    /// Type code 28, subtype 1.
+    AircraftStatus(AircraftStatus),
+    /// Type code 29, from ADS-B version 1 on.
+    TargetState(TargetState),
+    /// Type code 31.
+    OperationalStatus(OperationalStatus),
+    /// Any type code this crate does not decode (yet).
+    Unknown { type_code: u8, data: [u8; 7] },
+}
+
+impl Me {
+    /// Decode the 56-bit ME field.
+    pub fn decode(data: [u8; 7]) -> Self {
+        let type_code = field(&data, 1, 5) as u8;
+
+        match type_code {
+            1..=4 => Me::Identification(Identification::decode(&data)),
+            5..=8 => Me::SurfacePosition(SurfacePosition::decode(&data)),
+            9..=18 | 20..=22 => Me::AirbornePosition(AirbornePosition::decode(&data)),
+            19 => match AirborneVelocity::decode(&data) {
+                Some(velocity) => Me::AirborneVelocity(velocity),
+                None => Me::Unknown { type_code, data },
+            },
+            28 => match AircraftStatus::decode(&data) {
+                Some(status) => Me::AircraftStatus(status),
+                None => Me::Unknown { type_code, data },
+            },
+            29 => match TargetState::decode(&data) {
+                Some(state) => Me::TargetState(state),
+                None => Me::Unknown { type_code, data },
+            },
+            31 => match OperationalStatus::decode(&data) {
+                Some(status) => Me::OperationalStatus(status),
+                None => Me::Unknown { type_code, data },
+            },
+            _ => Me::Unknown { type_code, data },
+        }
+    }
+
+    /// Type code of the message element.
+    pub fn type_code(&self) -> u8 {
+        match self {
+            Me::Identification(identification) => identification.type_code,
+            Me::SurfacePosition(position) => position.type_code,
+            Me::AirbornePosition(position) => position.type_code,
+            Me::AirborneVelocity(_) => 19,
+            Me::AircraftStatus(_) => 28,
+            Me::TargetState(_) => 29,
+            Me::OperationalStatus(_) => 31,
+            Me::Unknown { type_code, .. } => *type_code,
+        }
+    }
+}
+
+/// Emitter category broadcast together with the callsign.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AircraftCategory {
+    NoInformation,
+    Light,
+    Small,
+    Large,
+    HighVortexLarge,
+    Heavy,
+    HighPerformance,
+    Rotorcraft,
+    Glider,
+    LighterThanAir,
+    Parachutist,
+    Ultralight,
+    Uav,
+    SpaceVehicle,
+    EmergencyVehicle,
+    ServiceVehicle,
+    Obstacle,
+    Reserved { type_code: u8, category: u8 },
+}
+
+impl AircraftCategory {
+    /// Decode the category from the type code (set D to A) and the 3-bit category field.
+    pub fn new(type_code: u8, category: u8) -> Self {
+        use AircraftCategory::*;
+
+        match (type_code, category) {
+            (_, 0) => NoInformation,
+            (4, 1) => Light,
+            (4, 2) => Small,
+            (4, 3) => Large,
+            (4, 4) => HighVortexLarge,
+            (4, 5) => Heavy,
+            (4, 6) => HighPerformance,
+            (4, 7) => Rotorcraft,
+            (3, 1) => Glider,
+            (3, 2) => LighterThanAir,
+            (3, 3) => Parachutist,
+            (3, 4) => Ultralight,
+            (3, 6) => Uav,
+            (3, 7) => SpaceVehicle,
+            (2, 1) => EmergencyVehicle,
+            (2, 3) => ServiceVehicle,
+            (2, 4..=7) => Obstacle,
+            (type_code, category) => Reserved {
+                type_code,
+                category,
+            },
+        }
+    }
+
+    /// Parse the two character code used by dump1090 and readsb, where sets A to D are type
+    /// codes 4 to 1, e.g. `A3` for [`AircraftCategory::Large`].
+    pub fn parse(code: &str) -> Option<Self> {
+        let mut chars = code.trim().chars();
+        let type_code = match chars.next()? {
+            'A' => 4,
+            'B' => 3,
+            'C' => 2,
+            'D' => 1,
+            _ => return None,
+        };
+        let category = chars.next()?.to_digit(8)? as u8;
+
+        chars
+            .next()
+            .is_none()
+            .then(|| Self::new(type_code, category))
+    }
+}
+
+/// Aircraft identification and category, type codes 1 to 4.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Identification {
+    pub type_code: u8,
+    pub category: AircraftCategory,
+    /// Callsign with the trailing spaces removed.
+    pub callsign: String,
+}
+
+pub(crate) const CALLSIGN_CHARSET: &[u8; 64] =
+    b"#ABCDEFGHIJKLMNOPQRSTUVWXYZ##### ###############0123456789######";
+
+impl Identification {
+    fn decode(data: &[u8]) -> Self {
+        let type_code = field(data, 1, 5) as u8;
+        let category = AircraftCategory::new(type_code, field(data, 6, 8) as u8);
+
+        let callsign: String = (0..8)
+            .map(|i| CALLSIGN_CHARSET[field(data, 9 + i * 6, 14 + i * 6) as usize] as char)
+            .collect();
+
+        Self {
+            type_code,
+            category,
+            callsign: callsign.trim_end().to_string(),
+        }
+    }
+}
+
+/// Format of a Compact Position Reporting frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CprFormat {
+    Even,
+    Odd,
+}
+
+/// Encoded 17-bit CPR latitude and longitude, as broadcast.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CprFrame {
+    pub format: CprFormat,
+    pub lat: u32,
+    pub lon: u32,
+}
+
+impl CprFrame {
+    fn decode(data: &[u8]) -> Self {
+        Self {
+            format: if field(data, 22, 22) == 0 {
+                CprFormat::Even
+            } else {
+                CprFormat::Odd
+            },
+            lat: field(data, 23, 39),
+            lon: field(data, 40, 56),
+        }
+    }
+}
+
+/// Source of the altitude in airborne position messages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AltitudeSource {
+    Barometric,
+    Gnss,
+}
+
+/// Airborne position, type codes 9 to 18 and 20 to 22.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AirbornePosition {
+    pub type_code: u8,
+    pub surveillance_status: u8,
+    /// NIC supplement B from version 2 on, the single antenna flag before.
+    pub nic_supplement_b: bool,
+    /// Altitude in feet.
+    pub altitude: Option<i32>,
+    pub altitude_source: AltitudeSource,
+    /// Whether the position is synchronized to UTC.
+    pub utc_sync: bool,
+    pub cpr: CprFrame,
+}
+
+impl AirbornePosition {
+    fn decode(data: &[u8]) -> Self {
+        let type_code = field(data, 1, 5) as u8;
+        let altitude_code = field(data, 9, 20);
+
+        let (altitude, altitude_source) = if type_code >= 20 {
+            // GNSS height above the ellipsoid, in meters.
+            let altitude = (altitude_code != 0)
+                .then(|| (altitude_code as f64 * FEET_PER_METER).round() as i32);
+            (altitude, AltitudeSource::Gnss)
+        } else {
+            (decode_ac12(altitude_code), AltitudeSource::Barometric)
+        };
+
+        Self {
+            type_code,
+            surveillance_status: field(data, 6, 7) as u8,
+            nic_supplement_b: field(data, 8, 8) != 0,
+            altitude,
+            altitude_source,
+            utc_sync: field(data, 21, 21) != 0,
+            cpr: CprFrame::decode(data),
+        }
+    }
+}
+
+/// Surface position, type codes 5 to 8.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SurfacePosition {
+    pub type_code: u8,
+    /// Ground speed in knots.
+    pub ground_speed: Option<f64>,
+    /// Ground track in degrees, clockwise from the true north.
+    pub track: Option<f64>,
+    pub utc_sync: bool,
+    pub cpr: CprFrame,
+}
+
+impl SurfacePosition {
+    fn decode(data: &[u8]) -> Self {
+        let track = (field(data, 13, 13) != 0).then(|| field(data, 14, 20) as f64 * 360.0 / 128.0);
+
+        Self {
+            type_code: field(data, 1, 5) as u8,
+            ground_speed: decode_movement(field(data, 6, 12)),
+            track,
+            utc_sync: field(data, 21, 21) != 0,
+            cpr: CprFrame::decode(data),
+        }
+    }
+}
+
+/// Decode the non-linear movement field of surface position messages into knots.
+fn decode_movement(movement: u32) -> Option<f64> {
+    let (base, first, step) = match movement {
+        1 => return Some(0.0),
+        2..=8 => (0.125, 2, 0.125),
+        9..=12 => (1.0, 9, 0.25),
+        13..=38 => (2.0, 13, 0.5),
+        39..=93 => (15.0, 39, 1.0),
+        94..=108 => (70.0, 94, 2.0),
+        109..=123 => (100.0, 109, 5.0),
+        124 => return Some(175.0),
+        _ => return None,
+    };
+    Some(base + (movement - first) as f64 * step)
+}
+
+/// Kind of airspeed broadcast in subtypes 3 and 4 of the airborne velocity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AirspeedType {
+    Indicated,
+    True,
+}
+
+/// Airspeed in knots.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Airspeed {
+    pub airspeed_type: AirspeedType,
+    pub speed: f64,
+}
+
+/// Source of the vertical rate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerticalRateSource {
+    Gnss,
+    Barometric,
+}
+
+/// Airborne velocity, type code 19. Subtypes 1 and 2 carry the velocity over ground, subtypes 3
+/// and 4 the airspeed and heading, which are sent when the position is not available. Subtypes 2
+/// and 4 are for supersonic aircraft and have a coarser resolution.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AirborneVelocity {
+    pub subtype: u8,
+    /// Ground speed in knots.
+    pub ground_speed: Option<f64>,
+    /// Ground track in degrees, clockwise from the true north.
+    pub track: Option<f64>,
+    pub airspeed: Option<Airspeed>,
+    /// Heading in degrees, clockwise from the magnetic north.
+    pub heading: Option<f64>,
+    /// Vertical rate in feet per minute, positive when climbing.
+    pub vertical_rate: Option<i32>,
+    pub vertical_rate_source: VerticalRateSource,
+    /// GNSS altitude minus barometric altitude, in feet.
+    pub gnss_altitude_difference: Option<i32>,
+}
+
+impl AirborneVelocity {
+    fn decode(data: &[u8]) -> Option<Self> {
+        let subtype = field(data, 6, 8) as u8;
+        let (ground_speed, track, airspeed, heading) = match subtype {
+            1 | 2 => {
+                let (ground_speed, track) = decode_ground_velocity(data, subtype);
+                (ground_speed, track, None, None)
+            }
+            3 | 4 => {
+                let (airspeed, heading) = decode_airspeed(data, subtype);
+                (None, None, airspeed, heading)
+            }
+            _ => return None,
+        };
+
+        let vertical_rate_source = if field(data, 36, 36) != 0 {
+            VerticalRateSource::Barometric
+        } else {
+            VerticalRateSource::Gnss
+        };
+
+        let vertical_rate = match field(data, 38, 46) {
+            0 => None,
+            rate => {
+                let rate = (rate as i32 - 1) * 64;
+                Some(if field(data, 37, 37) != 0 {
+                    -rate
+                } else {
+                    rate
+                })
+            }
+        };
+
+        let gnss_altitude_difference = match field(data, 50, 56) {
+            0 => None,
+            difference => {
+                let difference = (difference as i32 - 1) * 25;
+                Some(if field(data, 49, 49) != 0 {
+                    -difference
+                } else {
+                    difference
+                })
+            }
+        };
+
+        Some(Self {
+            subtype,
+            ground_speed,
+            track,
+            airspeed,
+            heading,
+            vertical_rate,
+            vertical_rate_source,
+            gnss_altitude_difference,
+        })
+    }
+}
+
+/// Decode the airspeed and the magnetic heading.
+fn decode_airspeed(data: &[u8], subtype: u8) -> (Option<Airspeed>, Option<f64>) {
+    let heading = (field(data, 14, 14) != 0).then(|| field(data, 15, 24) as f64 * 360.0 / 1024.0);
+
+    let airspeed = match field(data, 26, 35) {
+        0 => None,
+        speed => {
+            let multiplier = if subtype == 4 { 4.0 } else { 1.0 };
+            let airspeed_type = if field(data, 25, 25) != 0 {
+                AirspeedType::True
+            } else {
+                AirspeedType::Indicated
+            };
+            Some(Airspeed {
+                airspeed_type,
+                speed: (speed - 1) as f64 * multiplier,
+            })
+        }
+    };
+
+    (airspeed, heading)
+}
+
+/// Decode the east-west and north-south velocity components into ground speed and track.
+fn decode_ground_velocity(data: &[u8], subtype: u8) -> (Option<f64>, Option<f64>) {
+    let multiplier = if subtype == 2 { 4.0 } else { 1.0 };
+
+    let (v_ew, v_ns) = match (field(data, 15, 24), field(data, 26, 35)) {
+        (0, _) | (_, 0) => return (None, None),
+        (v_ew, v_ns) => (
+            (v_ew - 1) as f64 * multiplier,
+            (v_ns - 1) as f64 * multiplier,
+        ),
+    };
+
+    let v_ew = if field(data, 14, 14) != 0 {
+        -v_ew
+    } else {
+        v_ew
+    };
+    let v_ns = if field(data, 25, 25) != 0 {
+        -v_ns
+    } else {
+        v_ns
+    };
+
+    let ground_speed = v_ew.hypot(v_ns);
+    let track = v_ew.atan2(v_ns).to_degrees().rem_euclid(360.0);
+
+    (Some(ground_speed), Some(track))
+}
+
+/// Emergency or priority status, shared by ADS-B and UAT.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EmergencyStatus {
+    None,
+    General,
+    Medical,
+    MinimumFuel,
+    NoCommunications,
+    UnlawfulInterference,
+    DownedAircraft,
+    Reserved,
+}
+
+impl EmergencyStatus {
+    /// Decode the 3-bit emergency/priority status field.
+    pub fn from_code(code: u8) -> Self {
+        match code {
+            0 => EmergencyStatus::None,
+            1 => EmergencyStatus::General,
+            2 => EmergencyStatus::Medical,
+            3 => EmergencyStatus::MinimumFuel,
+            4 => EmergencyStatus::NoCommunications,
+            5 => EmergencyStatus::UnlawfulInterference,
+            6 => EmergencyStatus::DownedAircraft,
+            _ => EmergencyStatus::Reserved,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            EmergencyStatus::None => "No emergency",
+            EmergencyStatus::General => "General emergency",
+            EmergencyStatus::Medical => "Medical emergency",
+            EmergencyStatus::MinimumFuel => "Minimum fuel",
+            EmergencyStatus::NoCommunications => "No communications",
+            EmergencyStatus::UnlawfulInterference => "Unlawful interference",
+            EmergencyStatus::DownedAircraft => "Downed aircraft",
+            EmergencyStatus::Reserved => "Reserved emergency status",
+        }
+    }
+}
+
+/// Emergency/priority status and Mode A code, type code 28 subtype 1. Subtype 2, the ACAS
+/// resolution advisory broadcast, is not decoded.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AircraftStatus {
+    pub emergency: EmergencyStatus,
+    pub squawk: Squawk,
+}
+
+impl AircraftStatus {
+    fn decode(data: &[u8]) -> Option<Self> {
+        if field(data, 6, 8) != 1 {
+            return None;
+        }
+
+        Some(Self {
+            emergency: EmergencyStatus::from_code(field(data, 9, 11) as u8),
+            squawk: Squawk::from_id13(field(data, 12, 24)),
+        })
+    }
+}
+
+/// Target state and status, type code 29. Version 1 transmitters send subtype 0 with the
+/// target altitude and heading, version 2 ones subtype 1 with the values selected on the
+/// autopilot.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TargetState {
+    pub subtype: u8,
+    /// Selected or target altitude, in feet.
+    pub selected_altitude: Option<i32>,
+    pub selected_altitude_source: TargetAltitudeSource,
+    /// Barometric pressure setting in hPa.
+    pub qnh: Option<f64>,
+    /// Selected or target heading, in degrees. Version 1 can also report a track.
+    pub selected_heading: Option<f64>,
+    /// Navigation accuracy category for position.
+    pub nac_p: u8,
+    /// Whether the barometric altitude has been cross-checked against another source.
+    pub nic_baro: bool,
+    /// Source integrity level.
+    pub sil: u8,
+    /// Only reported by version 2.
+    pub modes: NavigationModes,
+}
+
+impl TargetState {
+    fn decode(data: &[u8]) -> Option<Self> {
+        match field(data, 6, 7) {
+            0 => Some(Self::decode_version_1(data)),
+            1 => Some(Self::decode_version_2(data)),
+            _ => None,
+        }
+    }
+
+    fn decode_version_1(data: &[u8]) -> Self {
+        let selected_altitude = (field(data, 8, 9) != 0 && field(data, 16, 25) <= 1010)
+            .then(|| field(data, 16, 25) as i32 * 100 - 1000);
+        let selected_heading = (field(data, 26, 27) != 0 && field(data, 28, 36) < 360)
+            .then(|| field(data, 28, 36) as f64);
+
+        Self {
+            subtype: 0,
+            selected_altitude,
+            selected_altitude_source: TargetAltitudeSource::Unknown,
+            qnh: None,
+            selected_heading,
+            nac_p: field(data, 40, 43) as u8,
+            nic_baro: field(data, 44, 44) != 0,
+            sil: field(data, 45, 46) as u8,
+            modes: NavigationModes::default(),
+        }
+    }
+
+    fn decode_version_2(data: &[u8]) -> Self {
+        let selected_altitude = match field(data, 10, 20) {
+            0 => None,
+            altitude => Some((altitude as i32 - 1) * 32),
+        };
+        let selected_altitude_source = if field(data, 9, 9) != 0 {
+            TargetAltitudeSource::Fms
+        } else {
+            TargetAltitudeSource::Mcp
+        };
+        let qnh = match field(data, 21, 29) {
+            0 => None,
+            qnh => Some((qnh - 1) as f64 * 0.8 + 800.0),
+        };
+        let selected_heading =
+            (field(data, 30, 30) != 0).then(|| field(data, 31, 39) as f64 * 180.0 / 256.0);
+
+        let modes = if field(data, 47, 47) != 0 {
+            NavigationModes {
+                autopilot: Some(field(data, 48, 48) != 0),
+                vnav: Some(field(data, 49, 49) != 0),
+                altitude_hold: Some(field(data, 50, 50) != 0),
+                approach: Some(field(data, 52, 52) != 0),
+                tcas: Some(field(data, 53, 53) != 0),
+                lnav: Some(field(data, 54, 54) != 0),
+            }
+        } else {
+            // The TCAS status is valid regardless of the mode status bit.
+            NavigationModes {
+                tcas: Some(field(data, 53, 53) != 0),
+                ..Default::default()
+            }
+        };
+
+        Self {
+            subtype: 1,
+            selected_altitude,
+            selected_altitude_source,
+            qnh,
+            selected_heading,
+            nac_p: field(data, 40, 43) as u8,
+            nic_baro: field(data, 44, 44) != 0,
+            sil: field(data, 45, 46) as u8,
+            modes,
+        }
+    }
+}
+
+/// Aircraft operational status, type code 31. It carries the ADS-B version, which tells how to
+/// interpret the integrity values of the other messages. Version 0 transmitters only report
+/// their capabilities, so the other fields are `None`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OperationalStatus {
+    /// Whether the status is of an aircraft on the ground, subtype 1.
+    pub surface: bool,
+    /// ADS-B version: 0 for DO-260, 1 for DO-260A and 2 for DO-260B.
+    pub version: u8,
+    pub nic_supplement_a: Option<bool>,
+    /// Only reported on the ground, by version 2.
+    pub nic_supplement_c: Option<bool>,
+    /// Navigation accuracy category for position.
+    pub nac_p: Option<u8>,
+    /// Geometric vertical accuracy, only reported in the air by version 2.
+    pub gva: Option<u8>,
+    /// Source integrity level.
+    pub sil: Option<u8>,
+    /// Whether the SIL is per sample rather than per hour, version 2.
+    pub sil_supplement: Option<bool>,
+    /// Whether the barometric altitude has been cross-checked, only reported in the air.
+    pub nic_baro: Option<bool>,
+}
+
+impl OperationalStatus {
+    fn decode(data: &[u8]) -> Option<Self> {
+        let surface = match field(data, 6, 8) {
+            0 => false,
+            1 => true,
+            _ => return None,
+        };
+        let version = field(data, 41, 43) as u8;
+
+        let mut status = Self {
+            surface,
+            version,
+            nic_supplement_a: None,
+            nic_supplement_c: None,
+            nac_p: None,
+            gva: None,
+            sil: None,
+            sil_supplement: None,
+            nic_baro: None,
+        };
+
+        if version >= 1 {
+            status.nic_supplement_a = Some(field(data, 44, 44) != 0);
+            status.nac_p = Some(field(data, 45, 48) as u8);
+            status.sil = Some(field(data, 51, 52) as u8);
+            if !surface {
+                status.nic_baro = Some(field(data, 53, 53) != 0);
+            }
+        }
+        if version >= 2 {
+            status.sil_supplement = Some(field(data, 55, 55) != 0);
+            if surface {
+                status.nic_supplement_c = Some(field(data, 20, 20) != 0);
+            } else {
+                status.gva = Some(field(data, 49, 50) as u8);
+            }
+        }
+
+        Some(status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Frame, Message};
+
+    fn me(hex: &str) -> Me {
+        match Message::decode(&Frame::from_hex(hex).unwrap()) {
+            Message::ExtendedSquitter { me, .. } => me,
+            other => panic!("not an extended squitter: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn decoding_identification() {
+        let Me::Identification(identification) = me("8D4840D6202CC371C32CE0576098") else {
+            panic!("not an identification");
+        };
+        assert_eq!(identification.callsign, "KLM1023");
+        assert_eq!(identification.category, AircraftCategory::NoInformation);
+    }
+
+    #[test]
+    fn parsing_category_codes() {
+        assert_eq!(AircraftCategory::parse("A3"), Some(AircraftCategory::Large));
+        assert_eq!(
+            AircraftCategory::parse("B1"),
+            Some(AircraftCategory::Glider)
+        );
+        assert_eq!(
+            AircraftCategory::parse("A0"),
+            Some(AircraftCategory::NoInformation)
+        );
+        assert_eq!(AircraftCategory::parse("E1"), None);
+        assert_eq!(AircraftCategory::parse("A38"), None);
+    }
+
+    #[test]
+    fn decoding_airborne_position() {
+        let Me::AirbornePosition(position) = me("8D40621D58C382D690C8AC2863A7") else {
+            panic!("not an airborne position");
+        };
+        assert_eq!(position.altitude, Some(38000));
+        assert_eq!(position.altitude_source, AltitudeSource::Barometric);
+        assert_eq!(
+            position.cpr,
+            CprFrame {
+                format: CprFormat::Even,
+                lat: 93000,
+                lon: 51372
+            }
+        );
+    }
+
+    #[test]
+    fn decoding_surface_position() {
+        let Me::SurfacePosition(position) = me("8C4841753A9A153237AEF0F275BE") else {
+            panic!("not a surface position");
+        };
+        assert_eq!(position.ground_speed, Some(17.0));
+        assert!((position.track.unwrap() - 92.8125).abs() < 1e-9);
+        assert_eq!(position.cpr.lat, 39195);
+        assert_eq!(position.cpr.lon, 110320);
+    }
+
+    #[test]
+    fn decoding_ground_velocity() {
+        let Me::AirborneVelocity(velocity) = me("8D485020994409940838175B284F") else {
+            panic!("not an airborne velocity");
+        };
+        assert_eq!(velocity.subtype, 1);
+        assert!((velocity.ground_speed.unwrap() - 159.20).abs() < 0.01);
+        assert!((velocity.track.unwrap() - 182.88).abs() < 0.01);
+        assert_eq!(velocity.vertical_rate, Some(-832));
+        assert_eq!(velocity.vertical_rate_source, VerticalRateSource::Gnss);
+        assert_eq!(velocity.gnss_altitude_difference, Some(550));
+        assert_eq!(velocity.airspeed, None);
+    }
+
+    #[test]
+    fn decoding_airspeed() {
+        let Me::AirborneVelocity(velocity) = me("8DA05F219B06B6AF189400CBC33F") else {
+            panic!("not an airborne velocity");
+        };
+        assert_eq!(velocity.subtype, 3);
+        assert_eq!(
+            velocity.airspeed,
+            Some(Airspeed {
+                airspeed_type: AirspeedType::True,
+                speed: 375.0
+            })
+        );
+        assert!((velocity.heading.unwrap() - 243.98).abs() < 0.01);
+        assert_eq!(velocity.vertical_rate, Some(-2304));
+        assert_eq!(
+            velocity.vertical_rate_source,
+            VerticalRateSource::Barometric
+        );
+        assert_eq!(velocity.ground_speed, None);
+    }
+
+    #[test]
+    fn decoding_target_state() {
+        let Me::TargetState(state) = me("8DA05629EA21485CBF3F8CADAEEB") else {
+            panic!("not a target state");
+        };
+        assert_eq!(state.subtype, 1);
+        assert_eq!(state.selected_altitude, Some(16992));
+        assert_eq!(state.selected_altitude_source, TargetAltitudeSource::Mcp);
+        assert!((state.qnh.unwrap() - 1012.8).abs() < 0.01);
+        assert!((state.selected_heading.unwrap() - 66.8).abs() < 0.1);
+        assert_eq!(state.nac_p, 9);
+        assert!(state.nic_baro);
+        assert_eq!(state.sil, 3);
+        assert_eq!(
+            state.modes,
+            NavigationModes {
+                autopilot: Some(true),
+                vnav: Some(true),
+                altitude_hold: Some(false),
+                approach: Some(false),
+                lnav: Some(true),
+                tcas: Some(true),
+            }
+        );
+    }
+
+    /// ME field with the given bits, numbered from 1, set to the given values.
+    fn me_with(fields: &[(usize, usize, u32)]) -> [u8; 7] {
+        let mut data = [0; 7];
+        for &(first, last, value) in fields {
+            for bit in first..=last {
+                if value >> (last - bit) & 1 != 0 {
+                    data[(bit - 1) / 8] |= 0x80 >> ((bit - 1) % 8);
+                }
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn decoding_aircraft_status() {
+        // Medical emergency squawking 7700: A and B bits set, interleaved in the ID field.
+        let data = me_with(&[(1, 5, 28), (6, 8, 1), (9, 11, 2), (12, 24, 0x0AAA)]);
+        let Me::AircraftStatus(status) = Me::decode(data) else {
+            panic!("not an aircraft status");
+        };
+        assert_eq!(status.emergency, EmergencyStatus::Medical);
+        assert_eq!(status.squawk, Squawk(0x7700));
+
+        // Resolution advisories are left undecoded.
+        let data = me_with(&[(1, 5, 28), (6, 8, 2)]);
+        assert!(matches!(
+            Me::decode(data),
+            Me::Unknown { type_code: 28, .. }
+        ));
+    }
+
+    #[test]
+    fn decoding_operational_status() {
+        // Airborne, version 2, NIC supplement A, NACp 9, GVA 2, SIL 3, NICbaro.
+        let data = me_with(&[
+            (1, 5, 31),
+            (41, 43, 2),
+            (44, 44, 1),
+            (45, 48, 9),
+            (49, 50, 2),
+            (51, 52, 3),
+            (53, 53, 1),
+        ]);
+        let Me::OperationalStatus(status) = Me::decode(data) else {
+            panic!("not an operational status");
+        };
+        assert!(!status.surface);
+        assert_eq!(status.version, 2);
+        assert_eq!(status.nic_supplement_a, Some(true));
+        assert_eq!(status.nic_supplement_c, None);
+        assert_eq!(status.nac_p, Some(9));
+        assert_eq!(status.gva, Some(2));
+        assert_eq!(status.sil, Some(3));
+        assert_eq!(status.nic_baro, Some(true));
+
+        // On the ground, supplement C replaces the GVA.
+        let data = me_with(&[(1, 5, 31), (6, 8, 1), (20, 20, 1), (41, 43, 2)]);
+        let Me::OperationalStatus(status) = Me::decode(data) else {
+            panic!("not an operational status");
+        };
+        assert!(status.surface);
+        assert_eq!(status.nic_supplement_c, Some(true));
+        assert_eq!(status.gva, None);
+        assert_eq!(status.nic_baro, None);
+
+        // Version 0 only tells its version.
+        let data = me_with(&[(1, 5, 31), (44, 52, 0x1FF)]);
+        let Me::OperationalStatus(status) = Me::decode(data) else {
+            panic!("not an operational status");
+        };
+        assert_eq!(status.version, 0);
+        assert_eq!(status.nac_p, None);
+        assert_eq!(status.nic_supplement_a, None);
+    }
+}
@@ -0,0 +1,122 @@
+//! Altitude codes used by Mode C and Mode S replies.
+
+/// Decode the 13-bit altitude code (AC) field of DF0, DF4, DF16 and DF20 replies. Returns the
+/// altitude in feet, or `None` if it is not available or invalid.
+pub fn decode_ac13(ac: u32) -> Option<i32> {
+    if ac == 0 {
+        return None;
+    }
+
+    let m_bit = ac & 0x0040 != 0;
+    let q_bit = ac & 0x0010 != 0;
+
+    if m_bit {
+        // Metric altitude. Rarely seen in practice, reported in 25 m increments.
+        let n = ((ac & 0x1F80) >> 1) | (ac & 0x003F);
+        return Some((n as f64 * 25.0 * FEET_PER_METER).round() as i32);
+    }
+
+    if q_bit {
+        // 25 ft increments, with the M and Q bits removed.
+        let n = ((ac & 0x1F80) >> 2) | ((ac & 0x0020) >> 1) | (ac & 0x000F);
+        return Some(n as i32 * 25 - 1000);
+    }
+
+    gillham_to_altitude(crate::squawk::id13_to_gillham(ac))
+}
+
+/// Decode the 12-bit altitude field of airborne position messages. It is the same as the AC13
+/// field, but without the M bit.
+pub fn decode_ac12(ac: u32) -> Option<i32> {
+    if ac == 0 {
+        return None;
+    }
+
+    if ac & 0x0010 != 0 {
+        let n = ((ac & 0x0FE0) >> 1) | (ac & 0x000F);
+        return Some(n as i32 * 25 - 1000);
+    }
+
+    // Reinsert the M bit, so it can be decoded as a regular Gillham code.
+    let ac13 = ((ac & 0x0FC0) << 1) | (ac & 0x003F);
+    gillham_to_altitude(crate::squawk::id13_to_gillham(ac13))
+}
+
+/// Convert a Gillham coded (Mode C) altitude into feet. The code is expected in the same
+/// `0xABCD` layout used by [`crate::Squawk`], where each hex digit holds the 4, 2 and 1 pulses of
+/// the A, B, C and D groups.
+pub fn gillham_to_altitude(code: u16) -> Option<i32> {
+    // D1 is never used for altitude, and at least one C pulse must be present.
+    if code & 0x8889 != 0 || code & 0x00F0 == 0 {
+        return None;
+    }
+
+    let mut one_hundreds: i32 = 0;
+    if code & 0x0010 != 0 {
+        one_hundreds ^= 0x007; // C1
+    }
+    if code & 0x0020 != 0 {
+        one_hundreds ^= 0x003; // C2
+    }
+    if code & 0x0040 != 0 {
+        one_hundreds ^= 0x001; // C4
+    }
+
+    // Remove 7s from the hundreds, turning 7 into 5 and 5 into 7.
+    if one_hundreds & 5 == 5 {
+        one_hundreds ^= 2;
+    }
+
+    // Only 1 to 5 are valid.
+    if one_hundreds > 5 {
+        return None;
+    }
+
+    let mut five_hundreds: i32 = 0;
+    for (mask, value) in [
+        (0x0002, 0x0FF), // D2
+        (0x0004, 0x07F), // D4
+        (0x1000, 0x03F), // A1
+        (0x2000, 0x01F), // A2
+        (0x4000, 0x00F), // A4
+        (0x0100, 0x007), // B1
+        (0x0200, 0x003), // B2
+        (0x0400, 0x001), // B4
+    ] {
+        if code & mask != 0 {
+            five_hundreds ^= value;
+        }
+    }
+
+    // Hundreds are reflected on every odd five hundreds.
+    if five_hundreds & 1 != 0 {
+        one_hundreds = 6 - one_hundreds;
+    }
+
+    Some((five_hundreds * 5 + one_hundreds - 13) * 100)
+}
+
+pub(crate) const FEET_PER_METER: f64 = 3.28084;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decoding_q_bit_altitudes() {
+        // From DF4 `20001838CA3804` and DF17 `8D40621D58C382D690C8AC2863A7`.
+        assert_eq!(decode_ac13(0x1838), Some(38000));
+        assert_eq!(decode_ac12(0xC38), Some(38000));
+        assert_eq!(decode_ac13(0), None);
+    }
+
+    #[test]
+    fn decoding_gillham_altitudes() {
+        // C4 alone is the lowest valid code.
+        assert_eq!(gillham_to_altitude(0x0040), Some(-1200));
+        assert_eq!(gillham_to_altitude(0x0010), Some(-800));
+        assert_eq!(gillham_to_altitude(0x0240), Some(700));
+        assert_eq!(gillham_to_altitude(0x0001), None);
+        assert_eq!(gillham_to_altitude(0x0000), None);
+    }
+}
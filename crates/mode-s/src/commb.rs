@@ -0,0 +1,619 @@
+//! Comm-B registers of Enhanced Surveillance, carried in the MB field of DF20 and DF21 replies.
+//!
+//! The replies do not tell which register (BDS) was requested by the interrogator, so it has to
+//! be inferred from the content. Every register has reserved bits and status bits which must be
+//! consistent with the values, and the values must be within the physical limits. A register is
+//! only decoded if exactly one candidate passes these checks.
+
+use crate::Icao;
+use crate::adsb::CALLSIGN_CHARSET;
+use crate::altitude::{FEET_PER_METER, decode_ac13};
+use crate::frame::field;
+
+/// Comm-B Data Selector, written as two hexadecimal digits, e.g. `Bds(0x40)` for BDS 4,0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Bds(pub u8);
+
+impl std::fmt::Display for Bds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:X},{:X}", self.0 >> 4, self.0 & 0xF)
+    }
+}
+
+/// Decoded Comm-B register.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CommB {
+    /// BDS 1,0.
+    DataLinkCapability(DataLinkCapability),
+    /// BDS 1,7, the registers the transponder supports.
+    CommonUsageCapability(Vec<Bds>),
+    /// BDS 2,0, callsign with the trailing spaces removed.
+    Identification(String),
+    /// BDS 3,0.
+    ResolutionAdvisory(ResolutionAdvisory),
+    /// BDS 4,0.
+    SelectedVerticalIntention(SelectedVerticalIntention),
+    /// BDS 5,0.
+    TrackAndTurn(TrackAndTurn),
+    /// BDS 6,0.
+    HeadingAndSpeed(HeadingAndSpeed),
+}
+
+impl CommB {
+    /// Infer the register and decode it. `altitude` is the barometric altitude of the same
+    /// reply, which helps telling registers apart. Returns `None` if no register or more than
+    /// one match.
+    pub fn infer(mb: [u8; 7], altitude: Option<i32>) -> Option<Self> {
+        let mut candidates = candidates(mb, altitude);
+        if candidates.len() != 1 {
+            return None;
+        }
+        candidates.pop()
+    }
+
+    /// Decode the MB field as a known register, if it is valid as such.
+    pub fn decode(bds: Bds, mb: [u8; 7], altitude: Option<i32>) -> Option<Self> {
+        if mb == [0; 7] {
+            return None;
+        }
+
+        match bds.0 {
+            0x10 => DataLinkCapability::decode(&mb).map(CommB::DataLinkCapability),
+            0x17 => decode_capabilities(&mb).map(CommB::CommonUsageCapability),
+            0x20 => decode_identification(&mb).map(CommB::Identification),
+            0x30 => ResolutionAdvisory::decode(&mb).map(CommB::ResolutionAdvisory),
+            0x40 => SelectedVerticalIntention::decode(&mb).map(CommB::SelectedVerticalIntention),
+            0x50 => TrackAndTurn::decode(&mb).map(CommB::TrackAndTurn),
+            0x60 => HeadingAndSpeed::decode(&mb, altitude).map(CommB::HeadingAndSpeed),
+            _ => None,
+        }
+    }
+
+    pub fn bds(&self) -> Bds {
+        Bds(match self {
+            CommB::DataLinkCapability(_) => 0x10,
+            CommB::CommonUsageCapability(_) => 0x17,
+            CommB::Identification(_) => 0x20,
+            CommB::ResolutionAdvisory(_) => 0x30,
+            CommB::SelectedVerticalIntention(_) => 0x40,
+            CommB::TrackAndTurn(_) => 0x50,
+            CommB::HeadingAndSpeed(_) => 0x60,
+        })
+    }
+}
+
+/// Every register the MB field is valid as.
+pub fn candidates(mb: [u8; 7], altitude: Option<i32>) -> Vec<CommB> {
+    [0x10, 0x17, 0x20, 0x30, 0x40, 0x50, 0x60]
+        .into_iter()
+        .filter_map(|bds| CommB::decode(Bds(bds), mb, altitude))
+        .collect()
+}
+
+/// Checks that a value is zero when its status bit is not set.
+fn status_consistent(data: &[u8], status: usize, first: usize, last: usize) -> bool {
+    field(data, status, status) != 0 || field(data, first, last) == 0
+}
+
+/// Value of a status bit followed by the value bits, if the status bit is set.
+fn with_status(data: &[u8], status: usize, last: usize) -> Option<u32> {
+    (field(data, status, status) != 0).then(|| field(data, status + 1, last))
+}
+
+/// Two's complement value, where `bits` includes the sign bit.
+fn signed(value: u32, bits: u32) -> i32 {
+    if value & (1 << (bits - 1)) != 0 {
+        value as i32 - (1 << bits)
+    } else {
+        value as i32
+    }
+}
+
+/// Data link capability report, BDS 1,0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DataLinkCapability {
+    /// Mode S subnetwork version number, zero if not available.
+    pub subnetwork_version: u8,
+    pub enhanced_protocol: bool,
+    pub specific_services: bool,
+    /// The aircraft identification can be read from BDS 2,0.
+    pub identification_capability: bool,
+    pub squitter_capability: bool,
+    pub surveillance_identifier_code: bool,
+    pub common_usage_capability: bool,
+}
+
+impl DataLinkCapability {
+    fn decode(data: &[u8]) -> Option<Self> {
+        if field(data, 1, 8) != 0x10 || field(data, 10, 14) != 0 {
+            return None;
+        }
+
+        // Version numbers above 4 require the overlay capability.
+        let subnetwork_version = field(data, 17, 23) as u8;
+        let overlay = field(data, 15, 15) != 0;
+        if overlay != (subnetwork_version >= 5) {
+            return None;
+        }
+
+        Some(Self {
+            subnetwork_version,
+            enhanced_protocol: field(data, 24, 24) != 0,
+            specific_services: field(data, 25, 25) != 0,
+            identification_capability: field(data, 33, 33) != 0,
+            squitter_capability: field(data, 34, 34) != 0,
+            surveillance_identifier_code: field(data, 35, 35) != 0,
+            common_usage_capability: field(data, 36, 36) != 0,
+        })
+    }
+}
+
+/// Registers announced by each of the first 24 bits of BDS 1,7.
+const COMMON_USAGE_REGISTERS: [u8; 24] = [
+    0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x20, 0x21, 0x40, 0x41, 0x42, 0x43, 0x44, 0x45, 0x48, 0x50,
+    0x51, 0x52, 0x53, 0x54, 0x55, 0x56, 0x5F, 0x60,
+];
+
+fn decode_capabilities(data: &[u8]) -> Option<Vec<Bds>> {
+    if field(data, 25, 56) != 0 {
+        return None;
+    }
+
+    let registers: Vec<_> = COMMON_USAGE_REGISTERS
+        .iter()
+        .enumerate()
+        .filter(|(bit, _)| field(data, bit + 1, bit + 1) != 0)
+        .map(|(_, bds)| Bds(*bds))
+        .collect();
+
+    // Every transponder supporting the register supports the identification.
+    registers.contains(&Bds(0x20)).then_some(registers)
+}
+
+fn decode_identification(data: &[u8]) -> Option<String> {
+    if field(data, 1, 8) != 0x20 {
+        return None;
+    }
+
+    let callsign = (0..8)
+        .map(|i| CALLSIGN_CHARSET[field(data, 9 + i * 6, 14 + i * 6) as usize] as char)
+        .collect::<String>();
+    if callsign.contains('#') {
+        return None;
+    }
+
+    Some(callsign.trim_end().to_string())
+}
+
+/// Aircraft which caused a resolution advisory.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Threat {
+    Address(Icao),
+    /// Threat without a Mode S transponder, located by the ACAS.
+    Position {
+        /// Barometric altitude in feet.
+        altitude: Option<i32>,
+        /// Range in nautical miles.
+        range: Option<f64>,
+        /// Bearing in degrees, clockwise from the aircraft's heading.
+        bearing: Option<f64>,
+    },
+}
+
+/// ACAS resolution advisory report, BDS 3,0.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ResolutionAdvisory {
+    /// Active resolution advisories, the 14 ARA bits.
+    pub active: u16,
+    /// Resolution advisory complements, the 4 RAC bits: do not pass below, do not pass above,
+    /// do not turn left and do not turn right.
+    pub complements: u8,
+    /// The advisory has just ended.
+    pub terminated: bool,
+    /// More than one threat is being resolved.
+    pub multiple_threats: bool,
+    pub threat: Option<Threat>,
+}
+
+impl ResolutionAdvisory {
+    fn decode(data: &[u8]) -> Option<Self> {
+        if field(data, 1, 8) != 0x30 {
+            return None;
+        }
+
+        // The upper ARA values are reserved for ACAS III.
+        if field(data, 16, 22) >= 48 {
+            return None;
+        }
+
+        let threat = match field(data, 29, 30) {
+            0 => None,
+            1 => Some(Threat::Address(Icao(field(data, 31, 54)))),
+            2 => {
+                let range = field(data, 44, 50);
+                let bearing = field(data, 51, 56);
+                Some(Threat::Position {
+                    altitude: decode_ac13(field(data, 31, 43)),
+                    range: (range != 0).then(|| (range - 1) as f64 / 10.0),
+                    bearing: (1..=60)
+                        .contains(&bearing)
+                        .then(|| (bearing - 1) as f64 * 6.0),
+                })
+            }
+            _ => return None,
+        };
+
+        Some(Self {
+            active: field(data, 9, 22) as u16,
+            complements: field(data, 23, 26) as u8,
+            terminated: field(data, 27, 27) != 0,
+            multiple_threats: field(data, 28, 28) != 0,
+            threat,
+        })
+    }
+
+    /// Short description of the advisory for a single threat, like the pilot hears it.
+    pub fn description(&self) -> &'static str {
+        if self.terminated {
+            return "Clear of conflict";
+        }
+        if self.active & (1 << 13) == 0 {
+            return if self.multiple_threats {
+                "Multiple threats"
+            } else {
+                "No advisory"
+            };
+        }
+
+        let corrective = self.active & (1 << 12) != 0;
+        let downward = self.active & (1 << 11) != 0;
+        let increased_rate = self.active & (1 << 10) != 0;
+        let reversal = self.active & (1 << 9) != 0;
+        let crossing = self.active & (1 << 8) != 0;
+
+        match (corrective, downward) {
+            _ if reversal && downward => "Descend, descend now",
+            _ if reversal => "Climb, climb now",
+            _ if increased_rate && downward => "Increase descent",
+            _ if increased_rate => "Increase climb",
+            (true, true) if crossing => "Descend, crossing descend",
+            (true, false) if crossing => "Climb, crossing climb",
+            (true, true) => "Descend",
+            (true, false) => "Climb",
+            (false, true) => "Don't climb",
+            (false, false) => "Don't descend",
+        }
+    }
+}
+
+/// Source of the target altitude in BDS 4,0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TargetAltitudeSource {
+    Unknown,
+    Aircraft,
+    Mcp,
+    Fms,
+}
+
+/// Autopilot modes, as reported by BDS 4,0 and by the target state and status message.
+/// Modes which are not reported are `None`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NavigationModes {
+    pub autopilot: Option<bool>,
+    pub vnav: Option<bool>,
+    pub altitude_hold: Option<bool>,
+    pub approach: Option<bool>,
+    pub lnav: Option<bool>,
+    pub tcas: Option<bool>,
+}
+
+/// Selected vertical intention, BDS 4,0.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SelectedVerticalIntention {
+    /// Altitude selected on the MCP or FCU, in feet.
+    pub mcp_altitude: Option<i32>,
+    /// Altitude selected in the FMS, in feet.
+    pub fms_altitude: Option<i32>,
+    /// Barometric pressure setting in hPa.
+    pub qnh: Option<f64>,
+    pub modes: NavigationModes,
+    pub target_source: Option<TargetAltitudeSource>,
+}
+
+impl SelectedVerticalIntention {
+    fn decode(data: &[u8]) -> Option<Self> {
+        let consistent = status_consistent(data, 1, 2, 13)
+            && status_consistent(data, 14, 15, 26)
+            && status_consistent(data, 27, 28, 39)
+            && status_consistent(data, 48, 49, 51)
+            && status_consistent(data, 54, 55, 56);
+        if !consistent || field(data, 40, 47) != 0 || field(data, 52, 53) != 0 {
+            return None;
+        }
+
+        let mcp_altitude = with_status(data, 1, 13).map(|altitude| altitude as i32 * 16);
+        let fms_altitude = with_status(data, 14, 26).map(|altitude| altitude as i32 * 16);
+        let qnh = with_status(data, 27, 39).map(|qnh| qnh as f64 * 0.1 + 800.0);
+
+        let plausible_altitude = |altitude: Option<i32>| altitude.is_none_or(|a| a <= 50000);
+        if !plausible_altitude(mcp_altitude)
+            || !plausible_altitude(fms_altitude)
+            || qnh.is_some_and(|qnh| !(900.0..=1100.0).contains(&qnh))
+        {
+            return None;
+        }
+
+        let modes = match with_status(data, 48, 51) {
+            Some(modes) => NavigationModes {
+                vnav: Some(modes & 0b100 != 0),
+                altitude_hold: Some(modes & 0b010 != 0),
+                approach: Some(modes & 0b001 != 0),
+                ..Default::default()
+            },
+            None => NavigationModes::default(),
+        };
+
+        let target_source = with_status(data, 54, 56).map(|source| match source {
+            1 => TargetAltitudeSource::Aircraft,
+            2 => TargetAltitudeSource::Mcp,
+            3 => TargetAltitudeSource::Fms,
+            _ => TargetAltitudeSource::Unknown,
+        });
+
+        Some(Self {
+            mcp_altitude,
+            fms_altitude,
+            qnh,
+            modes,
+            target_source,
+        })
+    }
+}
+
+/// Track and turn report, BDS 5,0.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TrackAndTurn {
+    /// Roll angle in degrees, positive when the right wing is down.
+    pub roll: Option<f64>,
+    /// Track over ground in degrees, clockwise from the true north.
+    pub track: Option<f64>,
+    /// Ground speed in knots.
+    pub ground_speed: Option<f64>,
+    /// Rate of the track change in degrees per second, positive when turning right.
+    pub track_rate: Option<f64>,
+    /// True airspeed in knots.
+    pub true_airspeed: Option<f64>,
+}
+
+impl TrackAndTurn {
+    fn decode(data: &[u8]) -> Option<Self> {
+        let consistent = status_consistent(data, 1, 2, 11)
+            && status_consistent(data, 12, 13, 23)
+            && status_consistent(data, 24, 25, 34)
+            && status_consistent(data, 35, 36, 45)
+            && status_consistent(data, 46, 47, 56);
+        if !consistent {
+            return None;
+        }
+
+        let roll = with_status(data, 1, 11).map(|roll| signed(roll, 10) as f64 * 45.0 / 256.0);
+        let track = with_status(data, 12, 23)
+            .map(|track| (signed(track, 11) as f64 * 90.0 / 512.0).rem_euclid(360.0));
+        let ground_speed = with_status(data, 24, 34).map(|speed| speed as f64 * 2.0);
+        let track_rate =
+            with_status(data, 35, 45).map(|rate| signed(rate, 10) as f64 * 8.0 / 256.0);
+        let true_airspeed = with_status(data, 46, 56).map(|speed| speed as f64 * 2.0);
+
+        if roll.is_some_and(|roll| roll.abs() > 50.0)
+            || ground_speed.is_some_and(|speed| speed > 600.0)
+            || true_airspeed.is_some_and(|speed| speed > 500.0)
+        {
+            return None;
+        }
+        if let (Some(ground_speed), Some(true_airspeed)) = (ground_speed, true_airspeed) {
+            // Even the strongest jet streams do not make more of a difference.
+            if (ground_speed - true_airspeed).abs() > 200.0 {
+                return None;
+            }
+        }
+
+        Some(Self {
+            roll,
+            track,
+            ground_speed,
+            track_rate,
+            true_airspeed,
+        })
+    }
+}
+
+/// Heading and speed report, BDS 6,0.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HeadingAndSpeed {
+    /// Heading in degrees, clockwise from the magnetic north.
+    pub heading: Option<f64>,
+    /// Indicated airspeed in knots.
+    pub indicated_airspeed: Option<f64>,
+    pub mach: Option<f64>,
+    /// Barometric vertical rate in feet per minute.
+    pub barometric_vertical_rate: Option<i32>,
+    /// Inertial vertical rate in feet per minute.
+    pub inertial_vertical_rate: Option<i32>,
+}
+
+impl HeadingAndSpeed {
+    fn decode(data: &[u8], altitude: Option<i32>) -> Option<Self> {
+        let consistent = status_consistent(data, 1, 2, 12)
+            && status_consistent(data, 13, 14, 23)
+            && status_consistent(data, 24, 25, 34)
+            && status_consistent(data, 35, 36, 45)
+            && status_consistent(data, 46, 47, 56);
+        if !consistent {
+            return None;
+        }
+
+        let heading = with_status(data, 1, 12)
+            .map(|heading| (signed(heading, 11) as f64 * 90.0 / 512.0).rem_euclid(360.0));
+        let indicated_airspeed = with_status(data, 13, 23).map(|speed| speed as f64);
+        let mach = with_status(data, 24, 34).map(|mach| mach as f64 * 2.048 / 512.0);
+        let barometric_vertical_rate = with_status(data, 35, 45).map(|rate| signed(rate, 10) * 32);
+        let inertial_vertical_rate = with_status(data, 46, 56).map(|rate| signed(rate, 10) * 32);
+
+        if indicated_airspeed.is_some_and(|speed| speed == 0.0 || speed > 500.0)
+            || mach.is_some_and(|mach| mach == 0.0 || mach > 1.0)
+            || barometric_vertical_rate.is_some_and(|rate| rate.abs() > 6000)
+            || inertial_vertical_rate.is_some_and(|rate| rate.abs() > 6000)
+        {
+            return None;
+        }
+
+        // The airspeed and Mach number must agree at the altitude of the aircraft.
+        if let (Some(speed), Some(mach), Some(altitude)) = (indicated_airspeed, mach, altitude) {
+            if (mach_to_cas(mach, altitude) - speed).abs() > 30.0 {
+                return None;
+            }
+        }
+
+        Some(Self {
+            heading,
+            indicated_airspeed,
+            mach,
+            barometric_vertical_rate,
+            inertial_vertical_rate,
+        })
+    }
+}
+
+/// Calibrated airspeed in knots of the given Mach number at a pressure altitude in feet, in the
+/// International Standard Atmosphere.
+fn mach_to_cas(mach: f64, altitude: i32) -> f64 {
+    const SEA_LEVEL_PRESSURE: f64 = 101_325.0;
+    const SEA_LEVEL_TEMPERATURE: f64 = 288.15;
+    const SEA_LEVEL_SPEED_OF_SOUND: f64 = 340.294;
+    const TROPOPAUSE: f64 = 11_000.0;
+    const METERS_PER_SECOND_PER_KNOT: f64 = 0.514_444;
+
+    let height = altitude as f64 / FEET_PER_METER;
+    let pressure = if height < TROPOPAUSE {
+        SEA_LEVEL_PRESSURE * (1.0 - 0.0065 * height / SEA_LEVEL_TEMPERATURE).powf(5.255_88)
+    } else {
+        22_632.0 * (-0.000_157_688 * (height - TROPOPAUSE)).exp()
+    };
+
+    let impact_pressure = pressure * ((1.0 + 0.2 * mach * mach).powf(3.5) - 1.0);
+    let cas = SEA_LEVEL_SPEED_OF_SOUND
+        * (5.0 * ((impact_pressure / SEA_LEVEL_PRESSURE + 1.0).powf(2.0 / 7.0) - 1.0)).sqrt();
+    cas / METERS_PER_SECOND_PER_KNOT
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Frame, Message};
+
+    /// MB field and altitude of a DF20 reply.
+    fn mb(hex: &str) -> ([u8; 7], Option<i32>) {
+        match Message::decode(&Frame::from_hex(hex).unwrap()) {
+            Message::CommBAltitude { mb, altitude, .. } => (mb, altitude),
+            message => panic!("not a DF20 reply: {message:?}"),
+        }
+    }
+
+    fn infer(hex: &str) -> Option<CommB> {
+        let (mb, altitude) = mb(hex);
+        CommB::infer(mb, altitude)
+    }
+
+    #[test]
+    fn inferring_identification() {
+        assert_eq!(
+            infer("A000083E202CC371C31DE0AA1CCF"),
+            Some(CommB::Identification("KLM1017".to_string()))
+        );
+    }
+
+    #[test]
+    fn inferring_selected_vertical_intention() {
+        let Some(CommB::SelectedVerticalIntention(intention)) =
+            infer("A000029C85E42F313000007047D3")
+        else {
+            panic!("not BDS 4,0");
+        };
+        assert_eq!(intention.mcp_altitude, Some(3008));
+        assert_eq!(intention.fms_altitude, Some(3008));
+        assert!((intention.qnh.unwrap() - 1020.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn inferring_track_and_turn() {
+        let Some(CommB::TrackAndTurn(report)) = infer("A000139381951536E024D4CCF6B5") else {
+            panic!("not BDS 5,0");
+        };
+        assert!((report.roll.unwrap() - 2.1).abs() < 0.01);
+        assert!((report.track.unwrap() - 114.258).abs() < 0.01);
+        assert_eq!(report.ground_speed, Some(438.0));
+        assert_eq!(report.track_rate, Some(0.125));
+        assert_eq!(report.true_airspeed, Some(424.0));
+    }
+
+    #[test]
+    fn inferring_heading_and_speed() {
+        let Some(CommB::HeadingAndSpeed(report)) = infer("A00004128F39F91A7E27C46ADC21") else {
+            panic!("not BDS 6,0");
+        };
+        assert!((report.heading.unwrap() - 42.715).abs() < 0.01);
+        assert_eq!(report.indicated_airspeed, Some(252.0));
+        assert!((report.mach.unwrap() - 0.42).abs() < 0.001);
+        assert_eq!(report.barometric_vertical_rate, Some(-1920));
+        assert_eq!(report.inertial_vertical_rate, Some(-1920));
+    }
+
+    #[test]
+    fn decoding_capabilities() {
+        let (mb, altitude) = mb("A0000638FA81C10000000081A92F");
+        let Some(CommB::CommonUsageCapability(registers)) = CommB::decode(Bds(0x17), mb, altitude)
+        else {
+            panic!("not BDS 1,7");
+        };
+        assert!(registers.contains(&Bds(0x20)));
+        assert!(registers.contains(&Bds(0x40)));
+        assert!(registers.contains(&Bds(0x60)));
+        assert_eq!(Bds(0x5F).to_string(), "5,F");
+    }
+
+    /// Set bits `first` to `last`, numbered from 1.
+    fn set(data: &mut [u8], first: usize, last: usize, value: u32) {
+        for bit in first..=last {
+            let set = (value >> (last - bit)) & 1;
+            data[(bit - 1) / 8] |= (set as u8) << (7 - (bit - 1) % 8);
+        }
+    }
+
+    #[test]
+    fn decoding_resolution_advisory() {
+        // Corrective "Climb" against a threat identified by its address.
+        let mut mb = [0; 7];
+        set(&mut mb, 1, 8, 0x30);
+        set(&mut mb, 9, 10, 0b11);
+        set(&mut mb, 29, 30, 1);
+        set(&mut mb, 31, 54, 0x4CA2D5);
+
+        let Some(CommB::ResolutionAdvisory(ra)) = CommB::decode(Bds(0x30), mb, None) else {
+            panic!("not BDS 3,0");
+        };
+        assert_eq!(ra.threat, Some(Threat::Address(Icao(0x4CA2D5))));
+        assert_eq!(ra.description(), "Climb");
+        assert!(!ra.terminated);
+
+        set(&mut mb, 27, 27, 1);
+        let Some(CommB::ResolutionAdvisory(ra)) = CommB::decode(Bds(0x30), mb, None) else {
+            panic!("not BDS 3,0");
+        };
+        assert_eq!(ra.description(), "Clear of conflict");
+    }
+
+    #[test]
+    fn rejecting_empty_registers() {
+        assert_eq!(CommB::infer([0; 7], None), None);
+    }
+}
@@ -0,0 +1,486 @@
+//! Compact Position Reporting (CPR) decoding.
+//!
+//! Positions are broadcast as 17-bit fractions of latitude and longitude zones, alternating
+//! between even and odd zone sizes. A pair of even and odd frames decodes into a globally
+//! unambiguous position, while a single frame can be decoded relative to a nearby reference.
+
+use crate::Position;
+use crate::adsb::{CprFormat, CprFrame};
+use crate::geo::{METERS_PER_NAUTICAL_MILE, distance, lat_lon};
+use std::time::{Duration, Instant};
+
+/// Number of latitude zones between the equator and a pole.
+const NZ: f64 = 15.0;
+
+/// 2^17, the resolution of encoded latitudes and longitudes.
+const SCALE: f64 = 131072.0;
+
+/// Number of longitude zones at the given latitude.
+fn nl(lat: f64) -> u32 {
+    let lat = lat.abs();
+    if lat == 0.0 {
+        return 59;
+    } else if lat == 87.0 {
+        return 2;
+    } else if lat > 87.0 {
+        return 1;
+    }
+
+    let a = 1.0 - (std::f64::consts::PI / (2.0 * NZ)).cos();
+    let b = lat.to_radians().cos().powi(2);
+    (2.0 * std::f64::consts::PI / (1.0 - a / b).acos()).floor() as u32
+}
+
+/// Span of the whole latitude range the encoding covers: the full circle for airborne
+/// positions, and a quarter of it for surface positions which have four times the resolution.
+fn span(surface: bool) -> f64 {
+    if surface { 90.0 } else { 360.0 }
+}
+
+fn odd(format: CprFormat) -> u32 {
+    match format {
+        CprFormat::Even => 0,
+        CprFormat::Odd => 1,
+    }
+}
+
+/// Decode a pair of even and odd airborne frames. `latest` tells which of them was received
+/// last, as the position is reported for that one.
+pub fn decode_global_airborne(
+    even: CprFrame,
+    odd: CprFrame,
+    latest: CprFormat,
+) -> Option<Position> {
+    let (lat_even, lat_odd) = global_latitudes(even, odd, false)?;
+    let (lat_even, lat_odd) = (wrap_latitude(lat_even), wrap_latitude(lat_odd));
+    if !(-90.0..=90.0).contains(&lat_even) || !(-90.0..=90.0).contains(&lat_odd) {
+        return None;
+    }
+
+    let lat = match latest {
+        CprFormat::Even => lat_even,
+        CprFormat::Odd => lat_odd,
+    };
+    let lon = global_longitude(even, odd, latest, lat, false);
+
+    Some(lat_lon(lat, wrap_longitude(lon)))
+}
+
+/// Decode a pair of even and odd surface frames. The encoding is ambiguous, so the candidate
+/// closest to `reference` (usually the receiver) is chosen.
+pub fn decode_global_surface(
+    even: CprFrame,
+    odd: CprFrame,
+    latest: CprFormat,
+    reference: Position,
+) -> Option<Position> {
+    let (lat_even, lat_odd) = global_latitudes(even, odd, true)?;
+
+    let lat = match latest {
+        CprFormat::Even => lat_even,
+        CprFormat::Odd => lat_odd,
+    };
+
+    // The northern latitude is decoded, the southern one is 90 degrees below.
+    let lat = [lat, lat - 90.0].into_iter().min_by(|a, b| {
+        (a - reference.y())
+            .abs()
+            .total_cmp(&(b - reference.y()).abs())
+    })?;
+
+    let lon = global_longitude(even, odd, latest, lat, true);
+
+    // Longitude repeats every 90 degrees.
+    let lon = (0..4)
+        .map(|quadrant| wrap_longitude(lon + 90.0 * quadrant as f64))
+        .min_by(|a, b| {
+            longitude_difference(*a, reference.x())
+                .total_cmp(&longitude_difference(*b, reference.x()))
+        })?;
+
+    Some(lat_lon(lat, lon))
+}
+
+fn global_latitudes(even: CprFrame, odd: CprFrame, surface: bool) -> Option<(f64, f64)> {
+    let span = span(surface);
+    let d_lat_even = span / (4.0 * NZ);
+    let d_lat_odd = span / (4.0 * NZ - 1.0);
+
+    let lat_even_cpr = even.lat as f64 / SCALE;
+    let lat_odd_cpr = odd.lat as f64 / SCALE;
+
+    let j = (59.0 * lat_even_cpr - 60.0 * lat_odd_cpr + 0.5).floor();
+
+    let lat_even = d_lat_even * (j.rem_euclid(60.0) + lat_even_cpr);
+    let lat_odd = d_lat_odd * (j.rem_euclid(59.0) + lat_odd_cpr);
+
+    // Both frames must come from the same longitude zone.
+    (nl(wrap_latitude(lat_even)) == nl(wrap_latitude(lat_odd))).then_some((lat_even, lat_odd))
+}
+
+fn global_longitude(
+    even: CprFrame,
+    odd: CprFrame,
+    latest: CprFormat,
+    lat: f64,
+    surface: bool,
+) -> f64 {
+    let nl = nl(lat) as f64;
+    let lon_even_cpr = even.lon as f64 / SCALE;
+    let lon_odd_cpr = odd.lon as f64 / SCALE;
+
+    let m = (lon_even_cpr * (nl - 1.0) - lon_odd_cpr * nl + 0.5).floor();
+
+    let (zones, lon_cpr) = match latest {
+        CprFormat::Even => (nl.max(1.0), lon_even_cpr),
+        CprFormat::Odd => ((nl - 1.0).max(1.0), lon_odd_cpr),
+    };
+
+    span(surface) / zones * (m.rem_euclid(zones) + lon_cpr)
+}
+
+/// Decode a single frame relative to a reference position. The result is only correct if the
+/// aircraft is within half a zone (roughly 180 NM airborne, 45 NM on the surface) of the
+/// reference.
+pub fn decode_local(frame: CprFrame, surface: bool, reference: Position) -> Position {
+    let span = span(surface);
+    let i = odd(frame.format) as f64;
+
+    let d_lat = span / (4.0 * NZ - i);
+    let lat_cpr = frame.lat as f64 / SCALE;
+    let j = (reference.y() / d_lat).floor()
+        + (0.5 + reference.y().rem_euclid(d_lat) / d_lat - lat_cpr).floor();
+    let lat = d_lat * (j + lat_cpr);
+
+    let d_lon = span / (nl(lat) as f64 - i).max(1.0);
+    let lon_cpr = frame.lon as f64 / SCALE;
+    let m = (reference.x() / d_lon).floor()
+        + (0.5 + reference.x().rem_euclid(d_lon) / d_lon - lon_cpr).floor();
+    let lon = d_lon * (m + lon_cpr);
+
+    lat_lon(lat, wrap_longitude(lon))
+}
+
+fn wrap_latitude(lat: f64) -> f64 {
+    if lat >= 270.0 { lat - 360.0 } else { lat }
+}
+
+fn wrap_longitude(lon: f64) -> f64 {
+    (lon + 180.0).rem_euclid(360.0) - 180.0
+}
+
+fn longitude_difference(a: f64, b: f64) -> f64 {
+    let difference = (a - b).rem_euclid(360.0);
+    difference.min(360.0 - difference)
+}
+
+/// Maximum time between an even and an odd frame for them to be decoded together.
+const AIRBORNE_PAIR_AGE: Duration = Duration::from_secs(10);
+const SURFACE_PAIR_AGE: Duration = Duration::from_secs(25);
+
+/// How long the last position is trusted as a reference for local decoding.
+const REFERENCE_AGE: Duration = Duration::from_secs(300);
+
+/// Maximum distance from the receiver at which local decoding is unambiguous.
+const AIRBORNE_RECEIVER_RANGE: f64 = 180.0 * METERS_PER_NAUTICAL_MILE;
+const SURFACE_RECEIVER_RANGE: f64 = 45.0 * METERS_PER_NAUTICAL_MILE;
+
+/// Speeds (in knots) above which a new fix is considered unreasonable.
+const AIRBORNE_MAX_SPEED: f64 = 1200.0;
+const SURFACE_MAX_SPEED: f64 = 150.0;
+
+/// Extra distance allowed on top of the maximum speed, to account for the CPR resolution and
+/// the fact that positions are not sent at the exact time they are received.
+const REASONABLENESS_MARGIN: f64 = 0.5 * METERS_PER_NAUTICAL_MILE;
+
+/// Per-aircraft CPR decoding state. It remembers the last even and odd frames and the last
+/// position, so each new frame decodes into a position as soon as possible.
+#[derive(Clone, Debug, Default)]
+pub struct CprDecoder {
+    even: Option<(CprFrame, Instant)>,
+    odd: Option<(CprFrame, Instant)>,
+    surface: bool,
+    last: Option<(Position, Instant)>,
+}
+
+impl CprDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Last position decoded, and when it was received.
+    pub fn position(&self) -> Option<(Position, Instant)> {
+        self.last
+    }
+
+    /// Feed a new frame, received at `time`. `receiver` is the position of the receiver, used
+    /// to resolve surface positions and as the reference when there is no previous fix.
+    ///
+    /// Returns the new position, or `None` if it could not be decoded yet or failed the
+    /// reasonableness check against the previous fix.
+    pub fn decode(
+        &mut self,
+        frame: CprFrame,
+        surface: bool,
+        time: Instant,
+        receiver: Option<Position>,
+    ) -> Option<Position> {
+        if surface != self.surface {
+            // Airborne and surface frames can not be paired together.
+            self.even = None;
+            self.odd = None;
+            self.surface = surface;
+        }
+
+        match frame.format {
+            CprFormat::Even => self.even = Some((frame, time)),
+            CprFormat::Odd => self.odd = Some((frame, time)),
+        }
+
+        let reference = self
+            .last
+            .filter(|(_, last_time)| time.saturating_duration_since(*last_time) < REFERENCE_AGE)
+            .map(|(position, _)| position);
+
+        let position = self
+            .decode_global(frame.format, time, reference.or(receiver))
+            .or_else(|| reference.map(|reference| decode_local(frame, surface, reference)))
+            .or_else(|| self.decode_relative_to_receiver(frame, receiver))?;
+
+        if !self.is_reasonable(position, time) {
+            // One of the frames is probably corrupted. Start over.
+            self.even = None;
+            self.odd = None;
+            return None;
+        }
+
+        self.last = Some((position, time));
+        Some(position)
+    }
+
+    fn decode_global(
+        &self,
+        latest: CprFormat,
+        time: Instant,
+        reference: Option<Position>,
+    ) -> Option<Position> {
+        let ((even, even_time), (odd, odd_time)) = (self.even?, self.odd?);
+
+        let max_age = if self.surface {
+            SURFACE_PAIR_AGE
+        } else {
+            AIRBORNE_PAIR_AGE
+        };
+        if even_time
+            .max(odd_time)
+            .duration_since(even_time.min(odd_time))
+            > max_age
+            || time.saturating_duration_since(even_time.min(odd_time)) > max_age
+        {
+            return None;
+        }
+
+        if self.surface {
+            decode_global_surface(even, odd, latest, reference?)
+        } else {
+            decode_global_airborne(even, odd, latest)
+        }
+    }
+
+    fn decode_relative_to_receiver(
+        &self,
+        frame: CprFrame,
+        receiver: Option<Position>,
+    ) -> Option<Position> {
+        let receiver = receiver?;
+        let range = if self.surface {
+            SURFACE_RECEIVER_RANGE
+        } else {
+            AIRBORNE_RECEIVER_RANGE
+        };
+
+        let position = decode_local(frame, self.surface, receiver);
+        (distance(position, receiver) < range).then_some(position)
+    }
+
+    /// Check whether the aircraft could have moved from the last fix to `position` in time.
+    fn is_reasonable(&self, position: Position, time: Instant) -> bool {
+        let Some((last, last_time)) = self.last else {
+            return true;
+        };
+
+        let max_speed = if self.surface {
+            SURFACE_MAX_SPEED
+        } else {
+            AIRBORNE_MAX_SPEED
+        };
+
+        let elapsed = time.saturating_duration_since(last_time).as_secs_f64();
+        let max_distance =
+            max_speed * METERS_PER_NAUTICAL_MILE / 3600.0 * elapsed + REASONABLENESS_MARGIN;
+
+        distance(last, position) <= max_distance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Frame, Message, adsb::Me};
+
+    fn cpr(hex: &str) -> CprFrame {
+        match Message::decode(&Frame::from_hex(hex).unwrap()) {
+            Message::ExtendedSquitter {
+                me: Me::AirbornePosition(position),
+                ..
+            } => position.cpr,
+            Message::ExtendedSquitter {
+                me: Me::SurfacePosition(position),
+                ..
+            } => position.cpr,
+            other => panic!("not a position: {other:?}"),
+        }
+    }
+
+    /// Encode a position, the way a transponder would.
+    fn encode(position: Position, format: CprFormat, surface: bool) -> CprFrame {
+        let span = span(surface);
+        let i = odd(format) as f64;
+
+        let d_lat = span / (4.0 * NZ - i);
+        let yz = (SCALE * position.y().rem_euclid(d_lat) / d_lat + 0.5).floor();
+        let r_lat = d_lat * (yz / SCALE + (position.y() / d_lat).floor());
+
+        let d_lon = span / (nl(r_lat) as f64 - i).max(1.0);
+        let xz = (SCALE * position.x().rem_euclid(d_lon) / d_lon + 0.5).floor();
+
+        CprFrame {
+            format,
+            lat: yz as u32 & 0x1FFFF,
+            lon: xz as u32 & 0x1FFFF,
+        }
+    }
+
+    fn assert_near(position: Position, lat: f64, lon: f64) {
+        assert!(
+            (position.y() - lat).abs() < 1e-4 && (position.x() - lon).abs() < 1e-4,
+            "{position:?} is not near {lat} {lon}"
+        );
+    }
+
+    #[test]
+    fn number_of_longitude_zones() {
+        assert_eq!(nl(0.0), 59);
+        assert_eq!(nl(52.0), 36);
+        assert_eq!(nl(-52.0), 36);
+        assert_eq!(nl(87.0), 2);
+        assert_eq!(nl(89.0), 1);
+    }
+
+    #[test]
+    fn global_airborne_decoding() {
+        let even = cpr("8D40621D58C382D690C8AC2863A7");
+        let odd = cpr("8D40621D58C386435CC412692AD6");
+
+        let position = decode_global_airborne(even, odd, CprFormat::Even).unwrap();
+        assert_near(position, 52.25720, 3.91937);
+    }
+
+    #[test]
+    fn local_decoding() {
+        let odd = cpr("8D40621D58C386435CC412692AD6");
+        assert_near(
+            decode_local(odd, false, lat_lon(52.258, 3.918)),
+            52.26578,
+            3.93891,
+        );
+
+        let surface = cpr("8C4841753A9A153237AEF0F275BE");
+        assert_near(
+            decode_local(surface, true, lat_lon(51.990, 4.375)),
+            52.32056,
+            4.73574,
+        );
+    }
+
+    #[test]
+    fn global_surface_decoding_in_every_quadrant() {
+        for (lat, lon) in [
+            (52.32, 4.73),
+            (-33.94, 151.17),
+            (40.64, -73.78),
+            (-22.81, -43.25),
+        ] {
+            let position = lat_lon(lat, lon);
+            let even = encode(position, CprFormat::Even, true);
+            let odd = encode(position, CprFormat::Odd, true);
+            let reference = lat_lon(lat + 0.5, lon - 0.5);
+
+            let decoded = decode_global_surface(even, odd, CprFormat::Odd, reference).unwrap();
+            assert!(distance(decoded, position) < 10.0, "{decoded:?}");
+        }
+    }
+
+    #[test]
+    fn decoder_pairs_frames_and_rejects_jumps() {
+        let start = Instant::now();
+        let mut decoder = CprDecoder::new();
+
+        let position = lat_lon(36.67, -4.49);
+        assert_eq!(
+            decoder.decode(encode(position, CprFormat::Even, false), false, start, None),
+            None
+        );
+
+        let decoded = decoder
+            .decode(
+                encode(position, CprFormat::Odd, false),
+                false,
+                start + Duration::from_secs(1),
+                None,
+            )
+            .unwrap();
+        assert!(distance(decoded, position) < 10.0);
+
+        // Next fix is decoded locally, relative to the previous one.
+        let moved = lat_lon(36.68, -4.49);
+        let decoded = decoder
+            .decode(
+                encode(moved, CprFormat::Even, false),
+                false,
+                start + Duration::from_secs(6),
+                None,
+            )
+            .unwrap();
+        assert!(distance(decoded, moved) < 10.0);
+
+        // A corrupted frame placing the aircraft 200 km away a second later is rejected.
+        let jump = lat_lon(38.5, -4.49);
+        assert_eq!(
+            decoder.decode(
+                encode(jump, CprFormat::Odd, false),
+                false,
+                start + Duration::from_secs(7),
+                Some(jump),
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn decoding_relative_to_receiver() {
+        let mut decoder = CprDecoder::new();
+        let position = lat_lon(36.9, -4.2);
+        let receiver = lat_lon(36.67, -4.49);
+
+        let decoded = decoder
+            .decode(
+                encode(position, CprFormat::Odd, false),
+                false,
+                Instant::now(),
+                Some(receiver),
+            )
+            .unwrap();
+        assert!(distance(decoded, position) < 10.0);
+    }
+}
@@ -0,0 +1,257 @@
+//! Mode S CRC-24 parity checking, address recovery and bit error correction.
+
+use crate::{DecodeError, Frame, Icao};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Generator polynomial of the Mode S parity, as defined in ICAO Annex 10.
+const GENERATOR: u32 = 0xFFF409;
+
+const TABLE: [u32; 256] = build_table();
+
+const fn build_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = (i as u32) << 16;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x800000 != 0 {
+                (crc << 1) ^ GENERATOR
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+        table[i] = crc & 0xFFFFFF;
+        i += 1;
+    }
+    table
+}
+
+/// Compute the CRC-24 of `data`.
+pub fn crc24(data: &[u8]) -> u32 {
+    data.iter().fold(0, |crc, &byte| {
+        ((crc << 8) ^ TABLE[(((crc >> 16) ^ byte as u32) & 0xFF) as usize]) & 0xFFFFFF
+    })
+}
+
+/// CRC of the frame's data bits XORed with its parity field. It is zero for intact DF17 and
+/// DF18 frames, and equal to the aircraft address for replies with an address/parity field.
+pub fn residual(frame: &Frame) -> u32 {
+    let bytes = frame.bytes();
+    crc24(&bytes[..bytes.len() - 3]) ^ frame.parity()
+}
+
+/// How many flipped bits [`check`] is allowed to repair.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ErrorCorrection {
+    /// Only accept intact frames.
+    #[default]
+    Disabled,
+    SingleBit,
+    /// Repair one or two flipped bits. Only long frames are corrected this way, as short
+    /// frames do not have enough redundancy to do it reliably.
+    TwoBits,
+}
+
+/// A frame which passed the parity check.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Checked {
+    /// The frame, with the bit errors repaired.
+    pub frame: Frame,
+    /// Address of the aircraft, either read from the AA field or recovered from the AP field.
+    pub icao: Icao,
+    /// Number of bits which were flipped to repair the frame.
+    pub corrected_bits: u8,
+}
+
+impl Checked {
+    /// Whether the frame had to be repaired to pass the parity check.
+    pub fn corrected(&self) -> bool {
+        self.corrected_bits > 0
+    }
+}
+
+/// Check the parity of a frame, optionally repairing it.
+///
+/// Replies which overlay the address with the parity (DF0, DF4, DF5, DF16, DF20 and DF21) can
+/// not be verified on their own. Their address is recovered from the parity, and it is up to
+/// the caller to only trust addresses which were already seen in verified frames.
+pub fn check(frame: &Frame, correction: ErrorCorrection) -> Result<Checked, DecodeError> {
+    let residual = residual(frame);
+
+    match frame.downlink_format() {
+        0 | 4 | 5 | 16 | 20 | 21 => Ok(Checked {
+            frame: *frame,
+            icao: Icao(residual),
+            corrected_bits: 0,
+        }),
+        // The low 7 bits of the DF11 parity are overlaid with the interrogator code.
+        11 if residual & 0xFFFF80 == 0 => Ok(checked_with_aa(*frame, 0)),
+        17 | 18 if residual == 0 => Ok(checked_with_aa(*frame, 0)),
+        11 | 17 | 18 => correct(frame, residual, correction)
+            .map(|(frame, bits)| checked_with_aa(frame, bits))
+            .ok_or(DecodeError::ParityMismatch(residual)),
+        df => Err(DecodeError::UnsupportedFormat(df)),
+    }
+}
+
+fn checked_with_aa(frame: Frame, corrected_bits: u8) -> Checked {
+    Checked {
+        icao: Icao(frame.field(9, 32)),
+        frame,
+        corrected_bits,
+    }
+}
+
+/// Positions of the flipped bits which produce a given syndrome.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct ErrorPattern {
+    bits: [usize; 2],
+    len: u8,
+}
+
+/// Try to repair the frame by looking up its syndrome.
+fn correct(frame: &Frame, syndrome: u32, correction: ErrorCorrection) -> Option<(Frame, u8)> {
+    let max_errors = match correction {
+        ErrorCorrection::Disabled => return None,
+        ErrorCorrection::SingleBit => 1,
+        ErrorCorrection::TwoBits if frame.bits() == 112 => 2,
+        ErrorCorrection::TwoBits => 1,
+    };
+
+    let pattern = syndromes(frame.bits()).get(&syndrome)?;
+    if pattern.len > max_errors {
+        return None;
+    }
+
+    let mut bytes = frame.bytes().to_vec();
+    for &bit in &pattern.bits[..pattern.len as usize] {
+        bytes[bit / 8] ^= 0x80 >> (bit % 8);
+    }
+
+    Some((Frame::new(&bytes).ok()?, pattern.len))
+}
+
+/// Syndrome table for frames of the given length, built on first use.
+fn syndromes(bits: usize) -> &'static HashMap<u32, ErrorPattern> {
+    static SHORT: OnceLock<HashMap<u32, ErrorPattern>> = OnceLock::new();
+    static LONG: OnceLock<HashMap<u32, ErrorPattern>> = OnceLock::new();
+
+    if bits == 112 {
+        LONG.get_or_init(|| build_syndromes(112, 2))
+    } else {
+        SHORT.get_or_init(|| build_syndromes(56, 1))
+    }
+}
+
+fn build_syndromes(bits: usize, max_errors: u8) -> HashMap<u32, ErrorPattern> {
+    let syndrome = |positions: &[usize]| {
+        let mut bytes = vec![0u8; bits / 8];
+        for &bit in positions {
+            bytes[bit / 8] ^= 0x80 >> (bit % 8);
+        }
+        let parity = crate::frame::field(&bytes, bits - 23, bits);
+        crc24(&bytes[..bytes.len() - 3]) ^ parity
+    };
+
+    let mut table = HashMap::new();
+    let mut ambiguous = Vec::new();
+    let mut insert = |syndrome: u32, pattern: ErrorPattern| {
+        if table.insert(syndrome, pattern).is_some() {
+            ambiguous.push(syndrome);
+        }
+    };
+
+    // The downlink format bits are never corrected, as flipping them would change the meaning
+    // (and possibly the length) of the whole frame.
+    for first in 5..bits {
+        insert(
+            syndrome(&[first]),
+            ErrorPattern {
+                bits: [first, 0],
+                len: 1,
+            },
+        );
+
+        if max_errors >= 2 {
+            for second in first + 1..bits {
+                insert(
+                    syndrome(&[first, second]),
+                    ErrorPattern {
+                        bits: [first, second],
+                        len: 2,
+                    },
+                );
+            }
+        }
+    }
+
+    for syndrome in ambiguous {
+        table.remove(&syndrome);
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(hex: &str) -> Frame {
+        Frame::from_hex(hex).unwrap()
+    }
+
+    fn flip(frame: &Frame, bits: &[usize]) -> Frame {
+        let mut bytes = frame.bytes().to_vec();
+        for bit in bits {
+            bytes[bit / 8] ^= 0x80 >> (bit % 8);
+        }
+        Frame::new(&bytes).unwrap()
+    }
+
+    #[test]
+    fn intact_frames_pass() {
+        let checked = check(
+            &frame("8D406B902015A678D4D220AA4BDA"),
+            ErrorCorrection::Disabled,
+        )
+        .unwrap();
+        assert_eq!(checked.icao, Icao(0x406B90));
+        assert!(!checked.corrected());
+
+        // DF11 replies carry the interrogator code in the low bits of the parity.
+        let checked = check(&frame("5D484FDEA248F5"), ErrorCorrection::Disabled).unwrap();
+        assert_eq!(checked.icao, Icao(0x484FDE));
+    }
+
+    #[test]
+    fn recovering_address_from_parity() {
+        for (hex, icao) in [
+            ("A0001839CA3800315800007448D9", 0x400940),
+            ("A000139381951536E024D4CCF6B5", 0x3C4DD2),
+            ("A000029CFFBAA11E2004727281F1", 0x4243D0),
+        ] {
+            let checked = check(&frame(hex), ErrorCorrection::Disabled).unwrap();
+            assert_eq!(checked.icao, Icao(icao));
+        }
+    }
+
+    #[test]
+    fn correcting_bit_errors() {
+        let original = frame("8D406B902015A678D4D220AA4BDA");
+
+        let broken = flip(&original, &[42]);
+        assert!(check(&broken, ErrorCorrection::Disabled).is_err());
+        let checked = check(&broken, ErrorCorrection::SingleBit).unwrap();
+        assert_eq!(checked.frame, original);
+        assert_eq!(checked.corrected_bits, 1);
+
+        let broken = flip(&original, &[10, 100]);
+        assert!(check(&broken, ErrorCorrection::SingleBit).is_err());
+        let checked = check(&broken, ErrorCorrection::TwoBits).unwrap();
+        assert_eq!(checked.frame, original);
+        assert_eq!(checked.corrected_bits, 2);
+    }
+}
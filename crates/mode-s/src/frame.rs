@@ -0,0 +1,159 @@
+//! Raw Mode S frames and bit-level access to their fields.
+
+use crate::DecodeError;
+use std::fmt;
+
+/// Length in bytes of a short (56-bit) Mode S frame.
+pub const SHORT_FRAME_LEN: usize = 7;
+
+/// Length in bytes of a long (112-bit) Mode S frame.
+pub const LONG_FRAME_LEN: usize = 14;
+
+/// A single 56 or 112-bit Mode S frame, as received from the air.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Frame {
+    bytes: [u8; LONG_FRAME_LEN],
+    len: usize,
+}
+
+impl Frame {
+    /// Construct a frame from its raw bytes. Only 7 and 14 byte frames are accepted, and the
+    /// length must match the one implied by the downlink format.
+    pub fn new(data: &[u8]) -> Result<Self, DecodeError> {
+        if data.len() != SHORT_FRAME_LEN && data.len() != LONG_FRAME_LEN {
+            return Err(DecodeError::InvalidLength(data.len() * 8));
+        }
+
+        let mut bytes = [0; LONG_FRAME_LEN];
+        bytes[..data.len()].copy_from_slice(data);
+        let frame = Self {
+            bytes,
+            len: data.len(),
+        };
+
+        let df = frame.downlink_format();
+        if expected_len(df).is_some_and(|len| len != data.len()) {
+            return Err(DecodeError::UnexpectedLength {
+                df,
+                bits: frame.bits(),
+            });
+        }
+
+        Ok(frame)
+    }
+
+    /// Parse a frame from its hexadecimal representation, e.g. `8D4840D6202CC371C32CE0576098`.
+    pub fn from_hex(hex: &str) -> Result<Self, DecodeError> {
+        let hex = hex.trim();
+        if hex.len() % 2 != 0 || hex.len() > LONG_FRAME_LEN * 2 {
+            return Err(DecodeError::InvalidLength(hex.len() * 4));
+        }
+
+        let mut bytes = [0; LONG_FRAME_LEN];
+        for (i, byte) in bytes.iter_mut().take(hex.len() / 2).enumerate() {
+            *byte = u8::from_str_radix(hex.get(i * 2..i * 2 + 2).unwrap_or_default(), 16)
+                .map_err(|_| DecodeError::InvalidHex)?;
+        }
+
+        Self::new(&bytes[..hex.len() / 2])
+    }
+
+    /// Raw bytes of the frame.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+
+    /// Number of bits in the frame, either 56 or 112.
+    pub fn bits(&self) -> usize {
+        self.len * 8
+    }
+
+    /// Downlink format (DF) of the frame. Formats 24 to 31 are all reported as 24, since only
+    /// the first two bits identify a Comm-D reply.
+    pub fn downlink_format(&self) -> u8 {
+        let df = self.bytes[0] >> 3;
+        if df >= 24 { 24 } else { df }
+    }
+
+    /// Read the field spanning from `first` to `last` bit, both inclusive and numbered from 1 as
+    /// in the ICAO Annex 10 tables.
+    pub(crate) fn field(&self, first: usize, last: usize) -> u32 {
+        field(self.bytes(), first, last)
+    }
+
+    /// The 56-bit ME (or MB/MV) field of a long frame.
+    pub(crate) fn payload(&self) -> [u8; 7] {
+        let mut payload = [0; 7];
+        payload.copy_from_slice(&self.bytes[4..11]);
+        payload
+    }
+
+    /// The last 24 bits of the frame, holding either the parity or the address/parity.
+    pub(crate) fn parity(&self) -> u32 {
+        self.field(self.bits() - 23, self.bits())
+    }
+}
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.bytes() {
+            write!(f, "{byte:02X}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Frame({self})")
+    }
+}
+
+/// Frame length in bytes implied by the downlink format, if it is known.
+fn expected_len(df: u8) -> Option<usize> {
+    match df {
+        0 | 4 | 5 | 11 => Some(SHORT_FRAME_LEN),
+        16 | 17 | 18 | 19 | 20 | 21 | 24 => Some(LONG_FRAME_LEN),
+        _ => None,
+    }
+}
+
+/// Read bits `first..=last` (numbered from 1) of `data` as an unsigned integer.
+pub(crate) fn field(data: &[u8], first: usize, last: usize) -> u32 {
+    debug_assert!(first >= 1 && first <= last && last - first < 32);
+    (first - 1..last).fold(0, |acc, bit| {
+        let set = (data[bit / 8] >> (7 - bit % 8)) & 1;
+        (acc << 1) | set as u32
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing_hex() {
+        let frame = Frame::from_hex("8D4840D6202CC371C32CE0576098").unwrap();
+        assert_eq!(frame.bits(), 112);
+        assert_eq!(frame.downlink_format(), 17);
+        assert_eq!(frame.field(9, 32), 0x4840D6);
+        assert_eq!(frame.parity(), 0x576098);
+        assert_eq!(frame.to_string(), "8D4840D6202CC371C32CE0576098");
+    }
+
+    #[test]
+    fn rejecting_malformed_frames() {
+        assert_eq!(
+            Frame::from_hex("8D4840D6202CC3"),
+            Err(DecodeError::UnexpectedLength { df: 17, bits: 56 })
+        );
+        assert_eq!(
+            Frame::from_hex("8D4840D6202CC371C32CE05760"),
+            Err(DecodeError::InvalidLength(104))
+        );
+        assert_eq!(
+            Frame::from_hex("8D4840D6202CC371C32CE05760ZZ"),
+            Err(DecodeError::InvalidHex)
+        );
+    }
+}
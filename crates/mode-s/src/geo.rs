@@ -0,0 +1,87 @@
+//! Geodesic helpers working on [`Position`]s.
+
+use crate::Position;
+
+/// Mean Earth radius in meters.
+pub const EARTH_RADIUS: f64 = 6_371_008.8;
+
+/// Meters in one nautical mile.
+pub const METERS_PER_NAUTICAL_MILE: f64 = 1852.0;
+
+/// Construct `Position` from latitude and longitude, just like `walkers::lat_lon`.
+pub fn lat_lon(lat: f64, lon: f64) -> Position {
+    Position::new(lon, lat)
+}
+
+/// Great circle distance between two positions, in meters.
+pub fn distance(from: Position, to: Position) -> f64 {
+    let (lat1, lat2) = (from.y().to_radians(), to.y().to_radians());
+    let d_lat = lat2 - lat1;
+    let d_lon = (to.x() - from.x()).to_radians();
+
+    let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS * a.sqrt().asin()
+}
+
+/// Initial bearing of the great circle from `from` to `to`, in degrees clockwise from the true
+/// north, between 0 and 360.
+pub fn bearing(from: Position, to: Position) -> f64 {
+    let (lat1, lat2) = (from.y().to_radians(), to.y().to_radians());
+    let d_lon = (to.x() - from.x()).to_radians();
+
+    let y = d_lon.sin() * lat2.cos();
+    let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * d_lon.cos();
+    y.atan2(x).to_degrees().rem_euclid(360.0)
+}
+
+/// Position reached when going `distance` meters from `from` along the great circle with the
+/// initial `bearing`, in degrees clockwise from the true north.
+pub fn destination(from: Position, bearing: f64, distance: f64) -> Position {
+    let (lat, lon) = (from.y().to_radians(), from.x().to_radians());
+    let bearing = bearing.to_radians();
+    let angle = distance / EARTH_RADIUS;
+
+    let lat2 = (lat.sin() * angle.cos() + lat.cos() * angle.sin() * bearing.cos()).asin();
+    let lon2 =
+        lon + (bearing.sin() * angle.sin() * lat.cos()).atan2(angle.cos() - lat.sin() * lat2.sin());
+
+    lat_lon(
+        lat2.to_degrees(),
+        (lon2.to_degrees() + 540.0).rem_euclid(360.0) - 180.0,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measuring_distance() {
+        // One degree of latitude is 60 nautical miles, give or take.
+        let distance = distance(lat_lon(50.0, 10.0), lat_lon(51.0, 10.0));
+        assert!((distance / METERS_PER_NAUTICAL_MILE - 60.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn measuring_bearing() {
+        let from = lat_lon(50.0, 10.0);
+        assert!(bearing(from, lat_lon(51.0, 10.0)).abs() < 1e-9);
+        assert!((bearing(from, lat_lon(49.0, 10.0)) - 180.0).abs() < 1e-9);
+
+        for expected in [45.0, 135.0, 300.0] {
+            let to = destination(from, expected, 200_000.0);
+            assert!((bearing(from, to) - expected).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn going_to_destination() {
+        let from = lat_lon(50.0, 10.0);
+        let to = destination(from, 90.0, 100_000.0);
+        assert!((distance(from, to) - 100_000.0).abs() < 1e-3);
+        assert!(to.x() > 10.0);
+
+        let to = destination(lat_lon(0.0, 179.9), 90.0, 50_000.0);
+        assert!(to.x() < -179.0);
+    }
+}
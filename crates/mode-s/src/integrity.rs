@@ -0,0 +1,183 @@
+//! Integrity and accuracy of ADS-B positions.
+//!
+//! Position messages only carry a coarse navigation integrity category (NIC) in their type code.
+//! From version 1 on, it is refined with supplement bits which come from the operational status
+//! (A, and C on the ground) and from the airborne position itself (B, version 2 only).
+
+use crate::geo::METERS_PER_NAUTICAL_MILE;
+
+const NM: f64 = METERS_PER_NAUTICAL_MILE;
+
+/// Navigation integrity category and the matching horizontal containment radius.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Integrity {
+    /// From 0 (unknown) to 11 (best).
+    pub nic: u8,
+    /// Radius in meters around the reported position which contains the true position, with the
+    /// probability given by the SIL. `None` when unknown.
+    pub containment_radius: Option<f64>,
+}
+
+impl Integrity {
+    const fn new(nic: u8, containment_radius: f64) -> Self {
+        Self {
+            nic,
+            containment_radius: Some(containment_radius),
+        }
+    }
+
+    const UNKNOWN: Self = Self {
+        nic: 0,
+        containment_radius: None,
+    };
+}
+
+/// Integrity of a position with the given type code, from 5 to 18 and 20 to 22. The supplements
+/// are ignored by version 0, and B and C by version 1. `supplement_bc` is supplement B in the air
+/// and supplement C on the ground.
+pub fn integrity(
+    type_code: u8,
+    version: u8,
+    supplement_a: bool,
+    supplement_bc: bool,
+) -> Option<Integrity> {
+    let (a, bc) = match version {
+        0 => (false, false),
+        1 => (supplement_a, supplement_a),
+        _ => (supplement_a, supplement_bc),
+    };
+
+    let integrity = match (type_code, a, bc) {
+        // Surface positions.
+        (5, ..) => Integrity::new(11, 7.5),
+        (6, ..) => Integrity::new(10, 25.0),
+        (7, true, _) if version > 0 => Integrity::new(9, 75.0),
+        (7, ..) => Integrity::new(8, 0.1 * NM),
+        (8, ..) if version < 2 => Integrity::UNKNOWN,
+        (8, true, true) => Integrity::new(7, 0.2 * NM),
+        (8, false, true) => Integrity::new(6, 0.3 * NM),
+        (8, true, false) => Integrity::new(6, 0.6 * NM),
+        (8, false, false) => Integrity::UNKNOWN,
+
+        // Airborne positions.
+        (9 | 20, ..) => Integrity::new(11, 7.5),
+        (10 | 21, ..) => Integrity::new(10, 25.0),
+        (11, true, true) => Integrity::new(9, 75.0),
+        (11, ..) => Integrity::new(8, 0.1 * NM),
+        (12, ..) => Integrity::new(7, 0.2 * NM),
+        (13, ..) if version == 0 => Integrity::new(6, 0.5 * NM),
+        (13, false, true) => Integrity::new(6, 0.3 * NM),
+        (13, true, true) => Integrity::new(6, 0.6 * NM),
+        (13, ..) => Integrity::new(6, 0.5 * NM),
+        (14, ..) => Integrity::new(5, NM),
+        (15, ..) => Integrity::new(4, 2.0 * NM),
+        (16, true, true) => Integrity::new(3, 4.0 * NM),
+        (16, ..) => Integrity::new(2, 8.0 * NM),
+        (17, ..) => Integrity::new(1, 20.0 * NM),
+        (18 | 22, ..) => Integrity::UNKNOWN,
+
+        _ => return None,
+    };
+
+    Some(integrity)
+}
+
+/// Integrity of a UAT position, which carries its NIC directly. Where several containment radii
+/// share a NIC on 1090 MHz, the largest one is used.
+pub fn uat_integrity(nic: u8) -> Integrity {
+    match nic {
+        11 => Integrity::new(11, 7.5),
+        10 => Integrity::new(10, 25.0),
+        9 => Integrity::new(9, 75.0),
+        8 => Integrity::new(8, 0.1 * NM),
+        7 => Integrity::new(7, 0.2 * NM),
+        6 => Integrity::new(6, 0.6 * NM),
+        5 => Integrity::new(5, NM),
+        4 => Integrity::new(4, 2.0 * NM),
+        3 => Integrity::new(3, 4.0 * NM),
+        2 => Integrity::new(2, 8.0 * NM),
+        1 => Integrity::new(1, 20.0 * NM),
+        _ => Integrity::UNKNOWN,
+    }
+}
+
+/// Estimated position uncertainty in meters, at 95% probability, of a navigation accuracy
+/// category for position. `None` when unknown.
+pub fn position_accuracy(nac_p: u8) -> Option<f64> {
+    Some(match nac_p {
+        11 => 3.0,
+        10 => 10.0,
+        9 => 30.0,
+        8 => 0.05 * NM,
+        7 => 0.1 * NM,
+        6 => 0.3 * NM,
+        5 => 0.5 * NM,
+        4 => NM,
+        3 => 2.0 * NM,
+        2 => 4.0 * NM,
+        1 => 10.0 * NM,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Containment radius rounded to a tenth of a meter.
+    fn radius(type_code: u8, version: u8, a: bool, bc: bool) -> Option<f64> {
+        integrity(type_code, version, a, bc)
+            .unwrap()
+            .containment_radius
+            .map(|radius| (radius * 10.0).round() / 10.0)
+    }
+
+    #[test]
+    fn combining_supplements() {
+        assert_eq!(radius(9, 2, false, false), Some(7.5));
+        assert_eq!(radius(11, 2, true, true), Some(75.0));
+        assert_eq!(radius(11, 2, true, false), Some(185.2));
+        assert_eq!(radius(13, 2, false, true), Some(555.6));
+        assert_eq!(radius(13, 2, true, true), Some(1111.2));
+        assert_eq!(radius(16, 2, true, true), Some(7408.0));
+        assert_eq!(radius(18, 2, true, true), None);
+
+        // Version 1 only has supplement A.
+        assert_eq!(radius(11, 1, true, false), Some(75.0));
+        assert_eq!(radius(16, 1, false, true), Some(14816.0));
+
+        // Version 0 has none.
+        assert_eq!(radius(11, 0, true, true), Some(185.2));
+        assert_eq!(radius(13, 0, true, true), Some(926.0));
+
+        // On the ground, supplement C takes the place of B.
+        assert_eq!(radius(8, 2, false, true), Some(555.6));
+        assert_eq!(radius(8, 1, true, true), None);
+
+        assert_eq!(integrity(19, 2, false, false), None);
+        assert_eq!(integrity(17, 2, false, false).unwrap().nic, 1);
+    }
+
+    #[test]
+    fn uat_integrity_matches_1090() {
+        for nic in 1..=11 {
+            let uat = uat_integrity(nic).containment_radius.unwrap();
+            let worst_1090 = (5..=22)
+                .flat_map(|type_code| {
+                    [(false, false), (true, false), (false, true), (true, true)]
+                        .map(|(a, bc)| integrity(type_code, 2, a, bc))
+                })
+                .flatten()
+                .filter(|integrity| integrity.nic == nic)
+                .filter_map(|integrity| integrity.containment_radius)
+                .fold(0.0, f64::max);
+            assert_eq!(uat, worst_1090, "NIC {nic}");
+        }
+    }
+
+    #[test]
+    fn accuracy() {
+        assert_eq!(position_accuracy(10), Some(10.0));
+        assert_eq!(position_accuracy(0), None);
+    }
+}
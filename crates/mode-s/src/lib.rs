@@ -0,0 +1,111 @@
+//! Decoder for Mode S and ADS-B (1090 MHz extended squitter) messages, as well as 978 MHz UAT
+//! messages in [`uat`].
+//!
+//! ```
+//! use mode_s::{ErrorCorrection, Frame, Message, adsb::Me};
+//!
+//! let frame = Frame::from_hex("8D4840D6202CC371C32CE0576098").unwrap();
+//! let checked = mode_s::check(&frame, ErrorCorrection::SingleBit).unwrap();
+//! if let Message::ExtendedSquitter { icao, me: Me::Identification(id), .. } = Message::decode(&checked.frame) {
+//!     assert_eq!(icao.to_string(), "4840D6");
+//!     assert_eq!(id.callsign, "KLM1023");
+//! }
+//! ```
+
+pub mod adsb;
+pub mod altitude;
+pub mod commb;
+pub mod country;
+pub mod cpr;
+pub mod crc;
+mod frame;
+pub mod geo;
+pub mod integrity;
+mod message;
+pub mod mode_ac;
+mod squawk;
+pub mod uat;
+
+pub use cpr::CprDecoder;
+pub use crc::{Checked, ErrorCorrection, check};
+pub use frame::{Frame, LONG_FRAME_LEN, SHORT_FRAME_LEN};
+pub use message::{FlightStatus, Message};
+pub use squawk::Squawk;
+
+use std::fmt;
+
+/// Geographical position, the same type as `walkers::Position`.
+pub type Position = geo_types::Point;
+
+/// 24-bit ICAO aircraft address.
+///
+/// Ground stations and some transmitters use other 24-bit addresses, like TIS-B track file
+/// numbers, which may collide with real ones. Those are flagged with [`Icao::NON_ICAO`] so
+/// that they are never taken for the aircraft with the same ICAO address, and written with
+/// a leading `~`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub struct Icao(pub u32);
+
+impl Icao {
+    /// Bit set above the 24 bits of the address when it is not an ICAO address.
+    pub const NON_ICAO: u32 = 1 << 24;
+
+    /// Non-ICAO `address`, e.g. an anonymous or TIS-B track file address.
+    pub fn non_icao(address: u32) -> Self {
+        Self(address & 0xFF_FFFF | Self::NON_ICAO)
+    }
+
+    /// Whether this is an ICAO address, rather than one flagged with [`Icao::NON_ICAO`].
+    pub fn is_icao(self) -> bool {
+        self.0 & Self::NON_ICAO == 0
+    }
+
+    /// Parse an address written as six hexadecimal digits, e.g. `4840D6`. A leading `~`, used
+    /// by some decoders for non-ICAO addresses, gives a non-ICAO address.
+    pub fn parse(hex: &str) -> Option<Self> {
+        let hex = hex.trim();
+        let (hex, icao) = match hex.strip_prefix('~') {
+            Some(hex) => (hex, false),
+            None => (hex, true),
+        };
+        if hex.len() != 6 {
+            return None;
+        }
+        let address = u32::from_str_radix(hex, 16).ok()?;
+        Some(if icao {
+            Self(address)
+        } else {
+            Self::non_icao(address)
+        })
+    }
+}
+
+impl fmt::Display for Icao {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.is_icao() {
+            write!(f, "~")?;
+        }
+        write!(f, "{:06X}", self.0 & 0xFF_FFFF)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DecodeError {
+    #[error("Frame of {0} bits is neither 56 nor 112 bits long.")]
+    InvalidLength(usize),
+
+    #[error("DF{df} frame can not be {bits} bits long.")]
+    UnexpectedLength { df: u8, bits: usize },
+
+    #[error("UAT message of {0} bits is neither 144 nor 272 bits long.")]
+    InvalidUatLength(usize),
+
+    #[error("Frame is not valid hexadecimal.")]
+    InvalidHex,
+
+    #[error("Parity check failed with residual {0:06X}.")]
+    ParityMismatch(u32),
+
+    #[error("Parity of DF{0} frames can not be checked.")]
+    UnsupportedFormat(u8),
+}
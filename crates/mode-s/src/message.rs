@@ -0,0 +1,174 @@
+//! Mode S downlink messages.
+
+use crate::adsb::Me;
+use crate::altitude::decode_ac13;
+use crate::{Frame, Icao, Squawk};
+
+/// Flight status (FS) field of surveillance and Comm-B replies.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FlightStatus(pub u8);
+
+impl FlightStatus {
+    /// Whether the aircraft reports being on the ground. `None` if it does not tell.
+    pub fn on_ground(&self) -> Option<bool> {
+        match self.0 {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+
+    /// Whether the Mode A code was changed recently, usually to an emergency code.
+    pub fn alert(&self) -> bool {
+        matches!(self.0, 2..=4)
+    }
+
+    /// Whether the pilot pressed the IDENT button (special position identification).
+    pub fn spi(&self) -> bool {
+        matches!(self.0, 4 | 5)
+    }
+}
+
+/// A decoded Mode S message.
+///
+/// Replies that overlay the address with the parity (DF4, DF5, DF20 and DF21) carry the raw
+/// address/parity field, as the address can only be recovered with [`crate::check`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    /// DF4, altitude reply.
+    SurveillanceAltitude {
+        flight_status: FlightStatus,
+        /// Altitude in feet.
+        altitude: Option<i32>,
+        parity: u32,
+    },
+    /// DF5, identity reply.
+    SurveillanceIdentity {
+        flight_status: FlightStatus,
+        squawk: Squawk,
+        parity: u32,
+    },
+    /// DF11, all-call reply.
+    AllCallReply {
+        capability: u8,
+        icao: Icao,
+        parity: u32,
+    },
+    /// DF17 ADS-B or DF18 TIS-B/ADS-R/non-transponder extended squitter.
+    ExtendedSquitter {
+        df: u8,
+        /// Capability (CA) of DF17, or control field (CF) of DF18.
+        capability: u8,
+        icao: Icao,
+        me: Me,
+    },
+    /// DF20, Comm-B altitude reply.
+    CommBAltitude {
+        flight_status: FlightStatus,
+        altitude: Option<i32>,
+        mb: [u8; 7],
+        parity: u32,
+    },
+    /// DF21, Comm-B identity reply.
+    CommBIdentity {
+        flight_status: FlightStatus,
+        squawk: Squawk,
+        mb: [u8; 7],
+        parity: u32,
+    },
+    /// Any other downlink format.
+    Other { df: u8 },
+}
+
+impl Message {
+    /// Decode a frame. This does not check the parity, so a corrupted frame will still decode
+    /// into (wrong) values.
+    pub fn decode(frame: &Frame) -> Self {
+        let df = frame.downlink_format();
+        let parity = frame.parity();
+
+        match df {
+            4 => Message::SurveillanceAltitude {
+                flight_status: FlightStatus(frame.field(6, 8) as u8),
+                altitude: decode_ac13(frame.field(20, 32)),
+                parity,
+            },
+            5 => Message::SurveillanceIdentity {
+                flight_status: FlightStatus(frame.field(6, 8) as u8),
+                squawk: Squawk::from_id13(frame.field(20, 32)),
+                parity,
+            },
+            11 => Message::AllCallReply {
+                capability: frame.field(6, 8) as u8,
+                icao: Icao(frame.field(9, 32)),
+                parity,
+            },
+            17 | 18 => Message::ExtendedSquitter {
+                df,
+                capability: frame.field(6, 8) as u8,
+                icao: Icao(frame.field(9, 32)),
+                me: Me::decode(frame.payload()),
+            },
+            20 => Message::CommBAltitude {
+                flight_status: FlightStatus(frame.field(6, 8) as u8),
+                altitude: decode_ac13(frame.field(20, 32)),
+                mb: frame.payload(),
+                parity,
+            },
+            21 => Message::CommBIdentity {
+                flight_status: FlightStatus(frame.field(6, 8) as u8),
+                squawk: Squawk::from_id13(frame.field(20, 32)),
+                mb: frame.payload(),
+                parity,
+            },
+            df => Message::Other { df },
+        }
+    }
+
+    /// Downlink format of the message.
+    pub fn downlink_format(&self) -> u8 {
+        match self {
+            Message::SurveillanceAltitude { .. } => 4,
+            Message::SurveillanceIdentity { .. } => 5,
+            Message::AllCallReply { .. } => 11,
+            Message::ExtendedSquitter { df, .. } => *df,
+            Message::CommBAltitude { .. } => 20,
+            Message::CommBIdentity { .. } => 21,
+            Message::Other { df } => *df,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(hex: &str) -> Message {
+        Message::decode(&Frame::from_hex(hex).unwrap())
+    }
+
+    #[test]
+    fn decoding_all_call_reply() {
+        assert_eq!(
+            decode("5D484FDEA248F5"),
+            Message::AllCallReply {
+                capability: 5,
+                icao: Icao(0x484FDE),
+                parity: 0xA248F5,
+            }
+        );
+    }
+
+    #[test]
+    fn decoding_surveillance_replies() {
+        let Message::SurveillanceAltitude { altitude, .. } = decode("20001838CA3804") else {
+            panic!("not an altitude reply");
+        };
+        assert_eq!(altitude, Some(38000));
+
+        let Message::CommBIdentity { squawk, .. } = decode("A800292DFFBBA9383FFCEB903D01") else {
+            panic!("not a Comm-B identity reply");
+        };
+        assert_eq!(squawk, Squawk(0x1346));
+    }
+}
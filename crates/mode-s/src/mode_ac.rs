@@ -0,0 +1,60 @@
+//! Mode A and Mode C replies, sent by transponders to secondary surveillance radars.
+
+use crate::Squawk;
+use crate::altitude::gillham_to_altitude;
+
+/// A Mode A/C reply. Both modes answer with the same 12 code pulses and only the interrogator
+/// knows which one it asked for, so a passive receiver has to read every reply both ways.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ModeAcReply {
+    /// Code pulses in the `0xABCD` layout used by [`Squawk`].
+    pub code: u16,
+    /// Special position identification pulse, only sent in Mode A.
+    pub spi: bool,
+}
+
+impl ModeAcReply {
+    /// Decode the payload of a Beast type `1` frame, which holds the code in the `0xABCD`
+    /// layout with the SPI pulse in bit `0x0080`.
+    pub fn from_beast(payload: [u8; 2]) -> Self {
+        let raw = u16::from_be_bytes(payload);
+        Self {
+            code: raw & 0x7777,
+            spi: raw & 0x0080 != 0,
+        }
+    }
+
+    /// The reply read as a Mode A code.
+    pub fn squawk(self) -> Squawk {
+        Squawk(self.code)
+    }
+
+    /// The reply read as a Mode C altitude in feet, if the code is a valid Gillham code.
+    pub fn altitude(self) -> Option<i32> {
+        if self.spi {
+            return None;
+        }
+        gillham_to_altitude(self.code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decoding_beast_payloads() {
+        let reply = ModeAcReply::from_beast([0x77, 0x00]);
+        assert_eq!(reply.squawk(), Squawk(0x7700));
+        assert!(!reply.spi);
+
+        let reply = ModeAcReply::from_beast([0x12, 0xB4]);
+        assert_eq!(reply.squawk(), Squawk(0x1234));
+        assert!(reply.spi);
+        assert_eq!(reply.altitude(), None);
+
+        // B2 and C4, 700 ft.
+        let reply = ModeAcReply::from_beast([0x02, 0x40]);
+        assert_eq!(reply.altitude(), Some(700));
+    }
+}
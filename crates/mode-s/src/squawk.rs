@@ -0,0 +1,66 @@
+//! Mode A identity codes, commonly known as squawks.
+
+use std::fmt;
+
+/// A Mode A identity code. Each hexadecimal digit of the inner value holds one octal digit of the
+/// code, so squawk 7700 is `Squawk(0x7700)`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub struct Squawk(pub u16);
+
+impl Squawk {
+    /// Decode the 13-bit identity (ID) field of DF5 and DF21 replies.
+    pub fn from_id13(id: u32) -> Self {
+        Self(id13_to_gillham(id))
+    }
+
+    /// Parse a squawk written as four octal digits, e.g. `7700`.
+    pub fn parse(code: &str) -> Option<Self> {
+        let code = code.trim();
+        if code.len() != 4 || !code.bytes().all(|b| (b'0'..=b'7').contains(&b)) {
+            return None;
+        }
+        u16::from_str_radix(code, 16).ok().map(Self)
+    }
+}
+
+impl fmt::Display for Squawk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04X}", self.0)
+    }
+}
+
+/// Reorder the interleaved `C1 A1 C2 A2 C4 A4 X B1 D1 B2 D2 B4 D4` bits of a 13-bit field into
+/// the `0xABCD` layout.
+pub(crate) fn id13_to_gillham(id: u32) -> u16 {
+    const BITS: [(u32, u16); 12] = [
+        (0x1000, 0x0010), // C1
+        (0x0800, 0x1000), // A1
+        (0x0400, 0x0020), // C2
+        (0x0200, 0x2000), // A2
+        (0x0100, 0x0040), // C4
+        (0x0080, 0x4000), // A4
+        (0x0020, 0x0100), // B1
+        (0x0010, 0x0001), // D1
+        (0x0008, 0x0200), // B2
+        (0x0004, 0x0002), // D2
+        (0x0002, 0x0400), // B4
+        (0x0001, 0x0004), // D4
+    ];
+
+    BITS.iter()
+        .filter(|(from, _)| id & from != 0)
+        .fold(0, |acc, (_, to)| acc | to)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decoding_identity() {
+        // ID field of DF21 `A800292DFFBBA9383FFCEB903D01`.
+        assert_eq!(Squawk::from_id13(0x092D).to_string(), "1346");
+        assert_eq!(Squawk::parse("7700"), Some(Squawk(0x7700)));
+        assert_eq!(Squawk::parse("7800"), None);
+    }
+}
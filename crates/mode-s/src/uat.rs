@@ -0,0 +1,530 @@
+//! Universal Access Transceiver (UAT) ADS-B messages, broadcast on 978 MHz in the United States.
+//!
+//! Basic messages are 18 bytes long and carry the header and the state vector. Long messages are
+//! 34 bytes long and, depending on their payload type, add the mode status and the auxiliary
+//! state vector. Unlike on 1090 MHz, positions are not CPR encoded.
+
+pub use crate::adsb::EmergencyStatus;
+use crate::adsb::{AircraftCategory, AltitudeSource, VerticalRateSource};
+use crate::frame::field;
+use crate::geo::lat_lon;
+use crate::{DecodeError, Icao, Position, Squawk};
+
+/// Length in bytes of a basic UAT message.
+pub const BASIC_MESSAGE_LEN: usize = 18;
+
+/// Length in bytes of a long UAT message.
+pub const LONG_MESSAGE_LEN: usize = 34;
+
+/// Kind of the address in the header, which also tells who sent the message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AddressQualifier {
+    /// ADS-B target with an ICAO address.
+    AdsbIcao,
+    /// ADS-B target with a self-assigned temporary address.
+    AdsbSelfAssigned,
+    /// TIS-B target with an ICAO address.
+    TisbIcao,
+    /// TIS-B target identified by a track file number.
+    TisbTrackFile,
+    SurfaceVehicle,
+    FixedBeacon,
+    /// ADS-R rebroadcast of a 1090 MHz target.
+    AdsrIcao,
+    Reserved,
+}
+
+impl AddressQualifier {
+    fn new(qualifier: u32) -> Self {
+        match qualifier {
+            0 => Self::AdsbIcao,
+            1 => Self::AdsbSelfAssigned,
+            2 => Self::TisbIcao,
+            3 => Self::TisbTrackFile,
+            4 => Self::SurfaceVehicle,
+            5 => Self::FixedBeacon,
+            6 => Self::AdsrIcao,
+            _ => Self::Reserved,
+        }
+    }
+
+    /// Whether the address is an ICAO address, rather than a self-assigned or track file
+    /// one, which may collide with the address of another aircraft.
+    pub fn is_icao(self) -> bool {
+        matches!(self, Self::AdsbIcao | Self::TisbIcao | Self::AdsrIcao)
+    }
+
+    /// Whether the message was sent by a ground station rather than by the target itself.
+    pub fn is_rebroadcast(self) -> bool {
+        matches!(self, Self::TisbIcao | Self::TisbTrackFile | Self::AdsrIcao)
+    }
+}
+
+/// Header common to all messages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Header {
+    /// Tells which elements follow the state vector.
+    pub payload_type: u8,
+    pub qualifier: AddressQualifier,
+    pub address: Icao,
+}
+
+/// Air or ground state of the target.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AirGroundState {
+    Subsonic,
+    Supersonic,
+    OnGround,
+    Reserved,
+}
+
+/// State vector, carried by every message.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StateVector {
+    pub position: Option<Position>,
+    /// Altitude in feet.
+    pub altitude: Option<i32>,
+    pub altitude_source: AltitudeSource,
+    /// Navigation integrity category.
+    pub nic: u8,
+    pub air_ground_state: AirGroundState,
+    /// Ground speed in knots.
+    pub ground_speed: Option<f64>,
+    /// Track over ground in degrees, clockwise from the true north. On the ground this can
+    /// also be the heading.
+    pub track: Option<f64>,
+    /// Vertical rate in feet per minute, positive when climbing.
+    pub vertical_rate: Option<i32>,
+    pub vertical_rate_source: Option<VerticalRateSource>,
+    /// Whether the position is synchronized to UTC. Always `false` for TIS-B.
+    pub utc_coupled: bool,
+}
+
+/// Decode a 12-bit altitude, in steps of 25 feet from -1000.
+fn decode_altitude(raw: u32) -> Option<i32> {
+    (raw != 0).then(|| (raw as i32 - 1) * 25 - 1000)
+}
+
+/// Decode an 11-bit velocity component: a sign followed by a 10-bit magnitude in knots.
+fn decode_velocity(raw: u32, supersonic: bool) -> Option<f64> {
+    let magnitude = raw & 0x3FF;
+    if magnitude == 0 {
+        return None;
+    }
+
+    let multiplier = if supersonic { 4.0 } else { 1.0 };
+    let velocity = (magnitude - 1) as f64 * multiplier;
+    Some(if raw & 0x400 != 0 {
+        -velocity
+    } else {
+        velocity
+    })
+}
+
+impl StateVector {
+    fn decode(data: &[u8], qualifier: AddressQualifier) -> Self {
+        let raw_lat = field(data, 33, 55);
+        let raw_lon = field(data, 56, 79);
+        let nic = field(data, 93, 96) as u8;
+
+        let position = (nic != 0 || raw_lat != 0 || raw_lon != 0).then(|| {
+            let lat = raw_lat as f64 * 360.0 / 16_777_216.0;
+            let lon = raw_lon as f64 * 360.0 / 16_777_216.0;
+            lat_lon(
+                if lat > 90.0 { lat - 180.0 } else { lat },
+                if lon > 180.0 { lon - 360.0 } else { lon },
+            )
+        });
+
+        let altitude_source = if field(data, 80, 80) != 0 {
+            AltitudeSource::Gnss
+        } else {
+            AltitudeSource::Barometric
+        };
+
+        let air_ground_state = match field(data, 97, 98) {
+            0 => AirGroundState::Subsonic,
+            1 => AirGroundState::Supersonic,
+            2 => AirGroundState::OnGround,
+            _ => AirGroundState::Reserved,
+        };
+
+        let mut state = Self {
+            position,
+            altitude: decode_altitude(field(data, 81, 92)),
+            altitude_source,
+            nic,
+            air_ground_state,
+            ground_speed: None,
+            track: None,
+            vertical_rate: None,
+            vertical_rate_source: None,
+            utc_coupled: !qualifier.is_rebroadcast() && field(data, 133, 133) != 0,
+        };
+
+        match air_ground_state {
+            AirGroundState::Subsonic | AirGroundState::Supersonic => {
+                let supersonic = air_ground_state == AirGroundState::Supersonic;
+                let north = decode_velocity(field(data, 100, 110), supersonic);
+                let east = decode_velocity(field(data, 111, 121), supersonic);
+                if let (Some(north), Some(east)) = (north, east) {
+                    state.ground_speed = Some(east.hypot(north));
+                    state.track = (north != 0.0 || east != 0.0)
+                        .then(|| east.atan2(north).to_degrees().rem_euclid(360.0));
+                }
+
+                let rate = field(data, 124, 132);
+                if rate != 0 {
+                    let rate = (rate as i32 - 1) * 64;
+                    state.vertical_rate = Some(if field(data, 123, 123) != 0 {
+                        -rate
+                    } else {
+                        rate
+                    });
+                    state.vertical_rate_source = Some(if field(data, 122, 122) != 0 {
+                        VerticalRateSource::Barometric
+                    } else {
+                        VerticalRateSource::Gnss
+                    });
+                }
+            }
+            AirGroundState::OnGround => {
+                state.ground_speed = match field(data, 101, 110) {
+                    0 => None,
+                    speed => Some((speed - 1) as f64),
+                };
+                state.track = (field(data, 111, 112) != 0)
+                    .then(|| field(data, 113, 121) as f64 * 360.0 / 512.0);
+            }
+            AirGroundState::Reserved => {}
+        }
+
+        state
+    }
+}
+
+/// Mode status, carried by long messages of payload types 1 and 3.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModeStatus {
+    pub category: AircraftCategory,
+    /// Callsign with the trailing spaces removed, if the aircraft sends one.
+    pub callsign: Option<String>,
+    /// Mode A code, sent instead of the callsign by aircraft without one.
+    pub squawk: Option<Squawk>,
+    pub emergency: EmergencyStatus,
+    /// UAT MOPS version.
+    pub version: u8,
+    /// Source integrity level.
+    pub sil: u8,
+    /// Navigation accuracy category for position.
+    pub nac_p: u8,
+    /// Navigation accuracy category for velocity.
+    pub nac_v: u8,
+    pub nic_baro: bool,
+    /// Whether TCAS/ACAS is installed and operational.
+    pub tcas: bool,
+    /// Whether a TCAS resolution advisory is active.
+    pub ra_active: bool,
+    /// Whether the pilot pressed IDENT.
+    pub ident: bool,
+}
+
+const BASE40_CHARSET: &[u8; 40] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ  ..";
+
+impl ModeStatus {
+    fn decode(data: &[u8]) -> Self {
+        // Three 16-bit words, each holding three base 40 digits. The first digit of the first
+        // word is the emitter category.
+        let words = [
+            field(data, 137, 152),
+            field(data, 153, 168),
+            field(data, 169, 184),
+        ];
+        let digits: Vec<u32> = words
+            .iter()
+            .flat_map(|word| [word / 1600 % 40, word / 40 % 40, word % 40])
+            .collect();
+
+        let emitter = digits[0] as u8;
+        // The first two sets line up with ADS-B sets A and B, but the surface vehicles and
+        // obstacles of the third one are numbered differently from ADS-B set C.
+        let category = match emitter {
+            0..=15 => AircraftCategory::new(4 - emitter / 8, emitter % 8),
+            17 => AircraftCategory::EmergencyVehicle,
+            18 => AircraftCategory::ServiceVehicle,
+            19..=21 => AircraftCategory::Obstacle,
+            _ => AircraftCategory::Reserved {
+                type_code: 0,
+                category: emitter,
+            },
+        };
+
+        let text: String = digits[1..]
+            .iter()
+            .map(|&digit| BASE40_CHARSET[digit as usize] as char)
+            .collect();
+        let text = text.trim_end();
+
+        let (callsign, squawk) = if text.is_empty() {
+            (None, None)
+        } else if field(data, 215, 215) != 0 {
+            (Some(text.to_string()), None)
+        } else {
+            (None, Squawk::parse(text))
+        };
+
+        let emergency = EmergencyStatus::from_code(field(data, 185, 187) as u8);
+
+        Self {
+            category,
+            callsign,
+            squawk,
+            emergency,
+            version: field(data, 188, 190) as u8,
+            sil: field(data, 191, 192) as u8,
+            nac_p: field(data, 201, 204) as u8,
+            nac_v: field(data, 205, 207) as u8,
+            nic_baro: field(data, 208, 208) != 0,
+            tcas: field(data, 210, 210) != 0,
+            ra_active: field(data, 211, 211) != 0,
+            ident: field(data, 212, 212) != 0,
+        }
+    }
+}
+
+/// Auxiliary state vector, carried by long messages of payload types 1, 2, 5 and 6.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuxiliaryStateVector {
+    /// The altitude of the other kind than the one in the state vector, in feet.
+    pub secondary_altitude: Option<i32>,
+}
+
+impl AuxiliaryStateVector {
+    fn decode(data: &[u8]) -> Self {
+        Self {
+            secondary_altitude: decode_altitude(field(data, 233, 244)),
+        }
+    }
+}
+
+/// A decoded UAT downlink message.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UatMessage {
+    pub header: Header,
+    /// Missing for reserved payload types.
+    pub state_vector: Option<StateVector>,
+    pub mode_status: Option<ModeStatus>,
+    pub auxiliary_state_vector: Option<AuxiliaryStateVector>,
+}
+
+impl UatMessage {
+    /// Decode a basic or long message from its raw bytes.
+    pub fn decode(data: &[u8]) -> Result<Self, DecodeError> {
+        let long = match data.len() {
+            BASIC_MESSAGE_LEN => false,
+            LONG_MESSAGE_LEN => true,
+            len => return Err(DecodeError::InvalidUatLength(len * 8)),
+        };
+
+        let header = Header {
+            payload_type: field(data, 1, 5) as u8,
+            qualifier: AddressQualifier::new(field(data, 6, 8)),
+            address: Icao(field(data, 9, 32)),
+        };
+        let payload_type = header.payload_type;
+
+        let state_vector =
+            (payload_type <= 10).then(|| StateVector::decode(data, header.qualifier));
+        let mode_status = (long && matches!(payload_type, 1 | 3)).then(|| ModeStatus::decode(data));
+        let auxiliary_state_vector = (long && matches!(payload_type, 1 | 2 | 5 | 6))
+            .then(|| AuxiliaryStateVector::decode(data));
+
+        Ok(Self {
+            header,
+            state_vector,
+            mode_status,
+            auxiliary_state_vector,
+        })
+    }
+
+    /// Decode a message from its hexadecimal representation.
+    pub fn from_hex(hex: &str) -> Result<Self, DecodeError> {
+        let hex = hex.trim();
+        if hex.len() % 2 != 0 {
+            return Err(DecodeError::InvalidUatLength(hex.len() * 4));
+        }
+
+        let bytes = (0..hex.len() / 2)
+            .map(|i| u8::from_str_radix(hex.get(i * 2..i * 2 + 2).unwrap_or_default(), 16))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| DecodeError::InvalidHex)?;
+
+        Self::decode(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Message of `len` bytes with the given bits, numbered from 1, set to the given values.
+    fn message(len: usize, fields: &[(usize, usize, u32)]) -> Vec<u8> {
+        let mut data = vec![0; len];
+        for &(first, last, value) in fields {
+            for bit in first..=last {
+                if value >> (last - bit) & 1 != 0 {
+                    data[(bit - 1) / 8] |= 0x80 >> ((bit - 1) % 8);
+                }
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn decoding_basic_message() {
+        let data = message(
+            BASIC_MESSAGE_LEN,
+            &[
+                (9, 32, 0xA1B2C3),
+                // 45 degrees north, 90 degrees west.
+                (33, 55, 0x200000),
+                (56, 79, 0xC00000),
+                (81, 92, 241),
+                (93, 96, 8),
+                // 300 knots north and 400 knots west, descending at 640 ft/min.
+                (100, 110, 301),
+                (111, 121, 0x400 | 401),
+                (122, 122, 1),
+                (123, 132, 0x200 | 11),
+            ],
+        );
+        let message = UatMessage::decode(&data).unwrap();
+
+        assert_eq!(
+            message.header,
+            Header {
+                payload_type: 0,
+                qualifier: AddressQualifier::AdsbIcao,
+                address: Icao(0xA1B2C3),
+            }
+        );
+        assert_eq!(message.mode_status, None);
+
+        let state = message.state_vector.unwrap();
+        assert_eq!(state.position, Some(lat_lon(45.0, -90.0)));
+        assert_eq!(state.altitude, Some(5000));
+        assert_eq!(state.altitude_source, AltitudeSource::Barometric);
+        assert_eq!(state.nic, 8);
+        assert_eq!(state.air_ground_state, AirGroundState::Subsonic);
+        assert_eq!(state.ground_speed, Some(500.0));
+        assert!((state.track.unwrap() - 306.87).abs() < 0.01);
+        assert_eq!(state.vertical_rate, Some(-640));
+        assert_eq!(
+            state.vertical_rate_source,
+            Some(VerticalRateSource::Barometric)
+        );
+    }
+
+    #[test]
+    fn decoding_long_message() {
+        // "N12345" is 23, 1, 2, 3, 4, 5 in base 40, followed by two spaces.
+        let data = message(
+            LONG_MESSAGE_LEN,
+            &[
+                (1, 5, 1),
+                (6, 8, 2),
+                (97, 98, 2),
+                (101, 110, 13),
+                (111, 112, 1),
+                (113, 121, 128),
+                (137, 152, 1600 + 23 * 40 + 1),
+                (153, 168, 2 * 1600 + 3 * 40 + 4),
+                (169, 184, 5 * 1600 + 36 * 40 + 36),
+                (185, 187, 1),
+                (188, 190, 2),
+                (191, 192, 3),
+                (201, 204, 9),
+                (215, 215, 1),
+                (233, 244, 1041),
+            ],
+        );
+        let message = UatMessage::decode(&data).unwrap();
+        assert_eq!(message.header.qualifier, AddressQualifier::TisbIcao);
+
+        let state = message.state_vector.unwrap();
+        assert_eq!(state.air_ground_state, AirGroundState::OnGround);
+        assert_eq!(state.ground_speed, Some(12.0));
+        assert_eq!(state.track, Some(90.0));
+        assert_eq!(state.position, None);
+
+        let status = message.mode_status.unwrap();
+        assert_eq!(status.category, AircraftCategory::Light);
+        assert_eq!(status.callsign.as_deref(), Some("N12345"));
+        assert_eq!(status.squawk, None);
+        assert_eq!(status.emergency, EmergencyStatus::General);
+        assert_eq!(status.version, 2);
+        assert_eq!(status.sil, 3);
+        assert_eq!(status.nac_p, 9);
+
+        assert_eq!(
+            message.auxiliary_state_vector.unwrap().secondary_altitude,
+            Some(25000)
+        );
+    }
+
+    #[test]
+    fn decoding_squawk_instead_of_callsign() {
+        let data = message(
+            LONG_MESSAGE_LEN,
+            &[
+                (1, 5, 3),
+                (137, 152, 1600 + 40 + 2),
+                (153, 168, 7 * 1600 + 7 * 40 + 36),
+                (169, 184, 36 * 1600 + 36 * 40 + 36),
+            ],
+        );
+        let status = UatMessage::decode(&data).unwrap().mode_status.unwrap();
+        assert_eq!(status.callsign, None);
+        assert_eq!(status.squawk, Some(Squawk(0x1277)));
+    }
+
+    #[test]
+    fn decoding_emitter_categories() {
+        let category = |emitter: u32| {
+            let data = message(LONG_MESSAGE_LEN, &[(1, 5, 1), (137, 152, emitter * 1600)]);
+            UatMessage::decode(&data)
+                .unwrap()
+                .mode_status
+                .unwrap()
+                .category
+        };
+
+        assert_eq!(category(0), AircraftCategory::NoInformation);
+        assert_eq!(category(5), AircraftCategory::Heavy);
+        assert_eq!(category(9), AircraftCategory::Glider);
+        assert_eq!(category(14), AircraftCategory::Uav);
+        assert_eq!(category(17), AircraftCategory::EmergencyVehicle);
+        assert_eq!(category(18), AircraftCategory::ServiceVehicle);
+        for obstacle in 19..=21 {
+            assert_eq!(category(obstacle), AircraftCategory::Obstacle);
+        }
+        for reserved in [16, 22, 39] {
+            assert_eq!(
+                category(reserved),
+                AircraftCategory::Reserved {
+                    type_code: 0,
+                    category: reserved as u8,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn rejecting_invalid_lengths() {
+        assert_eq!(
+            UatMessage::decode(&[0; 14]),
+            Err(DecodeError::InvalidUatLength(112))
+        );
+        assert_eq!(UatMessage::from_hex("0G"), Err(DecodeError::InvalidHex));
+    }
+}
@@ -0,0 +1,340 @@
+//! Poller of the `aircraft.json` file, written by dump1090-fa, readsb and tar1090, and served
+//! by their web interfaces, e.g. at `http://localhost/tar1090/data/aircraft.json`.
+//!
+//! Unlike the other feeds, the file holds the current state of every aircraft, already decoded
+//! by the receiver software. Older dump1090 versions use different names for some of the
+//! fields, which are accepted as well.
+
+use crate::{Source, Update};
+use mode_s::adsb::{AircraftCategory, EmergencyStatus, VerticalRateSource};
+use mode_s::{Icao, Squawk, geo::lat_lon};
+use serde::Deserialize;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// Positions older than this are not reported.
+const POSITION_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Deserialize)]
+struct AircraftJson {
+    aircraft: Vec<Aircraft>,
+}
+
+/// Barometric altitude, which is either a number of feet or `"ground"`.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Altitude {
+    Feet(f64),
+    Ground(String),
+}
+
+#[derive(Debug, Deserialize)]
+struct Aircraft {
+    hex: String,
+    /// Source of the data, like `adsb_icao` or `mlat`. Only written by readsb and recent
+    /// dump1090-fa versions.
+    #[serde(rename = "type")]
+    source: Option<String>,
+    flight: Option<String>,
+    lat: Option<f64>,
+    lon: Option<f64>,
+    #[serde(alias = "altitude")]
+    alt_baro: Option<Altitude>,
+    alt_geom: Option<f64>,
+    #[serde(alias = "speed")]
+    gs: Option<f64>,
+    track: Option<f64>,
+    ias: Option<f64>,
+    tas: Option<f64>,
+    mag_heading: Option<f64>,
+    #[serde(alias = "vert_rate")]
+    baro_rate: Option<f64>,
+    geom_rate: Option<f64>,
+    nav_altitude_mcp: Option<f64>,
+    nav_altitude_fms: Option<f64>,
+    nav_heading: Option<f64>,
+    nav_qnh: Option<f64>,
+    version: Option<u8>,
+    nac_p: Option<u8>,
+    sil: Option<u8>,
+    gva: Option<u8>,
+    nic_baro: Option<u8>,
+    squawk: Option<String>,
+    category: Option<String>,
+    emergency: Option<String>,
+    /// Seconds since any message was received.
+    seen: Option<f64>,
+    /// Seconds since the position was updated.
+    seen_pos: Option<f64>,
+    rssi: Option<f64>,
+}
+
+impl Aircraft {
+    /// Updates of the aircraft as of `now`. The position is usually older than the rest of the
+    /// data, so it is reported in an update of its own, stamped with the time it was received.
+    fn into_updates(self, now: Instant) -> Vec<Update> {
+        let Some(icao) = Icao::parse(&self.hex) else {
+            log::debug!("Invalid hex '{}' in aircraft.json.", self.hex);
+            return Vec::new();
+        };
+        let ago = |seconds: Option<f64>| {
+            let age = Duration::try_from_secs_f64(seconds.unwrap_or(0.0)).unwrap_or_default();
+            now.checked_sub(age).unwrap_or(now)
+        };
+
+        let mut update = Update::new(icao, ago(self.seen));
+        update.source = self.source.as_deref().map_or(Source::Unknown, parse_source);
+
+        update.callsign = self
+            .flight
+            .map(|flight| flight.trim().to_string())
+            .filter(|flight| !flight.is_empty());
+        update.category = self.category.as_deref().and_then(AircraftCategory::parse);
+
+        match self.alt_baro {
+            Some(Altitude::Feet(altitude)) => {
+                update.altitude = Some(altitude as i32);
+                update.on_ground = Some(false);
+            }
+            Some(Altitude::Ground(ground)) if ground == "ground" => update.on_ground = Some(true),
+            _ => {}
+        }
+        update.geometric_altitude = self.alt_geom.map(|altitude| altitude as i32);
+
+        update.ground_speed = self.gs;
+        update.track = self.track;
+        update.indicated_airspeed = self.ias;
+        update.true_airspeed = self.tas;
+        update.heading = self.mag_heading;
+        (update.vertical_rate, update.vertical_rate_source) = match (self.baro_rate, self.geom_rate)
+        {
+            (Some(rate), _) => (Some(rate as i32), Some(VerticalRateSource::Barometric)),
+            (None, Some(rate)) => (Some(rate as i32), Some(VerticalRateSource::Gnss)),
+            (None, None) => (None, None),
+        };
+        update.selected_altitude = self.nav_altitude_mcp.map(|altitude| altitude as i32);
+        update.fms_selected_altitude = self.nav_altitude_fms.map(|altitude| altitude as i32);
+        update.selected_heading = self.nav_heading;
+        update.qnh = self.nav_qnh;
+        update.adsb_version = self.version;
+        update.nac_p = self.nac_p;
+        update.sil = self.sil;
+        update.gva = self.gva;
+        update.nic_baro = self.nic_baro.map(|nic_baro| nic_baro != 0);
+        update.squawk = self.squawk.as_deref().and_then(Squawk::parse);
+        update.emergency = self
+            .emergency
+            .as_deref()
+            .map(|emergency| emergency != "none");
+        update.emergency_status = self.emergency.as_deref().map(emergency_status);
+        update.rssi = self.rssi;
+
+        let position = self.lat.zip(self.lon).map(|(lat, lon)| lat_lon(lat, lon));
+        let position_age = self.seen_pos.unwrap_or(0.0);
+        match position {
+            Some(position) if position_age < POSITION_TIMEOUT.as_secs_f64() => {
+                let mut position_update = Update::new(icao, ago(self.seen_pos));
+                position_update.source = update.source;
+                position_update.position = Some(position);
+                vec![position_update, update]
+            }
+            _ => vec![update],
+        }
+    }
+}
+
+fn emergency_status(emergency: &str) -> EmergencyStatus {
+    match emergency {
+        "none" => EmergencyStatus::None,
+        "general" => EmergencyStatus::General,
+        "lifeguard" => EmergencyStatus::Medical,
+        "minfuel" => EmergencyStatus::MinimumFuel,
+        "nordo" => EmergencyStatus::NoCommunications,
+        "unlawful" => EmergencyStatus::UnlawfulInterference,
+        "downed" => EmergencyStatus::DownedAircraft,
+        _ => EmergencyStatus::Reserved,
+    }
+}
+
+fn parse_source(source: &str) -> Source {
+    match source {
+        "adsb_icao" | "adsb_icao_nt" | "adsb_other" => Source::Adsb,
+        "adsr_icao" | "adsr_other" => Source::Adsr,
+        "tisb_icao" | "tisb_other" | "tisb_trackfile" => Source::Tisb,
+        "mlat" => Source::Mlat,
+        "mode_s" => Source::ModeS,
+        _ => Source::Unknown,
+    }
+}
+
+/// Parse the contents of an `aircraft.json` file, fetched at `now`.
+pub fn parse(json: &[u8], now: Instant) -> Result<Vec<Update>, serde_json::Error> {
+    let file: AircraftJson = serde_json::from_slice(json)?;
+    Ok(file
+        .aircraft
+        .into_iter()
+        .flat_map(|aircraft| aircraft.into_updates(now))
+        .collect())
+}
+
+/// Periodically fetches `aircraft.json` over HTTP.
+pub struct AircraftJsonPoller {
+    client: reqwest::Client,
+    url: String,
+    interval: Duration,
+}
+
+impl AircraftJsonPoller {
+    /// Poller of the file at `url`, fetching it every `interval`.
+    pub fn new(url: impl Into<String>, interval: Duration) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.into(),
+            interval,
+        }
+    }
+
+    /// Fetch the file once.
+    pub async fn poll(&self) -> Result<Vec<Update>, Error> {
+        let response = self.client.get(&self.url).send().await?;
+        let bytes = response.error_for_status()?.bytes().await?;
+        Ok(parse(&bytes, Instant::now())?)
+    }
+
+    /// Keep polling until the receiving end of the channel is dropped.
+    pub async fn run(self, updates: mpsc::Sender<Update>) {
+        let mut interval = tokio::time::interval(self.interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+
+            match self.poll().await {
+                Ok(polled) => {
+                    for update in polled {
+                        if updates.send(update).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+                Err(err) => log::warn!("Can't fetch {}: {err}.", self.url),
+            }
+
+            if updates.is_closed() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AIRCRAFT_JSON: &str = r#"{
+        "now": 1700000000.0,
+        "messages": 1234,
+        "aircraft": [
+            {
+                "hex": "4ca2d6",
+                "flight": "RYR1234 ",
+                "alt_baro": 37000,
+                "alt_geom": 37450,
+                "gs": 451.2,
+                "track": 87.5,
+                "baro_rate": -64,
+                "nav_altitude_mcp": 36992,
+                "nav_qnh": 1013.6,
+                "version": 2,
+                "nac_p": 9,
+                "squawk": "7700",
+                "emergency": "general",
+                "category": "A3",
+                "lat": 51.4,
+                "lon": -0.1,
+                "seen_pos": 0.4,
+                "seen": 0.1,
+                "rssi": -12.3
+            },
+            { "hex": "~2b0c11", "type": "tisb_other", "alt_baro": "ground", "lat": 1.0, "lon": 2.0, "seen_pos": 120.0, "seen": 2.0 },
+            { "hex": "3c6444", "altitude": 12000, "speed": 250, "vert_rate": 1024, "seen": 5 },
+            { "hex": "not hex" }
+        ]
+    }"#;
+
+    #[test]
+    fn parsing_aircraft() {
+        let now = Instant::now();
+        let updates = parse(AIRCRAFT_JSON.as_bytes(), now).unwrap();
+        assert_eq!(updates.len(), 4);
+
+        // The position is reported separately, as of when it was received.
+        let update = &updates[0];
+        assert_eq!(update.icao, Icao(0x4CA2D6));
+        assert_eq!(update.position, Some(lat_lon(51.4, -0.1)));
+        assert_eq!(update.time, now - Duration::from_millis(400));
+        assert_eq!(update.altitude, None);
+
+        let update = &updates[1];
+        assert_eq!(update.icao, Icao(0x4CA2D6));
+        assert_eq!(update.time, now - Duration::from_millis(100));
+        assert_eq!(update.callsign.as_deref(), Some("RYR1234"));
+        assert_eq!(update.altitude, Some(37000));
+        assert_eq!(update.geometric_altitude, Some(37450));
+        assert_eq!(update.on_ground, Some(false));
+        assert_eq!(update.ground_speed, Some(451.2));
+        assert_eq!(update.track, Some(87.5));
+        assert_eq!(update.vertical_rate, Some(-64));
+        assert_eq!(update.selected_altitude, Some(36992));
+        assert_eq!(update.qnh, Some(1013.6));
+        assert_eq!(update.adsb_version, Some(2));
+        assert_eq!(update.nac_p, Some(9));
+        assert_eq!(update.squawk, Some(Squawk(0x7700)));
+        assert_eq!(update.emergency, Some(true));
+        assert_eq!(update.emergency_status, Some(EmergencyStatus::General));
+        assert_eq!(update.category, Some(AircraftCategory::Large));
+        assert_eq!(update.position, None);
+        assert_eq!(update.rssi, Some(-12.3));
+
+        // Stale positions are dropped.
+        let update = &updates[2];
+        assert_eq!(update.icao, Icao::non_icao(0x2B0C11));
+        assert_eq!(update.icao.to_string(), "~2B0C11");
+        assert_eq!(update.on_ground, Some(true));
+        assert_eq!(update.position, None);
+        assert_eq!(update.source, Source::Tisb);
+        assert_eq!(update.time, now - Duration::from_secs(2));
+
+        // Field names of older dump1090 versions.
+        let update = &updates[3];
+        assert_eq!(update.altitude, Some(12000));
+        assert_eq!(update.ground_speed, Some(250.0));
+        assert_eq!(update.vertical_rate, Some(1024));
+    }
+
+    #[tokio::test]
+    async fn polling_over_http() {
+        let server = hypermocker::Server::bind().await;
+        let url = format!("http://localhost:{}/data/aircraft.json", server.port());
+
+        server
+            .anticipate("/data/aircraft.json")
+            .await
+            .respond(AIRCRAFT_JSON)
+            .await;
+
+        let poller = AircraftJsonPoller::new(url, Duration::from_secs(1));
+        let updates = poller.poll().await.unwrap();
+        assert_eq!(updates.len(), 4);
+        assert_eq!(updates[1].callsign.as_deref(), Some("RYR1234"));
+    }
+}
@@ -0,0 +1,384 @@
+//! Alerts raised when aircraft squawk an emergency code or declare an emergency, and, unless
+//! disabled, when they set the SPI or alert flags.
+
+use crate::Tracker;
+use crate::filter::Filter;
+use crate::tracker::Aircraft;
+use mode_s::adsb::EmergencyStatus;
+use mode_s::{Icao, Squawk};
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+/// Alerts which ended and were acknowledged are forgotten beyond this number.
+const MAX_ALERTS: usize = 200;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AlertKind {
+    /// Squawk 7500.
+    Hijack,
+    /// Squawk 7600.
+    RadioFailure,
+    /// Squawk 7700.
+    EmergencySquawk,
+    /// Emergency or priority status declared without an emergency squawk.
+    Emergency(EmergencyStatus),
+    /// The pilot pressed IDENT.
+    Ident,
+    /// The Mode A code was changed recently.
+    SquawkChanged,
+}
+
+impl AlertKind {
+    fn from_squawk(squawk: Squawk) -> Option<Self> {
+        match squawk.0 {
+            0x7500 => Some(AlertKind::Hijack),
+            0x7600 => Some(AlertKind::RadioFailure),
+            0x7700 => Some(AlertKind::EmergencySquawk),
+            _ => None,
+        }
+    }
+
+    /// Whether the alert is about an emergency, rather than a special condition.
+    pub fn is_emergency(self) -> bool {
+        !matches!(self, AlertKind::Ident | AlertKind::SquawkChanged)
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            AlertKind::Hijack => "Squawking 7500, unlawful interference",
+            AlertKind::RadioFailure => "Squawking 7600, radio failure",
+            AlertKind::EmergencySquawk => "Squawking 7700, emergency",
+            AlertKind::Emergency(status) => status.name(),
+            AlertKind::Ident => "Ident",
+            AlertKind::SquawkChanged => "Squawk changed",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Alert {
+    pub id: u64,
+    pub icao: Icao,
+    pub kind: AlertKind,
+    pub callsign: Option<String>,
+    pub squawk: Option<Squawk>,
+    pub raised: SystemTime,
+    /// Whether the condition still holds.
+    pub active: bool,
+    pub acknowledged: bool,
+}
+
+/// Changes to the alerts, for whoever keeps a log of them.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AlertEvent {
+    Raised(Alert),
+    Acknowledged(Alert),
+    Ended(Alert),
+}
+
+#[derive(Debug, Default)]
+pub struct Alerts {
+    /// Oldest first.
+    alerts: Vec<Alert>,
+    next_id: u64,
+    events: Vec<AlertEvent>,
+    /// Don't raise alerts for the SPI and alert flags.
+    emergencies_only: bool,
+    /// Only the aircraft matching it raise alerts.
+    filter: Option<Filter>,
+}
+
+impl Alerts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_emergencies_only(&mut self, emergencies_only: bool) {
+        self.emergencies_only = emergencies_only;
+    }
+
+    /// Only raise alerts for the aircraft matching `filter`, e.g. the ones shown on the map.
+    /// The alerts of the other aircraft end at the next check.
+    pub fn set_filter(&mut self, filter: Option<Filter>) {
+        self.filter = filter;
+    }
+
+    fn conditions(&self, aircraft: &Aircraft) -> Vec<AlertKind> {
+        let mut conditions = Vec::new();
+
+        let squawk = aircraft
+            .squawk
+            .and_then(|squawk| AlertKind::from_squawk(squawk.value));
+        let status = aircraft
+            .emergency_status
+            .map(|status| status.value)
+            .filter(|status| *status != EmergencyStatus::None);
+        let declared = aircraft.emergency.is_some_and(|emergency| emergency.value);
+        match (squawk, status) {
+            (Some(kind), _) => conditions.push(kind),
+            (None, Some(status)) => conditions.push(AlertKind::Emergency(status)),
+            (None, None) if declared => {
+                conditions.push(AlertKind::Emergency(EmergencyStatus::General))
+            }
+            (None, None) => {}
+        }
+
+        if !self.emergencies_only {
+            if aircraft.spi.is_some_and(|spi| spi.value) {
+                conditions.push(AlertKind::Ident);
+            }
+            if aircraft.alert.is_some_and(|alert| alert.value) {
+                conditions.push(AlertKind::SquawkChanged);
+            }
+        }
+
+        conditions
+    }
+
+    /// Raise alerts for the conditions which started since the last check, and end the ones
+    /// whose conditions are over, or whose aircraft are gone.
+    pub fn check(&mut self, tracker: &Tracker, now: SystemTime) {
+        let mut current = HashSet::new();
+
+        for aircraft in tracker.iter() {
+            if self
+                .filter
+                .as_ref()
+                .is_some_and(|filter| !filter.matches(aircraft))
+            {
+                continue;
+            }
+
+            for kind in self.conditions(aircraft) {
+                current.insert((aircraft.icao, kind));
+
+                let raised = self
+                    .alerts
+                    .iter()
+                    .any(|alert| alert.active && alert.icao == aircraft.icao && alert.kind == kind);
+                if raised {
+                    continue;
+                }
+
+                let alert = Alert {
+                    id: self.next_id,
+                    icao: aircraft.icao,
+                    kind,
+                    callsign: aircraft
+                        .callsign
+                        .as_ref()
+                        .map(|callsign| callsign.value.clone()),
+                    squawk: aircraft.squawk.map(|squawk| squawk.value),
+                    raised: now,
+                    active: true,
+                    acknowledged: false,
+                };
+                self.next_id += 1;
+                self.events.push(AlertEvent::Raised(alert.clone()));
+                self.alerts.push(alert);
+            }
+        }
+
+        for alert in &mut self.alerts {
+            if alert.active && !current.contains(&(alert.icao, alert.kind)) {
+                alert.active = false;
+                self.events.push(AlertEvent::Ended(alert.clone()));
+            }
+        }
+
+        let mut excess = self.alerts.len().saturating_sub(MAX_ALERTS);
+        self.alerts.retain(|alert| {
+            let forget = excess > 0 && !alert.active && alert.acknowledged;
+            if forget {
+                excess -= 1;
+            }
+            !forget
+        });
+    }
+
+    pub fn acknowledge(&mut self, id: u64) {
+        if let Some(alert) = self
+            .alerts
+            .iter_mut()
+            .find(|alert| alert.id == id && !alert.acknowledged)
+        {
+            alert.acknowledged = true;
+            self.events.push(AlertEvent::Acknowledged(alert.clone()));
+        }
+    }
+
+    pub fn acknowledge_all(&mut self) {
+        for alert in &mut self.alerts {
+            if !alert.acknowledged {
+                alert.acknowledged = true;
+                self.events.push(AlertEvent::Acknowledged(alert.clone()));
+            }
+        }
+    }
+
+    /// Every alert, newest first.
+    pub fn iter(&self) -> impl Iterator<Item = &Alert> {
+        self.alerts.iter().rev()
+    }
+
+    pub fn unacknowledged(&self) -> impl Iterator<Item = &Alert> {
+        self.iter().filter(|alert| !alert.acknowledged)
+    }
+
+    /// Whether `icao` has an alert whose condition still holds.
+    pub fn is_alerting(&self, icao: Icao) -> bool {
+        self.alerts
+            .iter()
+            .any(|alert| alert.active && alert.icao == icao)
+    }
+
+    /// Take the changes since the last call.
+    pub fn take_events(&mut self) -> Vec<AlertEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+/// How often the tracker is checked for new alerts.
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Check the aircraft in `tracker` for alerts, forever.
+pub async fn watch(alerts: Arc<RwLock<Alerts>>, tracker: Arc<RwLock<Tracker>>) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let tracker = tracker.read().unwrap();
+        alerts.write().unwrap().check(&tracker, SystemTime::now());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Update;
+    use std::time::Instant;
+
+    fn squawking(tracker: &mut Tracker, icao: Icao, squawk: u16) {
+        let mut update = Update::new(icao, Instant::now());
+        update.squawk = Some(Squawk(squawk));
+        tracker.update(update);
+    }
+
+    #[test]
+    fn raising_and_ending_alerts() {
+        let mut tracker = Tracker::default();
+        let mut alerts = Alerts::new();
+        let now = SystemTime::now();
+
+        squawking(&mut tracker, Icao(1), 0x7700);
+        squawking(&mut tracker, Icao(2), 0x1000);
+        alerts.check(&tracker, now);
+        alerts.check(&tracker, now);
+
+        let raised: Vec<_> = alerts
+            .iter()
+            .map(|alert| (alert.icao, alert.kind))
+            .collect();
+        assert_eq!(raised, [(Icao(1), AlertKind::EmergencySquawk)]);
+        assert!(alerts.is_alerting(Icao(1)));
+        assert!(!alerts.is_alerting(Icao(2)));
+
+        squawking(&mut tracker, Icao(1), 0x7600);
+        alerts.check(&tracker, now);
+        let events = alerts.take_events();
+        assert_eq!(events.len(), 3);
+        assert!(
+            matches!(&events[1], AlertEvent::Raised(alert) if alert.kind == AlertKind::RadioFailure)
+        );
+        assert!(
+            matches!(&events[2], AlertEvent::Ended(alert) if alert.kind == AlertKind::EmergencySquawk)
+        );
+        assert!(alerts.take_events().is_empty());
+    }
+
+    #[test]
+    fn declaring_emergencies() {
+        let mut tracker = Tracker::default();
+        let mut alerts = Alerts::new();
+
+        // The squawk already tells about the emergency.
+        let mut update = Update::new(Icao(1), Instant::now());
+        update.squawk = Some(Squawk(0x7700));
+        update.emergency = Some(true);
+        update.emergency_status = Some(EmergencyStatus::General);
+        tracker.update(update);
+
+        let mut update = Update::new(Icao(2), Instant::now());
+        update.emergency = Some(true);
+        update.emergency_status = Some(EmergencyStatus::Medical);
+        update.spi = Some(true);
+        tracker.update(update);
+
+        alerts.check(&tracker, SystemTime::now());
+        let mut raised: Vec<_> = alerts
+            .iter()
+            .map(|alert| (alert.icao, alert.kind))
+            .collect();
+        raised.sort_by_key(|(icao, kind)| (*icao, !kind.is_emergency()));
+        assert_eq!(
+            raised,
+            [
+                (Icao(1), AlertKind::EmergencySquawk),
+                (Icao(2), AlertKind::Emergency(EmergencyStatus::Medical)),
+                (Icao(2), AlertKind::Ident),
+            ]
+        );
+
+        alerts.set_emergencies_only(true);
+        alerts.check(&tracker, SystemTime::now());
+        assert!(
+            alerts
+                .iter()
+                .all(|alert| alert.active == alert.kind.is_emergency())
+        );
+    }
+
+    #[test]
+    fn filtering_alerts() {
+        let mut tracker = Tracker::default();
+        let mut alerts = Alerts::new();
+
+        squawking(&mut tracker, Icao(1), 0x7700);
+        squawking(&mut tracker, Icao(2), 0x7700);
+        alerts.check(&tracker, SystemTime::now());
+
+        alerts.set_filter(Some(Filter::parse("icao:000002").unwrap()));
+        alerts.check(&tracker, SystemTime::now());
+        assert!(!alerts.is_alerting(Icao(1)));
+        assert!(alerts.is_alerting(Icao(2)));
+    }
+
+    #[test]
+    fn acknowledging_alerts() {
+        let mut tracker = Tracker::default();
+        let mut alerts = Alerts::new();
+
+        squawking(&mut tracker, Icao(1), 0x7500);
+        squawking(&mut tracker, Icao(2), 0x7700);
+        alerts.check(&tracker, SystemTime::now());
+        alerts.take_events();
+
+        let id = alerts
+            .iter()
+            .find(|alert| alert.icao == Icao(1))
+            .unwrap()
+            .id;
+        alerts.acknowledge(id);
+        alerts.acknowledge(id);
+        assert_eq!(alerts.unacknowledged().count(), 1);
+        assert_eq!(alerts.take_events().len(), 1);
+
+        alerts.acknowledge_all();
+        assert_eq!(alerts.unacknowledged().count(), 0);
+        assert_eq!(alerts.take_events().len(), 1);
+        // Acknowledging does not end the alert.
+        assert!(alerts.is_alerting(Icao(1)));
+    }
+}
@@ -0,0 +1,313 @@
+//! AVR text format, served by dump1090 on port 30002 and printed by `rtl_adsb`.
+//!
+//! Each line holds a single frame in hexadecimal, like `*8D4840D6202CC371C32CE0576098;`. Lines
+//! starting with `@` instead carry a 48-bit MLAT timestamp in the first 12 hex digits.
+//!
+//! [`AvrClient`] decodes the frames of a server, a captured log or the standard input into
+//! [`Update`]s.
+
+use crate::{Decoder, Update};
+use futures::Stream;
+use mode_s::{ErrorCorrection, Frame, Position};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::fs::File;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader, Lines, Stdin};
+use tokio::net::{TcpStream, ToSocketAddrs};
+use tokio::sync::mpsc;
+
+/// A single frame read from an AVR source.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AvrFrame {
+    /// 48-bit MLAT timestamp, counting at 12 MHz, if the line had one.
+    pub timestamp: Option<u64>,
+    pub frame: Frame,
+}
+
+/// Parse a single line. Returns `None` for empty or malformed lines.
+pub fn parse_line(line: &str) -> Option<AvrFrame> {
+    let line = line.trim();
+    let body = line.strip_suffix(';')?;
+
+    let (timestamp, hex) = if let Some(body) = body.strip_prefix('*') {
+        (None, body)
+    } else if let Some(body) = body.strip_prefix('@') {
+        let timestamp = u64::from_str_radix(body.get(..12)?, 16).ok()?;
+        (Some(timestamp), body.get(12..)?)
+    } else {
+        return None;
+    };
+
+    match Frame::from_hex(hex) {
+        Ok(frame) => Some(AvrFrame { timestamp, frame }),
+        Err(err) => {
+            log::debug!("Skipping AVR line '{line}': {err}");
+            None
+        }
+    }
+}
+
+/// Reads AVR frames line by line from any async byte stream.
+pub struct AvrReader<R> {
+    lines: Lines<BufReader<R>>,
+}
+
+impl AvrReader<TcpStream> {
+    /// Connect to an AVR server, e.g. `localhost:30002`.
+    pub async fn connect(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let stream = TcpStream::connect(addr).await?;
+        Ok(Self::new(stream))
+    }
+}
+
+impl AvrReader<File> {
+    /// Read a captured log.
+    pub async fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::open(path).await?;
+        Ok(Self::new(file))
+    }
+}
+
+impl AvrReader<Stdin> {
+    /// Read frames piped into the standard input, e.g. from `rtl_adsb`.
+    pub fn stdin() -> Self {
+        Self::new(tokio::io::stdin())
+    }
+}
+
+impl<R> AvrReader<R>
+where
+    R: AsyncRead + Unpin,
+{
+    pub fn new(reader: R) -> Self {
+        Self {
+            lines: BufReader::new(reader).lines(),
+        }
+    }
+
+    /// Wait for the next frame, skipping malformed lines. Returns `None` once the stream is
+    /// closed.
+    pub async fn next_frame(&mut self) -> io::Result<Option<AvrFrame>> {
+        while let Some(line) = self.lines.next_line().await? {
+            if let Some(frame) = parse_line(&line) {
+                return Ok(Some(frame));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Turn the reader into a [`Stream`] of frames.
+    pub fn into_stream(self) -> impl Stream<Item = io::Result<AvrFrame>> {
+        futures::stream::unfold(self, |mut reader| async move {
+            match reader.next_frame().await {
+                Ok(Some(frame)) => Some((Ok(frame), reader)),
+                Ok(None) => None,
+                Err(err) => Some((Err(err), reader)),
+            }
+        })
+    }
+}
+
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Where AVR frames are read from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AvrSource {
+    /// AVR server, e.g. `localhost:30002`, reconnected to whenever the connection drops.
+    Tcp(String),
+    /// Captured log, read once.
+    File(PathBuf),
+    /// Standard input, e.g. piped from `rtl_adsb`, read once.
+    Stdin,
+}
+
+impl AvrSource {
+    /// Source named on the command line: `-` for the standard input, a file path otherwise.
+    pub fn from_arg(arg: &str) -> Self {
+        match arg {
+            "-" => AvrSource::Stdin,
+            path => AvrSource::File(path.into()),
+        }
+    }
+}
+
+/// Client of an AVR source, which decodes the frames into updates.
+pub struct AvrClient {
+    source: AvrSource,
+    decoder: Decoder,
+    receiver: Option<Arc<RwLock<Option<Position>>>>,
+}
+
+impl AvrClient {
+    pub fn new(source: AvrSource, correction: ErrorCorrection) -> Self {
+        Self {
+            source,
+            decoder: Decoder::new(correction),
+            receiver: None,
+        }
+    }
+
+    pub fn decoder_mut(&mut self) -> &mut Decoder {
+        &mut self.decoder
+    }
+
+    /// Follow the receiver location, which may be changed while the client runs, as the
+    /// reference for decoding positions.
+    pub fn with_receiver(mut self, receiver: Arc<RwLock<Option<Position>>>) -> Self {
+        self.receiver = Some(receiver);
+        self
+    }
+
+    /// Read frames until the stream is closed, sending the decoded updates. Returns
+    /// `Ok(false)` if the receiving end of the channel was dropped.
+    async fn read_frames<R>(
+        &mut self,
+        mut reader: AvrReader<R>,
+        updates: &mpsc::Sender<Update>,
+    ) -> io::Result<bool>
+    where
+        R: AsyncRead + Unpin,
+    {
+        while let Some(frame) = reader.next_frame().await? {
+            if let Some(receiver) = &self.receiver {
+                self.decoder.set_receiver(*receiver.read().unwrap());
+            }
+
+            if let Some(update) = self.decoder.decode(&frame.frame, Instant::now()) {
+                if updates.send(update).await.is_err() {
+                    return Ok(false);
+                }
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Read a file or the standard input to the end, or keep receiving frames from a server
+    /// until the receiving end of the channel is dropped.
+    pub async fn run(mut self, updates: mpsc::Sender<Update>) {
+        let addr = match self.source.clone() {
+            AvrSource::Tcp(addr) => addr,
+            AvrSource::File(path) => {
+                let result = match AvrReader::open(&path).await {
+                    Ok(reader) => self.read_frames(reader, &updates).await,
+                    Err(err) => Err(err),
+                };
+                match result {
+                    Ok(_) => log::info!("Done reading AVR frames from {}.", path.display()),
+                    Err(err) => log::warn!("Can't read AVR frames from {}: {err}.", path.display()),
+                }
+                return;
+            }
+            AvrSource::Stdin => {
+                match self.read_frames(AvrReader::stdin(), &updates).await {
+                    Ok(_) => log::info!("Done reading AVR frames from the standard input."),
+                    Err(err) => log::warn!("Can't read AVR frames from the standard input: {err}."),
+                }
+                return;
+            }
+        };
+
+        let mut backoff = MIN_BACKOFF;
+
+        loop {
+            match AvrReader::connect(&addr).await {
+                Ok(reader) => {
+                    log::info!("Connected to AVR feed at {addr}.");
+                    backoff = MIN_BACKOFF;
+
+                    match self.read_frames(reader, &updates).await {
+                        Ok(true) => log::warn!("AVR feed at {addr} closed."),
+                        Ok(false) => return,
+                        Err(err) => log::warn!("AVR feed at {addr} failed: {err}."),
+                    }
+                }
+                Err(err) => log::warn!("Can't connect to AVR feed at {addr}: {err}."),
+            }
+
+            if updates.is_closed() {
+                return;
+            }
+
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing_lines() {
+        let frame = Frame::from_hex("8D4840D6202CC371C32CE0576098").unwrap();
+
+        assert_eq!(
+            parse_line("*8D4840D6202CC371C32CE0576098;"),
+            Some(AvrFrame {
+                timestamp: None,
+                frame
+            })
+        );
+        assert_eq!(
+            parse_line("*8d4840d6202cc371c32ce0576098;\r\n"),
+            Some(AvrFrame {
+                timestamp: None,
+                frame
+            })
+        );
+        assert_eq!(
+            parse_line("@0000ABCDEF018D4840D6202CC371C32CE0576098;"),
+            Some(AvrFrame {
+                timestamp: Some(0xABCDEF01),
+                frame
+            })
+        );
+    }
+
+    #[test]
+    fn skipping_malformed_lines() {
+        assert_eq!(parse_line(""), None);
+        assert_eq!(parse_line("*8D4840D6202CC371C32CE0576098"), None);
+        assert_eq!(parse_line("*8D4840;"), None);
+        assert_eq!(parse_line("@0000AB;"), None);
+    }
+
+    #[tokio::test]
+    async fn reading_lines() {
+        let input: &[u8] = b"*8D4840D6202CC371C32CE0576098;\ngarbage\n*5D484FDEA248F5;\n";
+        let mut reader = AvrReader::new(input);
+
+        let first = reader.next_frame().await.unwrap().unwrap();
+        assert_eq!(first.frame.downlink_format(), 17);
+        let second = reader.next_frame().await.unwrap().unwrap();
+        assert_eq!(second.frame.downlink_format(), 11);
+        assert_eq!(reader.next_frame().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn decoding_files() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            file.path(),
+            "*8D4840D6202CC371C32CE0576098;\n*8D4840D6202CC371C32CE0576099;\n",
+        )
+        .unwrap();
+
+        let (tx, mut rx) = mpsc::channel(16);
+        let source = AvrSource::File(file.path().to_path_buf());
+        AvrClient::new(source, ErrorCorrection::Disabled)
+            .run(tx)
+            .await;
+
+        let update = rx.recv().await.unwrap();
+        assert_eq!(update.icao, mode_s::Icao(0x4840D6));
+        assert_eq!(update.callsign.as_deref(), Some("KLM1023"));
+        // The second frame fails the parity check.
+        assert!(rx.recv().await.is_none());
+    }
+}
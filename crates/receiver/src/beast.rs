@@ -0,0 +1,430 @@
+//! Client for the Beast binary protocol, served by dump1090 and readsb on port 30005.
+//!
+//! Every frame starts with `0x1A`, followed by the frame type, a 48-bit MLAT timestamp, the
+//! signal level and the payload. Any `0x1A` byte inside the frame is escaped by doubling it.
+
+use crate::{Decoder, Update};
+use futures::Stream;
+use mode_s::mode_ac::ModeAcReply;
+use mode_s::{ErrorCorrection, Frame, Position};
+use std::io;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::net::{TcpStream, ToSocketAddrs};
+use tokio::sync::mpsc;
+
+const ESCAPE: u8 = 0x1A;
+
+/// Payload of a Beast frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BeastPayload {
+    /// Type `1`, a Mode A or Mode C reply.
+    ModeAc([u8; 2]),
+    /// Type `2` (56 bits) or type `3` (112 bits), a Mode S frame.
+    ModeS(Frame),
+}
+
+/// A single frame received from a Beast source.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BeastFrame {
+    /// 48-bit MLAT timestamp, counting at 12 MHz. Zero if the frame was synthesized, for
+    /// example by an MLAT server.
+    pub timestamp: u64,
+    /// Raw signal level, where 255 is the full scale.
+    pub signal: u8,
+    pub payload: BeastPayload,
+}
+
+/// Timestamp readsb and mlat-client give to frames synthesized from MLAT results, `\xFF\0MLAT`.
+const MLAT_TIMESTAMP: u64 = 0xFF00_4D4C_4154;
+
+impl BeastFrame {
+    /// Whether the frame was synthesized from an MLAT result rather than received over the air.
+    pub fn is_mlat(&self) -> bool {
+        self.timestamp == 0 || self.timestamp == MLAT_TIMESTAMP
+    }
+
+    /// Signal level in dBFS.
+    pub fn rssi(&self) -> f64 {
+        let level = self.signal as f64 / 255.0;
+        10.0 * (level * level).log10()
+    }
+}
+
+/// Frame type byte and the length of the payload it carries.
+fn payload_len(frame_type: u8) -> Option<usize> {
+    match frame_type {
+        b'1' => Some(2),
+        b'2' => Some(mode_s::SHORT_FRAME_LEN),
+        b'3' => Some(mode_s::LONG_FRAME_LEN),
+        // Receiver status and configuration frames.
+        b'4' => Some(14),
+        _ => None,
+    }
+}
+
+/// Incremental, IO-free parser of a Beast byte stream.
+#[derive(Debug, Default)]
+pub struct BeastDecoder {
+    buffer: Vec<u8>,
+}
+
+impl BeastDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append received bytes.
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Take the next complete frame out of the buffer, if there is one. Garbage and frames
+    /// which fail to parse are skipped.
+    pub fn next_frame(&mut self) -> Option<BeastFrame> {
+        loop {
+            // Resynchronize on the next frame start. Without one, everything received so far
+            // is garbage, e.g. from pointing the client at a text feed.
+            let Some(start) = self.buffer.iter().position(|&byte| byte == ESCAPE) else {
+                self.buffer.clear();
+                return None;
+            };
+            self.buffer.drain(..start);
+
+            let frame_type = *self.buffer.get(1)?;
+            let Some(len) = payload_len(frame_type) else {
+                // Either garbage or an escaped 0x1A we lost sync on.
+                self.buffer.drain(..1);
+                continue;
+            };
+
+            // Unescape timestamp, signal level and payload.
+            let mut data = Vec::with_capacity(7 + len);
+            let mut i = 2;
+            while data.len() < 7 + len {
+                let byte = *self.buffer.get(i)?;
+                if byte == ESCAPE {
+                    if *self.buffer.get(i + 1)? != ESCAPE {
+                        // Unescaped 0x1A means a new frame started, this one is truncated.
+                        self.buffer.drain(..i);
+                        break;
+                    }
+                    i += 1;
+                }
+                data.push(byte);
+                i += 1;
+            }
+
+            if data.len() < 7 + len {
+                continue;
+            }
+            self.buffer.drain(..i);
+
+            if let Some(frame) = parse(frame_type, &data) {
+                return Some(frame);
+            }
+        }
+    }
+}
+
+fn parse(frame_type: u8, data: &[u8]) -> Option<BeastFrame> {
+    let timestamp = data[..6]
+        .iter()
+        .fold(0u64, |acc, &byte| (acc << 8) | byte as u64);
+    let signal = data[6];
+    let payload = &data[7..];
+
+    let payload = match frame_type {
+        b'1' => BeastPayload::ModeAc([payload[0], payload[1]]),
+        b'2' | b'3' => match Frame::new(payload) {
+            Ok(frame) => BeastPayload::ModeS(frame),
+            Err(err) => {
+                log::debug!("Skipping Beast frame: {err}");
+                return None;
+            }
+        },
+        _ => return None,
+    };
+
+    Some(BeastFrame {
+        timestamp,
+        signal,
+        payload,
+    })
+}
+
+/// Reads Beast frames from any async byte stream, such as a TCP connection or a file.
+pub struct BeastReader<R> {
+    reader: R,
+    decoder: BeastDecoder,
+}
+
+impl BeastReader<TcpStream> {
+    /// Connect to a Beast server, e.g. `localhost:30005`.
+    pub async fn connect(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let stream = TcpStream::connect(addr).await?;
+        Ok(Self::new(stream))
+    }
+}
+
+impl<R> BeastReader<R>
+where
+    R: AsyncRead + Unpin,
+{
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            decoder: BeastDecoder::new(),
+        }
+    }
+
+    /// Wait for the next frame. Returns `None` once the stream is closed.
+    pub async fn next_frame(&mut self) -> io::Result<Option<BeastFrame>> {
+        let mut buffer = [0; 4096];
+        loop {
+            if let Some(frame) = self.decoder.next_frame() {
+                return Ok(Some(frame));
+            }
+
+            let read = self.reader.read(&mut buffer).await?;
+            if read == 0 {
+                return Ok(None);
+            }
+            self.decoder.push(&buffer[..read]);
+        }
+    }
+
+    /// Turn the reader into a [`Stream`] of frames.
+    pub fn into_stream(self) -> impl Stream<Item = io::Result<BeastFrame>> {
+        futures::stream::unfold(self, |mut reader| async move {
+            match reader.next_frame().await {
+                Ok(Some(frame)) => Some((Ok(frame), reader)),
+                Ok(None) => None,
+                Err(err) => Some((Err(err), reader)),
+            }
+        })
+    }
+}
+
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// TCP client of a Beast feed, which decodes the frames and reconnects whenever the connection
+/// drops.
+pub struct BeastClient {
+    addr: String,
+    decoder: Decoder,
+    receiver: Option<Arc<RwLock<Option<Position>>>>,
+}
+
+impl BeastClient {
+    /// Client of the feed at `addr`, e.g. `localhost:30005`.
+    pub fn new(addr: impl Into<String>, correction: ErrorCorrection) -> Self {
+        Self {
+            addr: addr.into(),
+            decoder: Decoder::new(correction),
+            receiver: None,
+        }
+    }
+
+    pub fn decoder_mut(&mut self) -> &mut Decoder {
+        &mut self.decoder
+    }
+
+    /// Follow the receiver location, which may be changed while the client runs, as the
+    /// reference for decoding positions.
+    pub fn with_receiver(mut self, receiver: Arc<RwLock<Option<Position>>>) -> Self {
+        self.receiver = Some(receiver);
+        self
+    }
+
+    /// Read frames until the stream is closed, sending the decoded updates and the Mode A/C
+    /// replies. Returns `Ok(false)` if the receiving end of either channel was dropped.
+    async fn read_frames<R>(
+        &mut self,
+        mut reader: BeastReader<R>,
+        updates: &mpsc::Sender<Update>,
+        mode_ac: &mpsc::Sender<(ModeAcReply, Instant)>,
+    ) -> io::Result<bool>
+    where
+        R: AsyncRead + Unpin,
+    {
+        while let Some(frame) = reader.next_frame().await? {
+            let now = Instant::now();
+            if let Some(receiver) = &self.receiver {
+                self.decoder.set_receiver(*receiver.read().unwrap());
+            }
+
+            let sent = match frame.payload {
+                BeastPayload::ModeAc(payload) => {
+                    let reply = ModeAcReply::from_beast(payload);
+                    mode_ac.send((reply, now)).await.is_ok()
+                }
+                BeastPayload::ModeS(_) => match self.decoder.decode_beast(&frame, now) {
+                    Some(update) => updates.send(update).await.is_ok(),
+                    None => true,
+                },
+            };
+
+            if !sent {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Keep receiving frames until the receiving end of either channel is dropped.
+    pub async fn run(
+        mut self,
+        updates: mpsc::Sender<Update>,
+        mode_ac: mpsc::Sender<(ModeAcReply, Instant)>,
+    ) {
+        let mut backoff = MIN_BACKOFF;
+
+        loop {
+            match BeastReader::connect(&self.addr).await {
+                Ok(reader) => {
+                    log::info!("Connected to Beast feed at {}.", self.addr);
+                    backoff = MIN_BACKOFF;
+
+                    match self.read_frames(reader, &updates, &mode_ac).await {
+                        Ok(true) => log::warn!("Beast feed at {} closed.", self.addr),
+                        Ok(false) => return,
+                        Err(err) => log::warn!("Beast feed at {} failed: {err}.", self.addr),
+                    }
+                }
+                Err(err) => {
+                    log::warn!("Can't connect to Beast feed at {}: {err}.", self.addr);
+                }
+            }
+
+            if updates.is_closed() || mode_ac.is_closed() {
+                return;
+            }
+
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::TcpListener;
+
+    /// Encode a frame, escaping it like dump1090 does.
+    fn encode(frame_type: u8, timestamp: u64, signal: u8, payload: &[u8]) -> Vec<u8> {
+        let mut data = timestamp.to_be_bytes()[2..].to_vec();
+        data.push(signal);
+        data.extend_from_slice(payload);
+
+        let mut encoded = vec![ESCAPE, frame_type];
+        for byte in data {
+            encoded.push(byte);
+            if byte == ESCAPE {
+                encoded.push(ESCAPE);
+            }
+        }
+        encoded
+    }
+
+    fn long_frame() -> Frame {
+        Frame::from_hex("8D4840D6202CC371C32CE0576098").unwrap()
+    }
+
+    #[test]
+    fn decoding_frames() {
+        let mut decoder = BeastDecoder::new();
+        decoder.push(&[0x00, 0xFF]); // Garbage before the first frame.
+        decoder.push(&encode(b'3', 0x1A1A_0000_001A, 0x1A, long_frame().bytes()));
+        decoder.push(&encode(b'1', 0, 100, &[0x12, 0x34]));
+
+        assert_eq!(
+            decoder.next_frame(),
+            Some(BeastFrame {
+                timestamp: 0x1A1A_0000_001A,
+                signal: 0x1A,
+                payload: BeastPayload::ModeS(long_frame()),
+            })
+        );
+        assert_eq!(
+            decoder.next_frame(),
+            Some(BeastFrame {
+                timestamp: 0,
+                signal: 100,
+                payload: BeastPayload::ModeAc([0x12, 0x34]),
+            })
+        );
+        assert_eq!(decoder.next_frame(), None);
+    }
+
+    #[test]
+    fn waiting_for_partial_frames() {
+        let encoded = encode(b'3', 42, 200, long_frame().bytes());
+        let mut decoder = BeastDecoder::new();
+
+        decoder.push(&encoded[..10]);
+        assert_eq!(decoder.next_frame(), None);
+
+        decoder.push(&encoded[10..]);
+        assert_eq!(decoder.next_frame().map(|frame| frame.timestamp), Some(42));
+    }
+
+    #[test]
+    fn skipping_truncated_frames() {
+        let mut truncated = encode(b'3', 1, 200, long_frame().bytes());
+        truncated.truncate(12);
+
+        let mut decoder = BeastDecoder::new();
+        decoder.push(&truncated);
+        decoder.push(&encode(b'3', 2, 200, long_frame().bytes()));
+
+        assert_eq!(decoder.next_frame().map(|frame| frame.timestamp), Some(2));
+    }
+
+    #[test]
+    fn discarding_garbage() {
+        let mut decoder = BeastDecoder::new();
+
+        for _ in 0..1000 {
+            decoder.push(b"*8D4840D6202CC371C32CE0576098;\n");
+            assert_eq!(decoder.next_frame(), None);
+        }
+        assert!(decoder.buffer.is_empty());
+
+        // Frames are still found once the garbage is over.
+        decoder.push(&encode(b'3', 3, 200, long_frame().bytes()));
+        assert_eq!(decoder.next_frame().map(|frame| frame.timestamp), Some(3));
+    }
+
+    #[tokio::test]
+    async fn reading_from_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            for timestamp in 1..=3 {
+                let frame = encode(b'3', timestamp, 255, long_frame().bytes());
+                socket.write_all(&frame).await.unwrap();
+            }
+        });
+
+        let frames: Vec<_> = BeastReader::connect(addr)
+            .await
+            .unwrap()
+            .into_stream()
+            .map(|frame| frame.unwrap())
+            .collect()
+            .await;
+
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[2].timestamp, 3);
+        assert_eq!(frames[0].rssi(), 0.0);
+    }
+}
@@ -0,0 +1,336 @@
+//! Coverage of the receiver: the farthest position received in each bearing bucket, for a few
+//! altitude bands, over a rolling period.
+
+use crate::{Source, Tracker};
+use mode_s::{Position, geo};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Number of bearing buckets around the receiver.
+pub const BEARING_BUCKETS: usize = 72;
+
+/// Width of a bearing bucket, in degrees.
+pub const BUCKET_WIDTH: f64 = 360.0 / BEARING_BUCKETS as f64;
+
+/// Positions farther than this, in meters, are taken to be decoding errors. It is beyond the
+/// radio horizon of an aircraft at FL600.
+const MAX_RANGE: f64 = 450.0 * geo::METERS_PER_NAUTICAL_MILE;
+
+/// How long the farthest positions are kept before newer, closer ones may replace them.
+pub const DEFAULT_RETENTION: Duration = Duration::from_secs(7 * 24 * 3600);
+
+/// Altitude bands tracked separately, as low aircraft are lost behind terrain much sooner.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AltitudeBand {
+    Low,
+    Medium,
+    High,
+}
+
+impl AltitudeBand {
+    pub const ALL: [AltitudeBand; 3] =
+        [AltitudeBand::Low, AltitudeBand::Medium, AltitudeBand::High];
+
+    pub fn from_altitude(altitude: i32) -> Self {
+        match altitude {
+            ..10_000 => AltitudeBand::Low,
+            10_000..25_000 => AltitudeBand::Medium,
+            _ => AltitudeBand::High,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::ALL.get(index).copied()
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AltitudeBand::Low => "Below 10000 ft",
+            AltitudeBand::Medium => "10000 to 25000 ft",
+            AltitudeBand::High => "Above 25000 ft",
+        }
+    }
+}
+
+/// Bearing of the middle of a bucket, in degrees.
+pub fn bucket_bearing(bucket: usize) -> f64 {
+    (bucket as f64 + 0.5) * BUCKET_WIDTH
+}
+
+/// Farthest position of a bucket.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CoveragePoint {
+    pub position: Position,
+    /// Distance from the receiver, in meters.
+    pub range: f64,
+    pub seen: SystemTime,
+}
+
+#[derive(Clone, Debug)]
+pub struct Coverage {
+    receiver: Option<Position>,
+    retention: Duration,
+    points: [[Option<CoveragePoint>; BEARING_BUCKETS]; AltitudeBand::ALL.len()],
+    /// Last time [`Coverage::record_tracker`] ran, so positions are only looked at once.
+    last_scan: Option<Instant>,
+}
+
+impl Default for Coverage {
+    fn default() -> Self {
+        Self::new(DEFAULT_RETENTION)
+    }
+}
+
+impl Coverage {
+    pub fn new(retention: Duration) -> Self {
+        Self {
+            receiver: None,
+            retention,
+            points: [[None; BEARING_BUCKETS]; AltitudeBand::ALL.len()],
+            last_scan: None,
+        }
+    }
+
+    pub fn receiver(&self) -> Option<Position> {
+        self.receiver
+    }
+
+    /// Set the position ranges are measured from. Moving the receiver clears the coverage.
+    pub fn set_receiver(&mut self, receiver: Option<Position>) {
+        if self.receiver != receiver {
+            self.receiver = receiver;
+            self.clear();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.points = [[None; BEARING_BUCKETS]; AltitudeBand::ALL.len()];
+    }
+
+    /// Record a position received at `seen`. Returns whether it is the farthest of its bucket.
+    pub fn record(&mut self, band: AltitudeBand, position: Position, seen: SystemTime) -> bool {
+        let Some(receiver) = self.receiver else {
+            return false;
+        };
+
+        let range = geo::distance(receiver, position);
+        if range > MAX_RANGE {
+            return false;
+        }
+
+        let bucket = (geo::bearing(receiver, position) / BUCKET_WIDTH) as usize % BEARING_BUCKETS;
+        let point = &mut self.points[band.index()][bucket];
+        let farther = point.is_none_or(|point| point.range < range);
+        if farther {
+            *point = Some(CoveragePoint {
+                position,
+                range,
+                seen,
+            });
+        }
+
+        farther
+    }
+
+    /// Forget the points older than the retention period.
+    pub fn expire(&mut self, now: SystemTime) {
+        let retention = self.retention;
+        for point in self.points.iter_mut().flatten() {
+            let expired = point.is_some_and(|point| {
+                now.duration_since(point.seen)
+                    .is_ok_and(|age| age > retention)
+            });
+            if expired {
+                *point = None;
+            }
+        }
+    }
+
+    /// Farthest point of every bucket in `band`, by bucket.
+    pub fn points(&self, band: AltitudeBand) -> &[Option<CoveragePoint>; BEARING_BUCKETS] {
+        &self.points[band.index()]
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (AltitudeBand, &CoveragePoint)> {
+        AltitudeBand::ALL.into_iter().flat_map(move |band| {
+            self.points(band)
+                .iter()
+                .flatten()
+                .map(move |point| (band, point))
+        })
+    }
+
+    pub fn max_range(&self, band: AltitudeBand) -> Option<f64> {
+        self.points(band)
+            .iter()
+            .flatten()
+            .map(|point| point.range)
+            .max_by(f64::total_cmp)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    /// Record the positions received since the last call. Rebroadcast positions are skipped,
+    /// as they were not received from the aircraft.
+    pub fn record_tracker(&mut self, tracker: &Tracker, now: Instant, system_now: SystemTime) {
+        for aircraft in tracker.iter() {
+            let Some(position) = aircraft.position else {
+                continue;
+            };
+            if matches!(position.source, Source::Tisb | Source::Adsr)
+                || self.last_scan.is_some_and(|scan| position.updated <= scan)
+            {
+                continue;
+            }
+
+            let band = if aircraft.on_ground.is_some_and(|on_ground| on_ground.value) {
+                AltitudeBand::Low
+            } else if let Some(altitude) = aircraft.altitude {
+                AltitudeBand::from_altitude(altitude.value)
+            } else {
+                continue;
+            };
+
+            let seen = system_now - position.age(now);
+            self.record(band, position.value, seen);
+        }
+
+        self.last_scan = Some(now);
+    }
+}
+
+/// How often the tracker is scanned for new positions.
+const SCAN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Record the positions of the aircraft in `tracker`, measured from `receiver`, forever.
+pub async fn record(
+    coverage: Arc<RwLock<Coverage>>,
+    tracker: Arc<RwLock<Tracker>>,
+    receiver: Arc<RwLock<Option<Position>>>,
+) {
+    let mut interval = tokio::time::interval(SCAN_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let receiver = *receiver.read().unwrap();
+        let tracker = tracker.read().unwrap();
+        let mut coverage = coverage.write().unwrap();
+        coverage.set_receiver(receiver);
+        coverage.record_tracker(&tracker, Instant::now(), SystemTime::now());
+        coverage.expire(SystemTime::now());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Update;
+    use mode_s::Icao;
+    use mode_s::geo::lat_lon;
+
+    fn coverage() -> Coverage {
+        let mut coverage = Coverage::default();
+        coverage.set_receiver(Some(lat_lon(50.0, 10.0)));
+        coverage
+    }
+
+    #[test]
+    fn keeping_farthest_positions() {
+        let mut coverage = coverage();
+        let receiver = coverage.receiver().unwrap();
+        let now = SystemTime::now();
+
+        let near = geo::destination(receiver, 92.0, 50_000.0);
+        let far = geo::destination(receiver, 93.0, 100_000.0);
+        assert!(coverage.record(AltitudeBand::High, near, now));
+        assert!(coverage.record(AltitudeBand::High, far, now));
+        assert!(!coverage.record(AltitudeBand::High, near, now));
+
+        let point = coverage.points(AltitudeBand::High)[18].unwrap();
+        assert_eq!(point.position, far);
+        assert!((point.range - 100_000.0).abs() < 1e-3);
+        assert_eq!(coverage.points(AltitudeBand::Low)[18], None);
+
+        // Decoding errors on the other side of the world.
+        assert!(!coverage.record(AltitudeBand::High, lat_lon(-50.0, -170.0), now));
+        assert_eq!(coverage.iter().count(), 1);
+        assert!((coverage.max_range(AltitudeBand::High).unwrap() - 100_000.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn expiring_and_moving() {
+        let mut coverage = coverage();
+        let receiver = coverage.receiver().unwrap();
+        let start = SystemTime::now();
+
+        coverage.record(
+            AltitudeBand::Low,
+            geo::destination(receiver, 0.0, 10_000.0),
+            start,
+        );
+        coverage.expire(start + DEFAULT_RETENTION);
+        assert!(!coverage.is_empty());
+        coverage.expire(start + DEFAULT_RETENTION + Duration::from_secs(1));
+        assert!(coverage.is_empty());
+
+        coverage.record(
+            AltitudeBand::Low,
+            geo::destination(receiver, 0.0, 10_000.0),
+            start,
+        );
+        coverage.set_receiver(Some(receiver));
+        assert!(!coverage.is_empty());
+        coverage.set_receiver(Some(lat_lon(40.0, 0.0)));
+        assert!(coverage.is_empty());
+    }
+
+    #[test]
+    fn recording_tracked_aircraft() {
+        let mut coverage = coverage();
+        let receiver = coverage.receiver().unwrap();
+        let start = Instant::now();
+        let mut tracker = Tracker::default();
+
+        let mut update = Update::new(Icao(1), start);
+        update.position = Some(geo::destination(receiver, 180.0, 80_000.0));
+        update.altitude = Some(12_000);
+        update.source = Source::Adsb;
+        tracker.update(update);
+
+        let mut update = Update::new(Icao(2), start);
+        update.position = Some(geo::destination(receiver, 270.0, 80_000.0));
+        update.altitude = Some(12_000);
+        update.source = Source::Tisb;
+        tracker.update(update);
+
+        let mut update = Update::new(Icao(3), start);
+        update.position = Some(geo::destination(receiver, 0.0, 2_000.0));
+        update.on_ground = Some(true);
+        tracker.update(update);
+
+        coverage.record_tracker(&tracker, start, SystemTime::now());
+        let points: Vec<_> = coverage
+            .iter()
+            .map(|(band, point)| (band, point.range.round()))
+            .collect();
+        assert_eq!(
+            points,
+            [
+                (AltitudeBand::Low, 2_000.0),
+                (AltitudeBand::Medium, 80_000.0)
+            ]
+        );
+
+        // Positions already looked at are not recorded again.
+        coverage.clear();
+        coverage.record_tracker(&tracker, start + SCAN_INTERVAL, SystemTime::now());
+        assert!(coverage.is_empty());
+    }
+}
@@ -0,0 +1,435 @@
+//! Turns raw Mode S frames into [`Update`]s.
+
+use crate::beast::{BeastFrame, BeastPayload};
+use crate::{Source, Update};
+use mode_s::adsb::{AirspeedType, AltitudeSource, EmergencyStatus, Me, VerticalRateSource};
+use mode_s::commb::{CommB, TargetAltitudeSource};
+use mode_s::{CprDecoder, ErrorCorrection, Frame, Icao, Message, Position};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// How long an address is trusted after the last frame with a verified parity.
+const ADDRESS_TIMEOUT: Duration = Duration::from_secs(60);
+
+struct AircraftState {
+    cpr: CprDecoder,
+    last_verified: Instant,
+}
+
+/// Stateful decoder of raw frames. It keeps track of the addresses seen in frames with a
+/// verified parity, and of the CPR state of each aircraft.
+pub struct Decoder {
+    correction: ErrorCorrection,
+    receiver: Option<Position>,
+    aircraft: HashMap<Icao, AircraftState>,
+    last_prune: Option<Instant>,
+}
+
+impl Decoder {
+    pub fn new(correction: ErrorCorrection) -> Self {
+        Self {
+            correction,
+            receiver: None,
+            aircraft: HashMap::new(),
+            last_prune: None,
+        }
+    }
+
+    /// Set the receiver position, used as the reference for decoding positions.
+    pub fn set_receiver(&mut self, receiver: Option<Position>) {
+        self.receiver = receiver;
+    }
+
+    /// Decode a frame received from a Beast source, including its signal level.
+    pub fn decode_beast(&mut self, frame: &BeastFrame, time: Instant) -> Option<Update> {
+        match frame.payload {
+            BeastPayload::ModeS(mode_s) => {
+                let mut update = self.decode(&mode_s, time)?;
+                update.rssi = Some(frame.rssi());
+                if frame.is_mlat() {
+                    update.source = Source::Mlat;
+                }
+                Some(update)
+            }
+            BeastPayload::ModeAc(_) => None,
+        }
+    }
+
+    /// Decode a frame received at `time`. Returns `None` if the frame is corrupted, comes from
+    /// an unknown address, or carries nothing of interest.
+    pub fn decode(&mut self, frame: &Frame, time: Instant) -> Option<Update> {
+        self.prune(time);
+
+        let checked = match mode_s::check(frame, self.correction) {
+            Ok(checked) => checked,
+            Err(err) => {
+                log::trace!("Dropping {frame}: {err}");
+                return None;
+            }
+        };
+
+        let message = Message::decode(&checked.frame);
+        // Non-transponder devices with an anonymous address and TIS-B targets identified by
+        // a track file number may share their address with an unrelated aircraft.
+        let non_icao = matches!(
+            message,
+            Message::ExtendedSquitter {
+                df: 18,
+                capability: 1 | 5,
+                ..
+            }
+        );
+        let icao = if non_icao {
+            Icao::non_icao(checked.icao.0)
+        } else {
+            checked.icao
+        };
+
+        let verified = matches!(message.downlink_format(), 11 | 17 | 18);
+        if verified {
+            self.aircraft
+                .entry(icao)
+                .and_modify(|state| state.last_verified = time)
+                .or_insert_with(|| AircraftState {
+                    cpr: CprDecoder::new(),
+                    last_verified: time,
+                });
+        }
+
+        // Addresses recovered from the parity are only trusted if they were seen before,
+        // otherwise every corrupted frame would create a ghost aircraft.
+        let receiver = self.receiver;
+        let state = self.aircraft.get_mut(&icao)?;
+        let mut update = Update::new(icao, time);
+        update.source = Source::ModeS;
+
+        match message {
+            Message::SurveillanceAltitude {
+                flight_status,
+                altitude,
+                ..
+            } => {
+                update.altitude = altitude;
+                update.on_ground = flight_status.on_ground();
+                update.alert = Some(flight_status.alert());
+                update.spi = Some(flight_status.spi());
+            }
+            Message::CommBAltitude {
+                flight_status,
+                altitude,
+                mb,
+                ..
+            } => {
+                update.altitude = altitude;
+                update.on_ground = flight_status.on_ground();
+                update.alert = Some(flight_status.alert());
+                update.spi = Some(flight_status.spi());
+                if let Some(comm_b) = CommB::infer(mb, altitude) {
+                    apply_comm_b(&mut update, comm_b);
+                }
+            }
+            Message::SurveillanceIdentity {
+                flight_status,
+                squawk,
+                ..
+            } => {
+                update.squawk = Some(squawk);
+                update.on_ground = flight_status.on_ground();
+                update.alert = Some(flight_status.alert());
+                update.spi = Some(flight_status.spi());
+            }
+            Message::CommBIdentity {
+                flight_status,
+                squawk,
+                mb,
+                ..
+            } => {
+                update.squawk = Some(squawk);
+                update.on_ground = flight_status.on_ground();
+                update.alert = Some(flight_status.alert());
+                update.spi = Some(flight_status.spi());
+                if let Some(comm_b) = CommB::infer(mb, None) {
+                    apply_comm_b(&mut update, comm_b);
+                }
+            }
+            Message::AllCallReply { capability, .. } => {
+                update.on_ground = match capability {
+                    4 => Some(true),
+                    5 => Some(false),
+                    _ => None,
+                };
+            }
+            Message::ExtendedSquitter {
+                df: 18,
+                capability: 3 | 4 | 7,
+                ..
+            } => {
+                // Coarse TIS-B and management messages use a different format.
+                return None;
+            }
+            Message::ExtendedSquitter {
+                df, capability, me, ..
+            } => {
+                update.source = match (df, capability) {
+                    (18, 2 | 5) => Source::Tisb,
+                    (18, 6) => Source::Adsr,
+                    _ => Source::Adsb,
+                };
+                apply_me(&mut update, me, &mut state.cpr, receiver);
+            }
+            Message::Other { .. } => return None,
+        }
+
+        Some(update)
+    }
+
+    /// Forget aircraft which were not heard for a while.
+    fn prune(&mut self, now: Instant) {
+        if self
+            .last_prune
+            .is_some_and(|last| now.saturating_duration_since(last) < ADDRESS_TIMEOUT)
+        {
+            return;
+        }
+
+        self.aircraft.retain(|_, state| {
+            now.saturating_duration_since(state.last_verified) < ADDRESS_TIMEOUT
+        });
+        self.last_prune = Some(now);
+    }
+}
+
+fn apply_me(update: &mut Update, me: Me, cpr: &mut CprDecoder, receiver: Option<Position>) {
+    match me {
+        Me::Identification(identification) => {
+            update.callsign = Some(identification.callsign);
+            update.category = Some(identification.category);
+        }
+        Me::AirbornePosition(position) => {
+            match position.altitude_source {
+                AltitudeSource::Barometric => update.altitude = position.altitude,
+                AltitudeSource::Gnss => update.geometric_altitude = position.altitude,
+            }
+            update.on_ground = Some(false);
+            update.position_type_code = Some(position.type_code);
+            update.nic_supplement_b = Some(position.nic_supplement_b);
+            update.position = cpr.decode(position.cpr, false, update.time, receiver);
+        }
+        Me::SurfacePosition(position) => {
+            update.on_ground = Some(true);
+            update.ground_speed = position.ground_speed;
+            update.track = position.track;
+            update.position_type_code = Some(position.type_code);
+            update.position = cpr.decode(position.cpr, true, update.time, receiver);
+        }
+        Me::AirborneVelocity(velocity) => {
+            update.ground_speed = velocity.ground_speed;
+            update.track = velocity.track;
+            update.heading = velocity.heading;
+            match velocity.airspeed {
+                Some(airspeed) if airspeed.airspeed_type == AirspeedType::Indicated => {
+                    update.indicated_airspeed = Some(airspeed.speed)
+                }
+                Some(airspeed) => update.true_airspeed = Some(airspeed.speed),
+                None => {}
+            }
+            update.vertical_rate = velocity.vertical_rate;
+            update.vertical_rate_source = velocity
+                .vertical_rate
+                .map(|_| velocity.vertical_rate_source);
+            update.gnss_altitude_difference = velocity.gnss_altitude_difference;
+        }
+        Me::AircraftStatus(status) => {
+            update.squawk = Some(status.squawk);
+            update.emergency = Some(status.emergency != EmergencyStatus::None);
+            update.emergency_status = Some(status.emergency);
+        }
+        Me::TargetState(state) => {
+            match state.selected_altitude_source {
+                TargetAltitudeSource::Fms => update.fms_selected_altitude = state.selected_altitude,
+                _ => update.selected_altitude = state.selected_altitude,
+            }
+            update.selected_heading = state.selected_heading;
+            update.qnh = state.qnh;
+            if state.modes != Default::default() {
+                update.navigation_modes = Some(state.modes);
+            }
+            update.nac_p = Some(state.nac_p);
+            update.nic_baro = Some(state.nic_baro);
+            update.sil = Some(state.sil);
+        }
+        Me::OperationalStatus(status) => {
+            update.adsb_version = Some(status.version);
+            update.nic_supplement_a = status.nic_supplement_a;
+            update.nic_supplement_c = status.nic_supplement_c;
+            update.nac_p = status.nac_p;
+            update.sil = status.sil;
+            update.gva = status.gva;
+            update.nic_baro = status.nic_baro;
+        }
+        Me::Unknown { .. } => {}
+    }
+}
+
+fn apply_comm_b(update: &mut Update, comm_b: CommB) {
+    match comm_b {
+        CommB::Identification(callsign) => update.callsign = Some(callsign),
+        CommB::ResolutionAdvisory(advisory) => update.resolution_advisory = Some(advisory),
+        CommB::SelectedVerticalIntention(intention) => {
+            update.selected_altitude = intention.mcp_altitude;
+            update.fms_selected_altitude = intention.fms_altitude;
+            update.qnh = intention.qnh;
+            if intention.modes != Default::default() {
+                update.navigation_modes = Some(intention.modes);
+            }
+        }
+        CommB::TrackAndTurn(report) => {
+            update.roll = report.roll;
+            update.track = report.track;
+            update.ground_speed = report.ground_speed;
+            update.track_rate = report.track_rate;
+            update.true_airspeed = report.true_airspeed;
+        }
+        CommB::HeadingAndSpeed(report) => {
+            update.heading = report.heading;
+            update.indicated_airspeed = report.indicated_airspeed;
+            update.mach = report.mach;
+            update.vertical_rate = report.barometric_vertical_rate;
+            update.vertical_rate_source = report
+                .barometric_vertical_rate
+                .map(|_| VerticalRateSource::Barometric);
+        }
+        CommB::DataLinkCapability(_) | CommB::CommonUsageCapability(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(hex: &str) -> Frame {
+        Frame::from_hex(hex).unwrap()
+    }
+
+    #[test]
+    fn decoding_position_pair() {
+        let mut decoder = Decoder::new(ErrorCorrection::Disabled);
+        let start = Instant::now();
+
+        let update = decoder
+            .decode(&frame("8D40621D58C382D690C8AC2863A7"), start)
+            .unwrap();
+        assert_eq!(update.icao, Icao(0x40621D));
+        assert_eq!(update.altitude, Some(38000));
+        assert_eq!(update.position, None);
+
+        let update = decoder
+            .decode(
+                &frame("8D40621D58C386435CC412692AD6"),
+                start + Duration::from_secs(1),
+            )
+            .unwrap();
+        let position = update.position.unwrap();
+        assert!((position.y() - 52.2657).abs() < 1e-3);
+        assert!((position.x() - 3.9389).abs() < 1e-3);
+    }
+
+    #[test]
+    fn classifying_sources() {
+        let mut decoder = Decoder::new(ErrorCorrection::Disabled);
+        let now = Instant::now();
+
+        let adsb = frame("8D40621D58C382D690C8AC2863A7");
+        assert_eq!(decoder.decode(&adsb, now).unwrap().source, Source::Adsb);
+
+        // The same message element rebroadcast as fine TIS-B, DF18 with CF 2.
+        let mut tisb = adsb.bytes().to_vec();
+        tisb[0] = 0x92;
+        let parity = mode_s::crc::crc24(&tisb[..11]);
+        tisb[11..].copy_from_slice(&parity.to_be_bytes()[1..]);
+        let tisb = Frame::new(&tisb).unwrap();
+        assert_eq!(decoder.decode(&tisb, now).unwrap().source, Source::Tisb);
+
+        // As TIS-B with a track file number, DF18 with CF 5, it is another target.
+        let mut track_file = adsb.bytes().to_vec();
+        track_file[0] = 0x95;
+        let parity = mode_s::crc::crc24(&track_file[..11]);
+        track_file[11..].copy_from_slice(&parity.to_be_bytes()[1..]);
+        let update = decoder
+            .decode(&Frame::new(&track_file).unwrap(), now)
+            .unwrap();
+        assert_eq!(update.source, Source::Tisb);
+        assert_eq!(update.icao, Icao::non_icao(0x40621D));
+
+        let mlat = BeastFrame {
+            timestamp: 0,
+            signal: 255,
+            payload: BeastPayload::ModeS(adsb),
+        };
+        assert_eq!(
+            decoder.decode_beast(&mlat, now).unwrap().source,
+            Source::Mlat
+        );
+
+        // DF4 altitude reply from the same aircraft.
+        let mut df4 = [0x20, 0x00, 0x17, 0x18, 0, 0, 0];
+        let parity = mode_s::crc::crc24(&df4[..4]) ^ 0x40621D;
+        df4[4..].copy_from_slice(&parity.to_be_bytes()[1..]);
+        let update = decoder.decode(&Frame::new(&df4).unwrap(), now).unwrap();
+        assert_eq!(update.source, Source::ModeS);
+    }
+
+    #[test]
+    fn decoding_target_state() {
+        let mut decoder = Decoder::new(ErrorCorrection::Disabled);
+
+        let update = decoder
+            .decode(&frame("8DA05629EA21485CBF3F8CADAEEB"), Instant::now())
+            .unwrap();
+        assert_eq!(update.selected_altitude, Some(16992));
+        assert_eq!(update.fms_selected_altitude, None);
+        assert!((update.selected_heading.unwrap() - 66.8).abs() < 0.1);
+        assert_eq!(update.nac_p, Some(9));
+        assert_eq!(update.navigation_modes.unwrap().autopilot, Some(true));
+    }
+
+    #[test]
+    fn decoding_comm_b() {
+        let mut decoder = Decoder::new(ErrorCorrection::Disabled);
+        let now = Instant::now();
+
+        // DF20 with BDS 4,0, made known to the decoder by a DF11 reply from the same address.
+        let df20 = frame("A000029C85E42F313000007047D3");
+        let icao = mode_s::crc::residual(&df20);
+
+        let mut df11 = [0x5D, 0, 0, 0, 0, 0, 0];
+        df11[1..4].copy_from_slice(&icao.to_be_bytes()[1..]);
+        let parity = mode_s::crc::crc24(&df11[..4]);
+        df11[4..].copy_from_slice(&parity.to_be_bytes()[1..]);
+        decoder.decode(&Frame::new(&df11).unwrap(), now).unwrap();
+
+        let update = decoder.decode(&df20, now).unwrap();
+        assert_eq!(update.selected_altitude, Some(3008));
+        assert_eq!(update.fms_selected_altitude, Some(3008));
+    }
+
+    #[test]
+    fn ignoring_unknown_parity_addresses() {
+        let mut decoder = Decoder::new(ErrorCorrection::Disabled);
+        let now = Instant::now();
+
+        // DF20 from 400940, which was never heard in a verified frame.
+        let df20 = frame("A0001839CA3800315800007448D9");
+        assert_eq!(decoder.decode(&df20, now), None);
+
+        // Craft a DF11 reply from the same address to make it known.
+        let mut df11 = [0x5D, 0x40, 0x09, 0x40, 0, 0, 0];
+        let parity = mode_s::crc::crc24(&df11[..4]);
+        df11[4..].copy_from_slice(&parity.to_be_bytes()[1..]);
+        assert!(decoder.decode(&Frame::new(&df11).unwrap(), now).is_some());
+
+        let update = decoder.decode(&df20, now).unwrap();
+        assert_eq!(update.icao, Icao(0x400940));
+        assert!(update.altitude.is_some());
+    }
+}
@@ -0,0 +1,436 @@
+//! Filter expressions which pick aircraft by predicate, e.g. `alt:10000-25000 !src:mlat mil`.
+//!
+//! An expression is a list of terms separated by spaces, all of which must match. A term is
+//! negated by prefixing it with `!`. The terms are:
+//!
+//! - `alt:LOW-HIGH`, `alt:>N`, `alt:<N`, or `alt:low|medium|high` for the coverage bands, in feet
+//! - `speed:LOW-HIGH`, `speed:>N` or `speed:<N`, ground speed in knots
+//! - `dist:LOW-HIGH`, `dist:>N` or `dist:<N`, in nautical miles from the receiver
+//! - `call:PREFIX` and `icao:PREFIX`
+//! - `squawk:PREFIX`, e.g. `squawk:7700` or `squawk:75`
+//! - `cat:A3`, the emitter category code
+//! - `src:adsb,mlat`, the source of the position, any of `adsb`, `adsr`, `tisb`, `mlat`,
+//!   `modes` or `unknown`
+//! - `mil`, addresses allocated to military aircraft
+//!
+//! A term on a value the aircraft did not send does not match.
+
+use crate::Source;
+use crate::coverage::AltitudeBand;
+use crate::tracker::Aircraft;
+use mode_s::adsb::AircraftCategory;
+use mode_s::{Position, country, geo};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum FilterError {
+    #[error("Unknown filter term `{0}`.")]
+    UnknownTerm(String),
+    #[error("Invalid value `{value}` for `{key}`.")]
+    InvalidValue { key: String, value: String },
+}
+
+/// What is done with the aircraft which match the active filter.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FilterMode {
+    /// Only the matching aircraft are shown.
+    #[default]
+    Hide,
+    /// Every aircraft is shown, the matching ones highlighted.
+    Highlight,
+}
+
+impl FilterMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            FilterMode::Hide => "hide",
+            FilterMode::Highlight => "highlight",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "hide" => Some(FilterMode::Hide),
+            "highlight" => Some(FilterMode::Highlight),
+            _ => None,
+        }
+    }
+}
+
+/// Inclusive range, open on the missing side.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Range {
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+impl Range {
+    fn parse(s: &str) -> Option<Self> {
+        let number = |s: &str| s.trim().parse::<f64>().ok();
+
+        if let Some(min) = s.strip_prefix('>') {
+            return Some(Range {
+                min: Some(number(min)?),
+                max: None,
+            });
+        }
+        if let Some(max) = s.strip_prefix('<') {
+            return Some(Range {
+                min: None,
+                max: Some(number(max)?),
+            });
+        }
+
+        let (min, max) = s.split_once('-')?;
+        let range = Range {
+            min: if min.is_empty() {
+                None
+            } else {
+                Some(number(min)?)
+            },
+            max: if max.is_empty() {
+                None
+            } else {
+                Some(number(max)?)
+            },
+        };
+        (range.min.is_some() || range.max.is_some()).then_some(range)
+    }
+
+    pub fn contains(&self, value: f64) -> bool {
+        self.min.is_none_or(|min| value >= min) && self.max.is_none_or(|max| value <= max)
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.min, self.max) {
+            (Some(min), None) => write!(f, ">{min}"),
+            (None, Some(max)) => write!(f, "<{max}"),
+            (min, max) => {
+                if let Some(min) = min {
+                    write!(f, "{min}")?;
+                }
+                f.write_str("-")?;
+                if let Some(max) = max {
+                    write!(f, "{max}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Term {
+    Altitude(Range),
+    AltitudeBand(AltitudeBand),
+    Speed(Range),
+    Distance(Range),
+    Callsign(String),
+    Icao(String),
+    Squawk(String),
+    Category(AircraftCategory),
+    Source(Vec<Source>),
+    Military,
+}
+
+fn source_code(source: Source) -> &'static str {
+    match source {
+        Source::Adsb => "adsb",
+        Source::Adsr => "adsr",
+        Source::Tisb => "tisb",
+        Source::Mlat => "mlat",
+        Source::ModeS => "modes",
+        Source::Unknown => "unknown",
+    }
+}
+
+fn parse_source(code: &str) -> Option<Source> {
+    [
+        Source::Adsb,
+        Source::Adsr,
+        Source::Tisb,
+        Source::Mlat,
+        Source::ModeS,
+        Source::Unknown,
+    ]
+    .into_iter()
+    .find(|source| source_code(*source) == code)
+}
+
+fn band_code(band: AltitudeBand) -> &'static str {
+    match band {
+        AltitudeBand::Low => "low",
+        AltitudeBand::Medium => "medium",
+        AltitudeBand::High => "high",
+    }
+}
+
+fn category_code(category: AircraftCategory) -> Option<String> {
+    // Categories are few, so the code is found by trying them all.
+    (b'A'..=b'D')
+        .flat_map(|set| (0..8).map(move |n| format!("{}{n}", set as char)))
+        .find(|code| AircraftCategory::parse(code) == Some(category))
+}
+
+impl Term {
+    fn parse(term: &str) -> Result<Self, FilterError> {
+        if term.eq_ignore_ascii_case("mil") {
+            return Ok(Term::Military);
+        }
+
+        let (key, value) = term
+            .split_once(':')
+            .ok_or_else(|| FilterError::UnknownTerm(term.to_string()))?;
+        let key = key.to_lowercase();
+        let invalid = || FilterError::InvalidValue {
+            key: key.clone(),
+            value: value.to_string(),
+        };
+        let range = || Range::parse(value).ok_or_else(invalid);
+
+        match key.as_str() {
+            "alt" => {
+                let band = AltitudeBand::ALL
+                    .into_iter()
+                    .find(|band| value.eq_ignore_ascii_case(band_code(*band)));
+                match band {
+                    Some(band) => Ok(Term::AltitudeBand(band)),
+                    None => Ok(Term::Altitude(range()?)),
+                }
+            }
+            "speed" => Ok(Term::Speed(range()?)),
+            "dist" => Ok(Term::Distance(range()?)),
+            "call" if !value.is_empty() => Ok(Term::Callsign(value.to_uppercase())),
+            "icao" if value.chars().all(|c| c.is_ascii_hexdigit()) && !value.is_empty() => {
+                Ok(Term::Icao(value.to_uppercase()))
+            }
+            "squawk" if value.chars().all(|c| ('0'..='7').contains(&c)) && !value.is_empty() => {
+                Ok(Term::Squawk(value.to_string()))
+            }
+            "cat" => AircraftCategory::parse(&value.to_uppercase())
+                .map(Term::Category)
+                .ok_or_else(invalid),
+            "src" => value
+                .split(',')
+                .map(|code| parse_source(&code.trim().to_lowercase()))
+                .collect::<Option<Vec<_>>>()
+                .filter(|sources| !sources.is_empty())
+                .map(Term::Source)
+                .ok_or_else(invalid),
+            "call" | "icao" | "squawk" => Err(invalid()),
+            _ => Err(FilterError::UnknownTerm(term.to_string())),
+        }
+    }
+
+    fn matches(&self, aircraft: &Aircraft, receiver: Option<Position>) -> bool {
+        match self {
+            Term::Altitude(range) => aircraft
+                .altitude
+                .is_some_and(|altitude| range.contains(altitude.value as f64)),
+            Term::AltitudeBand(band) => aircraft
+                .altitude
+                .is_some_and(|altitude| AltitudeBand::from_altitude(altitude.value) == *band),
+            Term::Speed(range) => aircraft
+                .ground_speed
+                .is_some_and(|speed| range.contains(speed.value)),
+            Term::Distance(range) => {
+                receiver
+                    .zip(aircraft.position)
+                    .is_some_and(|(receiver, position)| {
+                        range.contains(
+                            geo::distance(receiver, position.value) / geo::METERS_PER_NAUTICAL_MILE,
+                        )
+                    })
+            }
+            Term::Callsign(prefix) => aircraft
+                .callsign
+                .as_ref()
+                .is_some_and(|callsign| callsign.value.to_uppercase().starts_with(prefix)),
+            Term::Icao(prefix) => aircraft.icao.to_string().starts_with(prefix),
+            Term::Squawk(prefix) => aircraft
+                .squawk
+                .is_some_and(|squawk| squawk.value.to_string().starts_with(prefix)),
+            Term::Category(category) => aircraft
+                .category
+                .is_some_and(|aircraft_category| aircraft_category.value == *category),
+            Term::Source(sources) => aircraft
+                .position
+                .is_some_and(|position| sources.contains(&position.source)),
+            Term::Military => country::is_military(aircraft.icao),
+        }
+    }
+}
+
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Term::Altitude(range) => write!(f, "alt:{range}"),
+            Term::AltitudeBand(band) => write!(f, "alt:{}", band_code(*band)),
+            Term::Speed(range) => write!(f, "speed:{range}"),
+            Term::Distance(range) => write!(f, "dist:{range}"),
+            Term::Callsign(prefix) => write!(f, "call:{prefix}"),
+            Term::Icao(prefix) => write!(f, "icao:{prefix}"),
+            Term::Squawk(prefix) => write!(f, "squawk:{prefix}"),
+            Term::Category(category) => match category_code(*category) {
+                Some(code) => write!(f, "cat:{code}"),
+                None => write!(f, "cat:A0"),
+            },
+            Term::Source(sources) => {
+                let codes: Vec<_> = sources.iter().map(|source| source_code(*source)).collect();
+                write!(f, "src:{}", codes.join(","))
+            }
+            Term::Military => f.write_str("mil"),
+        }
+    }
+}
+
+/// Parsed filter expression. See the [module documentation](self) for the syntax.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Filter {
+    /// Terms, each with whether it is negated.
+    terms: Vec<(bool, Term)>,
+    /// Distances are measured from here, `dist` terms never match without it.
+    receiver: Option<Position>,
+}
+
+impl Filter {
+    pub fn parse(expression: &str) -> Result<Self, FilterError> {
+        let terms = expression
+            .split_whitespace()
+            .map(|term| match term.strip_prefix('!') {
+                Some(term) => Ok((true, Term::parse(term)?)),
+                None => Ok((false, Term::parse(term)?)),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            terms,
+            receiver: None,
+        })
+    }
+
+    pub fn set_receiver(&mut self, receiver: Option<Position>) {
+        self.receiver = receiver;
+    }
+
+    pub fn with_receiver(mut self, receiver: Option<Position>) -> Self {
+        self.set_receiver(receiver);
+        self
+    }
+
+    /// Whether the filter has no terms, and so matches every aircraft.
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn matches(&self, aircraft: &Aircraft) -> bool {
+        self.terms
+            .iter()
+            .all(|(negated, term)| term.matches(aircraft, self.receiver) != *negated)
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let terms: Vec<_> = self
+            .terms
+            .iter()
+            .map(|(negated, term)| {
+                if *negated {
+                    format!("!{term}")
+                } else {
+                    term.to_string()
+                }
+            })
+            .collect();
+        f.write_str(&terms.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Update;
+    use mode_s::geo::lat_lon;
+    use mode_s::{Icao, Squawk};
+    use std::time::Instant;
+
+    fn aircraft(icao: u32, update: impl FnOnce(&mut Update)) -> Aircraft {
+        let mut tracker = crate::Tracker::default();
+        let mut u = Update::new(Icao(icao), Instant::now());
+        update(&mut u);
+        tracker.update(u);
+        tracker.get(Icao(icao)).unwrap().clone()
+    }
+
+    #[test]
+    fn parsing_expressions() {
+        let filter = Filter::parse("alt:10000-25000 !src:mlat,tisb  call:ba mil cat:a3").unwrap();
+        assert_eq!(
+            filter.to_string(),
+            "alt:10000-25000 !src:mlat,tisb call:BA mil cat:A3"
+        );
+        assert_eq!(Filter::parse(&filter.to_string()).unwrap(), filter);
+
+        assert!(Filter::parse("").unwrap().is_empty());
+        assert_eq!(
+            Filter::parse("alt:>1000 dist:<50 speed:100-")
+                .unwrap()
+                .to_string(),
+            "alt:>1000 dist:<50 speed:>100"
+        );
+        assert_eq!(
+            Filter::parse("height:100"),
+            Err(FilterError::UnknownTerm("height:100".into()))
+        );
+        assert_eq!(
+            Filter::parse("squawk:7800"),
+            Err(FilterError::InvalidValue {
+                key: "squawk".into(),
+                value: "7800".into()
+            })
+        );
+        assert!(Filter::parse("src:radar").is_err());
+        assert!(Filter::parse("alt:-").is_err());
+    }
+
+    #[test]
+    fn matching_aircraft() {
+        let airliner = aircraft(0x4CA123, |update| {
+            update.callsign = Some("RYR12AB".into());
+            update.altitude = Some(36000);
+            update.ground_speed = Some(450.0);
+            update.squawk = Some(Squawk(0x7700));
+            update.position = Some(lat_lon(53.8, -6.0));
+            update.source = Source::Adsb;
+        });
+        let fighter = aircraft(0xAE1234, |update| {
+            update.altitude = Some(8000);
+        });
+
+        let matches = |expression: &str, aircraft: &Aircraft| {
+            Filter::parse(expression)
+                .unwrap()
+                .with_receiver(Some(lat_lon(53.4, -6.3)))
+                .matches(aircraft)
+        };
+
+        assert!(matches(
+            "alt:high speed:400-500 call:RYR squawk:77",
+            &airliner
+        ));
+        assert!(matches("icao:4CA src:adsb,mlat dist:<30", &airliner));
+        assert!(!matches("dist:>30", &airliner));
+        assert!(!matches("mil", &airliner));
+        assert!(matches("mil alt:low", &fighter));
+        // Terms on missing values don't match, unless negated.
+        assert!(!matches("speed:>0", &fighter));
+        assert!(matches("!speed:>0 !call:RYR", &fighter));
+        assert!(matches("", &fighter));
+
+        let filter = Filter::parse("dist:<1000").unwrap();
+        assert!(!filter.matches(&airliner));
+    }
+}
@@ -0,0 +1,18 @@
+//! Connections to ADS-B receivers, turning their feeds into aircraft [`Update`]s.
+
+pub mod aircraft_json;
+pub mod alerts;
+pub mod avr;
+pub mod beast;
+pub mod coverage;
+mod decoder;
+pub mod filter;
+pub mod mode_ac;
+pub mod sbs;
+pub mod tracker;
+pub mod uat;
+mod update;
+
+pub use decoder::Decoder;
+pub use tracker::Tracker;
+pub use update::{Link, Source, Update};
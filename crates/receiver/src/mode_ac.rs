@@ -0,0 +1,250 @@
+//! Association of Mode A/C replies with Mode S aircraft.
+//!
+//! Mode A/C replies carry no address, only a code which is either the squawk or the altitude.
+//! Every code heard often enough is kept as a target, and checked periodically against the
+//! squawks and altitudes of the tracked Mode S aircraft. A target is associated with an
+//! aircraft once that aircraft, and only that one, has matched it most of the time.
+//! Targets which remain unassociated are aircraft with only a Mode A/C transponder.
+
+use crate::Tracker;
+use mode_s::mode_ac::ModeAcReply;
+use mode_s::{Icao, Squawk};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// Targets which were not heard for this long are dropped.
+const TARGET_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Replies needed before a code is taken as a target rather than noise or a garbled reply.
+const MIN_REPLIES: u32 = 5;
+
+/// Association passes needed before an aircraft can be associated.
+const MIN_PASSES: u32 = 3;
+
+/// Share of the passes in which an aircraft must have matched the target.
+const MIN_MATCH_RATIO: f64 = 0.8;
+
+/// Mode C altitudes have a resolution of 100 feet.
+const ALTITUDE_TOLERANCE: i32 = 100;
+
+/// A recurring Mode A/C code.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModeAcTarget {
+    pub reply: ModeAcReply,
+    pub first_seen: Instant,
+    pub last_seen: Instant,
+    /// Number of replies with this code.
+    pub replies: u32,
+    /// The Mode S aircraft the code belongs to, if known.
+    pub associated: Option<Icao>,
+    /// Number of association passes, and how many of them each aircraft matched.
+    passes: u32,
+    matches: HashMap<Icao, u32>,
+}
+
+impl ModeAcTarget {
+    pub fn squawk(&self) -> Squawk {
+        self.reply.squawk()
+    }
+
+    pub fn altitude(&self) -> Option<i32> {
+        self.reply.altitude()
+    }
+
+    /// Whether the code was heard often enough to be taken as a target.
+    pub fn is_recurring(&self) -> bool {
+        self.replies >= MIN_REPLIES
+    }
+}
+
+/// Collects Mode A/C replies and associates them with Mode S aircraft.
+#[derive(Debug, Default)]
+pub struct ModeAcAssociator {
+    targets: HashMap<u16, ModeAcTarget>,
+}
+
+impl ModeAcAssociator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a reply received at `time`.
+    pub fn record(&mut self, reply: ModeAcReply, time: Instant) {
+        let target = self
+            .targets
+            .entry(reply.code)
+            .or_insert_with(|| ModeAcTarget {
+                reply,
+                first_seen: time,
+                last_seen: time,
+                replies: 0,
+                associated: None,
+                passes: 0,
+                matches: HashMap::new(),
+            });
+
+        target.reply = reply;
+        target.last_seen = target.last_seen.max(time);
+        target.replies += 1;
+    }
+
+    /// Compare every recurring target with the aircraft in `tracker`, and update the
+    /// associations.
+    pub fn associate(&mut self, tracker: &Tracker) {
+        for target in self.targets.values_mut() {
+            if !target.is_recurring() {
+                continue;
+            }
+
+            let squawk = target.squawk();
+            let altitude = target.altitude();
+            target.passes += 1;
+
+            for aircraft in tracker.iter() {
+                let same_squawk = aircraft.squawk.is_some_and(|code| code.value == squawk);
+                let same_altitude = altitude.is_some_and(|altitude| {
+                    aircraft.altitude.is_some_and(|reported| {
+                        (reported.value - altitude).abs() <= ALTITUDE_TOLERANCE
+                    })
+                });
+
+                if same_squawk || same_altitude {
+                    *target.matches.entry(aircraft.icao).or_default() += 1;
+                }
+            }
+
+            let mut candidates = target
+                .matches
+                .iter()
+                .filter(|(_, matches)| **matches as f64 / target.passes as f64 >= MIN_MATCH_RATIO);
+            target.associated = match (candidates.next(), candidates.next()) {
+                (Some((icao, _)), None) if target.passes >= MIN_PASSES => Some(*icao),
+                _ => None,
+            };
+        }
+    }
+
+    /// Drop targets which were not heard for a while.
+    pub fn expire(&mut self, now: Instant) {
+        self.targets
+            .retain(|_, target| now.saturating_duration_since(target.last_seen) <= TARGET_TIMEOUT);
+    }
+
+    pub fn targets(&self) -> impl Iterator<Item = &ModeAcTarget> {
+        self.targets.values()
+    }
+
+    /// Recurring targets which could not be associated with any Mode S aircraft.
+    pub fn unassociated(&self) -> impl Iterator<Item = &ModeAcTarget> {
+        self.targets()
+            .filter(|target| target.is_recurring() && target.associated.is_none())
+    }
+}
+
+/// How often the associations are updated.
+const ASSOCIATION_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Record the replies received on `replies`, and associate them with the aircraft in
+/// `tracker`, until the sending end of the channel is dropped.
+pub async fn associate(
+    associator: Arc<RwLock<ModeAcAssociator>>,
+    tracker: Arc<RwLock<Tracker>>,
+    mut replies: mpsc::Receiver<(ModeAcReply, Instant)>,
+) {
+    let mut interval = tokio::time::interval(ASSOCIATION_INTERVAL);
+
+    loop {
+        tokio::select! {
+            reply = replies.recv() => {
+                let Some((reply, time)) = reply else {
+                    return;
+                };
+                associator.write().unwrap().record(reply, time);
+            }
+            _ = interval.tick() => {
+                let tracker = tracker.read().unwrap();
+                let mut associator = associator.write().unwrap();
+                associator.associate(&tracker);
+                associator.expire(Instant::now());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Update;
+
+    fn reply(code: u16) -> ModeAcReply {
+        ModeAcReply { code, spi: false }
+    }
+
+    #[test]
+    fn associating_squawks_and_altitudes() {
+        let now = Instant::now();
+        let mut tracker = Tracker::default();
+        let mut update = Update::new(Icao(0x4CA2D6), now);
+        update.squawk = Some(Squawk(0x1000));
+        update.altitude = Some(725);
+        tracker.update(update);
+
+        let mut associator = ModeAcAssociator::new();
+        // 1000 is the squawk of the Mode S aircraft, 0240 its altitude (700 ft) and 2000
+        // belongs to an aircraft without Mode S. 4000 was only heard once.
+        for _ in 0..MIN_REPLIES {
+            for code in [0x1000, 0x0240, 0x2000] {
+                associator.record(reply(code), now);
+            }
+        }
+        associator.record(reply(0x4000), now);
+
+        associator.associate(&tracker);
+        assert!(
+            associator
+                .targets()
+                .all(|target| target.associated.is_none())
+        );
+
+        for _ in 1..MIN_PASSES {
+            associator.associate(&tracker);
+        }
+
+        let associated = |code| associator.targets.get(&code).unwrap().associated;
+        assert_eq!(associated(0x1000), Some(Icao(0x4CA2D6)));
+        assert_eq!(associated(0x0240), Some(Icao(0x4CA2D6)));
+        assert_eq!(associated(0x2000), None);
+
+        let unassociated: Vec<_> = associator
+            .unassociated()
+            .map(|target| target.squawk())
+            .collect();
+        assert_eq!(unassociated, [Squawk(0x2000)]);
+
+        associator.expire(now + TARGET_TIMEOUT + Duration::from_secs(1));
+        assert_eq!(associator.targets().count(), 0);
+    }
+
+    #[test]
+    fn ambiguous_codes_stay_unassociated() {
+        let now = Instant::now();
+        let mut tracker = Tracker::default();
+        for icao in [Icao(1), Icao(2)] {
+            let mut update = Update::new(icao, now);
+            update.squawk = Some(Squawk(0x1200));
+            tracker.update(update);
+        }
+
+        let mut associator = ModeAcAssociator::new();
+        for _ in 0..MIN_REPLIES {
+            associator.record(reply(0x1200), now);
+        }
+        for _ in 0..MIN_PASSES {
+            associator.associate(&tracker);
+        }
+
+        assert_eq!(associator.unassociated().count(), 1);
+    }
+}
@@ -0,0 +1,204 @@
+//! SBS-1 (BaseStation) CSV feed, served by dump1090 and many other tools on port 30003.
+//!
+//! Each line is a comma separated record like
+//! `MSG,3,1,1,4CA2D6,1,2019/01/01,12:00:00.000,2019/01/01,12:00:00.000,,37000,,,51.4,-0.1,,,0,0,0,0`,
+//! where the second field is the transmission type (1 to 8) telling which of the fields are set.
+//! MLAT results are sent in the same format, as `MLAT` records instead of `MSG`.
+
+use crate::{Source, Update};
+use mode_s::{Icao, Squawk, geo::lat_lon};
+use std::io;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+
+/// Indexes of the SBS fields.
+const HEX_IDENT: usize = 4;
+const CALLSIGN: usize = 10;
+const ALTITUDE: usize = 11;
+const GROUND_SPEED: usize = 12;
+const TRACK: usize = 13;
+const LATITUDE: usize = 14;
+const LONGITUDE: usize = 15;
+const VERTICAL_RATE: usize = 16;
+const SQUAWK: usize = 17;
+const ALERT: usize = 18;
+const EMERGENCY: usize = 19;
+const SPI: usize = 20;
+const ON_GROUND: usize = 21;
+
+/// Parse a single line of the feed, received at `time`. Returns `None` for lines which are not
+/// `MSG` or `MLAT` records or are malformed.
+pub fn parse_line(line: &str, time: Instant) -> Option<Update> {
+    let fields: Vec<&str> = line.trim().split(',').map(str::trim).collect();
+    let source = match fields.first() {
+        Some(&"MSG") => Source::Unknown,
+        Some(&"MLAT") => Source::Mlat,
+        _ => return None,
+    };
+    if fields.len() < 11 {
+        return None;
+    }
+
+    let Some(icao) = Icao::parse(fields[HEX_IDENT]) else {
+        log::debug!("Invalid SBS hex ident in '{line}'.");
+        return None;
+    };
+
+    let field = |index: usize| fields.get(index).copied().filter(|field| !field.is_empty());
+    let number = |index: usize| field(index).and_then(|field| field.parse::<f64>().ok());
+    let flag = |index: usize| field(index).map(|field| field != "0");
+
+    let mut update = Update::new(icao, time);
+    update.source = source;
+    update.callsign = field(CALLSIGN).map(str::to_string);
+    update.altitude = number(ALTITUDE).map(|altitude| altitude as i32);
+    update.ground_speed = number(GROUND_SPEED);
+    update.track = number(TRACK);
+    update.position = number(LATITUDE)
+        .zip(number(LONGITUDE))
+        .map(|(lat, lon)| lat_lon(lat, lon));
+    update.vertical_rate = number(VERTICAL_RATE).map(|rate| rate as i32);
+    update.squawk = field(SQUAWK).and_then(Squawk::parse);
+    update.alert = flag(ALERT);
+    update.emergency = flag(EMERGENCY);
+    update.spi = flag(SPI);
+    update.on_ground = flag(ON_GROUND);
+
+    Some(update)
+}
+
+/// Read lines from `reader` until it is closed, sending the parsed updates. Returns `Ok(false)`
+/// if the receiving end of the channel was dropped.
+pub async fn read_updates<R>(reader: R, updates: &mpsc::Sender<Update>) -> io::Result<bool>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Some(line) = lines.next_line().await? {
+        if let Some(update) = parse_line(&line, Instant::now()) {
+            if updates.send(update).await.is_err() {
+                return Ok(false);
+            }
+        }
+    }
+
+    Ok(true)
+}
+
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// TCP client of an SBS feed, which reconnects whenever the connection drops.
+pub struct SbsClient {
+    addr: String,
+}
+
+impl SbsClient {
+    /// Client of the feed at `addr`, e.g. `localhost:30003`.
+    pub fn new(addr: impl Into<String>) -> Self {
+        Self { addr: addr.into() }
+    }
+
+    /// Keep receiving updates until the receiving end of the channel is dropped.
+    pub async fn run(self, updates: mpsc::Sender<Update>) {
+        let mut backoff = MIN_BACKOFF;
+
+        loop {
+            match TcpStream::connect(&self.addr).await {
+                Ok(stream) => {
+                    log::info!("Connected to SBS feed at {}.", self.addr);
+                    backoff = MIN_BACKOFF;
+
+                    match read_updates(stream, &updates).await {
+                        Ok(true) => log::warn!("SBS feed at {} closed.", self.addr),
+                        Ok(false) => return,
+                        Err(err) => log::warn!("SBS feed at {} failed: {err}.", self.addr),
+                    }
+                }
+                Err(err) => {
+                    log::warn!("Can't connect to SBS feed at {}: {err}.", self.addr);
+                }
+            }
+
+            if updates.is_closed() {
+                return;
+            }
+
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn parsing_records() {
+        let now = Instant::now();
+
+        let update = parse_line("MSG,1,1,1,4CA2D6,1,2019/01/01,12:00:00.000,2019/01/01,12:00:00.000,RYR1234 ,,,,,,,,,,,0", now).unwrap();
+        assert_eq!(update.icao, Icao(0x4CA2D6));
+        assert_eq!(update.callsign.as_deref(), Some("RYR1234"));
+        assert_eq!(update.on_ground, Some(false));
+
+        let update = parse_line("MSG,3,1,1,4CA2D6,1,2019/01/01,12:00:00.000,2019/01/01,12:00:00.000,,37000,,,51.4,-0.1,,,0,0,0,0", now).unwrap();
+        assert_eq!(update.altitude, Some(37000));
+        assert_eq!(update.position, Some(lat_lon(51.4, -0.1)));
+        assert_eq!(update.callsign, None);
+
+        let update = parse_line("MSG,6,1,1,4CA2D6,1,2019/01/01,12:00:00.000,2019/01/01,12:00:00.000,,,,,,,,7700,-1,-1,0,0", now).unwrap();
+        assert_eq!(update.squawk, Some(Squawk(0x7700)));
+        assert_eq!(update.alert, Some(true));
+        assert_eq!(update.emergency, Some(true));
+        assert_eq!(update.spi, Some(false));
+        assert_eq!(update.source, Source::Unknown);
+
+        let update = parse_line("MLAT,3,1,1,4CA2D6,1,2019/01/01,12:00:00.000,2019/01/01,12:00:00.000,,37000,,,51.4,-0.1,,,0,0,0,0", now).unwrap();
+        assert_eq!(update.position, Some(lat_lon(51.4, -0.1)));
+        assert_eq!(update.source, Source::Mlat);
+    }
+
+    #[test]
+    fn skipping_other_records() {
+        let now = Instant::now();
+        assert_eq!(
+            parse_line(
+                "STA,,1,1,4CA2D6,1,2019/01/01,12:00:00.000,2019/01/01,12:00:00.000,RM",
+                now
+            ),
+            None
+        );
+        assert_eq!(parse_line("MSG,3,1,1,XYZ,1", now), None);
+        assert_eq!(parse_line("", now), None);
+    }
+
+    #[tokio::test]
+    async fn reconnecting_after_disconnect() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            for callsign in ["FIRST", "SECOND"] {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let line = format!("MSG,1,1,1,4CA2D6,1,,,,,{callsign},,,,,,,,,,,0\n");
+                socket.write_all(line.as_bytes()).await.unwrap();
+            }
+        });
+
+        let (tx, mut rx) = mpsc::channel(16);
+        let client = tokio::spawn(SbsClient::new(addr.to_string()).run(tx));
+
+        assert_eq!(rx.recv().await.unwrap().callsign.as_deref(), Some("FIRST"));
+        assert_eq!(rx.recv().await.unwrap().callsign.as_deref(), Some("SECOND"));
+
+        drop(rx);
+        client.abort();
+    }
+}
@@ -0,0 +1,708 @@
+//! Live table of aircraft, built by merging [`Update`]s from any number of sources.
+
+use crate::{Link, Source, Update};
+use mode_s::adsb::{AircraftCategory, EmergencyStatus, VerticalRateSource};
+use mode_s::commb::{NavigationModes, ResolutionAdvisory};
+use mode_s::integrity::{self, Integrity};
+use mode_s::{Icao, Position, Squawk, geo};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// A value together with the time it was last received and where it came from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Timestamped<T> {
+    pub value: T,
+    pub updated: Instant,
+    pub source: Source,
+}
+
+impl<T> Timestamped<T> {
+    /// Time since the value was received.
+    pub fn age(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.updated)
+    }
+}
+
+/// Replace `field` with `value`, unless the field holds something newer. Returns whether the
+/// field was replaced.
+fn merge<T>(
+    field: &mut Option<Timestamped<T>>,
+    value: Option<T>,
+    time: Instant,
+    source: Source,
+) -> bool {
+    let Some(value) = value else {
+        return false;
+    };
+
+    let newer = field.as_ref().is_none_or(|field| field.updated <= time);
+    if newer {
+        *field = Some(Timestamped {
+            value,
+            updated: time,
+            source,
+        });
+    }
+    newer
+}
+
+/// Drop `field` if it was not updated within `timeout`.
+fn expire<T>(field: &mut Option<Timestamped<T>>, now: Instant, timeout: Duration) {
+    if field.as_ref().is_some_and(|field| field.age(now) > timeout) {
+        *field = None;
+    }
+}
+
+/// Upper bound of the trail length, no matter how long it is configured to be.
+const MAX_TRAIL_POINTS: usize = 2000;
+
+/// A single point of a trail.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TrailPoint {
+    pub position: Position,
+    /// Barometric altitude at that point, in feet.
+    pub altitude: Option<i32>,
+    pub on_ground: bool,
+    pub time: Instant,
+}
+
+/// Everything known about a single aircraft. Each field remembers when it was last updated.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Aircraft {
+    pub icao: Icao,
+    pub first_seen: Instant,
+    pub last_seen: Instant,
+    /// Number of updates merged into this aircraft.
+    pub messages: u64,
+    /// Link of the last update.
+    pub link: Link,
+    pub callsign: Option<Timestamped<String>>,
+    pub category: Option<Timestamped<AircraftCategory>>,
+    /// Barometric altitude in feet.
+    pub altitude: Option<Timestamped<i32>>,
+    /// GNSS altitude in feet.
+    pub geometric_altitude: Option<Timestamped<i32>>,
+    pub position: Option<Timestamped<Position>>,
+    pub on_ground: Option<Timestamped<bool>>,
+    /// Ground speed in knots.
+    pub ground_speed: Option<Timestamped<f64>>,
+    /// Track over ground in degrees, clockwise from the true north.
+    pub track: Option<Timestamped<f64>>,
+    /// Indicated airspeed in knots.
+    pub indicated_airspeed: Option<Timestamped<f64>>,
+    /// True airspeed in knots.
+    pub true_airspeed: Option<Timestamped<f64>>,
+    /// Heading in degrees, clockwise from the magnetic north.
+    pub heading: Option<Timestamped<f64>>,
+    /// Vertical rate in feet per minute, positive when climbing.
+    pub vertical_rate: Option<Timestamped<i32>>,
+    pub vertical_rate_source: Option<Timestamped<VerticalRateSource>>,
+    /// GNSS altitude minus barometric altitude, in feet.
+    pub gnss_altitude_difference: Option<Timestamped<i32>>,
+    pub mach: Option<Timestamped<f64>>,
+    /// Roll angle in degrees, positive when the right wing is down.
+    pub roll: Option<Timestamped<f64>>,
+    /// Rate of the track change in degrees per second, positive when turning right.
+    pub track_rate: Option<Timestamped<f64>>,
+    /// Altitude selected on the MCP or FCU, in feet.
+    pub selected_altitude: Option<Timestamped<i32>>,
+    /// Altitude selected in the FMS, in feet.
+    pub fms_selected_altitude: Option<Timestamped<i32>>,
+    /// Heading selected on the MCP or FCU, in degrees.
+    pub selected_heading: Option<Timestamped<f64>>,
+    /// Barometric pressure setting in hPa.
+    pub qnh: Option<Timestamped<f64>>,
+    pub navigation_modes: Option<Timestamped<NavigationModes>>,
+    /// ADS-B version of the transmitter.
+    pub adsb_version: Option<Timestamped<u8>>,
+    /// Type code of the last position message.
+    pub position_type_code: Option<Timestamped<u8>>,
+    /// Navigation integrity category of the last UAT position.
+    pub nic: Option<Timestamped<u8>>,
+    pub nic_supplement_a: Option<Timestamped<bool>>,
+    pub nic_supplement_b: Option<Timestamped<bool>>,
+    pub nic_supplement_c: Option<Timestamped<bool>>,
+    /// Navigation accuracy category for position.
+    pub nac_p: Option<Timestamped<u8>>,
+    /// Source integrity level.
+    pub sil: Option<Timestamped<u8>>,
+    /// Geometric vertical accuracy.
+    pub gva: Option<Timestamped<u8>>,
+    pub nic_baro: Option<Timestamped<bool>>,
+    pub resolution_advisory: Option<Timestamped<ResolutionAdvisory>>,
+    pub squawk: Option<Timestamped<Squawk>>,
+    pub alert: Option<Timestamped<bool>>,
+    pub emergency: Option<Timestamped<bool>>,
+    pub emergency_status: Option<Timestamped<EmergencyStatus>>,
+    pub spi: Option<Timestamped<bool>>,
+    /// Signal level in dBFS.
+    pub rssi: Option<Timestamped<f64>>,
+    /// Recent positions, oldest first.
+    pub trail: VecDeque<TrailPoint>,
+}
+
+impl Aircraft {
+    fn new(icao: Icao, time: Instant) -> Self {
+        Self {
+            icao,
+            first_seen: time,
+            last_seen: time,
+            messages: 0,
+            link: Link::default(),
+            callsign: None,
+            category: None,
+            altitude: None,
+            geometric_altitude: None,
+            position: None,
+            on_ground: None,
+            ground_speed: None,
+            track: None,
+            indicated_airspeed: None,
+            true_airspeed: None,
+            heading: None,
+            vertical_rate: None,
+            vertical_rate_source: None,
+            gnss_altitude_difference: None,
+            mach: None,
+            roll: None,
+            track_rate: None,
+            selected_altitude: None,
+            fms_selected_altitude: None,
+            selected_heading: None,
+            qnh: None,
+            navigation_modes: None,
+            adsb_version: None,
+            position_type_code: None,
+            nic: None,
+            nic_supplement_a: None,
+            nic_supplement_b: None,
+            nic_supplement_c: None,
+            nac_p: None,
+            sil: None,
+            gva: None,
+            nic_baro: None,
+            resolution_advisory: None,
+            squawk: None,
+            alert: None,
+            emergency: None,
+            emergency_status: None,
+            spi: None,
+            rssi: None,
+            trail: VecDeque::new(),
+        }
+    }
+
+    fn merge(&mut self, update: Update, trail_length: Duration) {
+        let (time, source) = (update.time, update.source);
+        if time >= self.last_seen {
+            self.last_seen = time;
+            self.link = update.link;
+        }
+        self.messages += 1;
+
+        merge(&mut self.callsign, update.callsign, time, source);
+        merge(&mut self.category, update.category, time, source);
+        merge(&mut self.altitude, update.altitude, time, source);
+        merge(
+            &mut self.geometric_altitude,
+            update.geometric_altitude,
+            time,
+            source,
+        );
+        let moved = merge(&mut self.position, update.position, time, source);
+        merge(&mut self.on_ground, update.on_ground, time, source);
+        merge(&mut self.ground_speed, update.ground_speed, time, source);
+        merge(&mut self.track, update.track, time, source);
+        merge(
+            &mut self.indicated_airspeed,
+            update.indicated_airspeed,
+            time,
+            source,
+        );
+        merge(&mut self.true_airspeed, update.true_airspeed, time, source);
+        merge(&mut self.heading, update.heading, time, source);
+        merge(&mut self.vertical_rate, update.vertical_rate, time, source);
+        merge(
+            &mut self.vertical_rate_source,
+            update.vertical_rate_source,
+            time,
+            source,
+        );
+        merge(
+            &mut self.gnss_altitude_difference,
+            update.gnss_altitude_difference,
+            time,
+            source,
+        );
+        merge(&mut self.mach, update.mach, time, source);
+        merge(&mut self.roll, update.roll, time, source);
+        merge(&mut self.track_rate, update.track_rate, time, source);
+        merge(
+            &mut self.selected_altitude,
+            update.selected_altitude,
+            time,
+            source,
+        );
+        merge(
+            &mut self.fms_selected_altitude,
+            update.fms_selected_altitude,
+            time,
+            source,
+        );
+        merge(
+            &mut self.selected_heading,
+            update.selected_heading,
+            time,
+            source,
+        );
+        merge(&mut self.qnh, update.qnh, time, source);
+        merge(
+            &mut self.navigation_modes,
+            update.navigation_modes,
+            time,
+            source,
+        );
+        merge(&mut self.adsb_version, update.adsb_version, time, source);
+        merge(
+            &mut self.position_type_code,
+            update.position_type_code,
+            time,
+            source,
+        );
+        merge(
+            &mut self.nic_supplement_a,
+            update.nic_supplement_a,
+            time,
+            source,
+        );
+        merge(
+            &mut self.nic_supplement_b,
+            update.nic_supplement_b,
+            time,
+            source,
+        );
+        merge(
+            &mut self.nic_supplement_c,
+            update.nic_supplement_c,
+            time,
+            source,
+        );
+        merge(&mut self.nac_p, update.nac_p, time, source);
+        merge(&mut self.sil, update.sil, time, source);
+        merge(&mut self.gva, update.gva, time, source);
+        merge(&mut self.nic_baro, update.nic_baro, time, source);
+        merge(
+            &mut self.resolution_advisory,
+            update.resolution_advisory,
+            time,
+            source,
+        );
+        merge(&mut self.squawk, update.squawk, time, source);
+        merge(&mut self.alert, update.alert, time, source);
+        merge(&mut self.emergency, update.emergency, time, source);
+        merge(
+            &mut self.emergency_status,
+            update.emergency_status,
+            time,
+            source,
+        );
+        merge(&mut self.spi, update.spi, time, source);
+        merge(&mut self.rssi, update.rssi, time, source);
+
+        if moved {
+            self.extend_trail(time, trail_length);
+        }
+    }
+
+    fn extend_trail(&mut self, time: Instant, trail_length: Duration) {
+        let Some(position) = self.position else {
+            return;
+        };
+
+        if self
+            .trail
+            .back()
+            .is_some_and(|last| last.position == position.value)
+        {
+            return;
+        }
+
+        self.trail.push_back(TrailPoint {
+            position: position.value,
+            altitude: self.altitude.map(|altitude| altitude.value),
+            on_ground: self.on_ground.is_some_and(|on_ground| on_ground.value),
+            time,
+        });
+        self.trim_trail(time, trail_length);
+    }
+
+    fn trim_trail(&mut self, now: Instant, trail_length: Duration) {
+        while self.trail.len() > MAX_TRAIL_POINTS
+            || self
+                .trail
+                .front()
+                .is_some_and(|point| now.saturating_duration_since(point.time) > trail_length)
+        {
+            self.trail.pop_front();
+        }
+    }
+
+    fn expire(&mut self, now: Instant, timeouts: &Timeouts, trail_length: Duration) {
+        expire(&mut self.position, now, timeouts.position);
+        expire(&mut self.position_type_code, now, timeouts.position);
+        expire(&mut self.nic, now, timeouts.position);
+        self.trim_trail(now, trail_length);
+
+        expire(&mut self.altitude, now, timeouts.field);
+        expire(&mut self.geometric_altitude, now, timeouts.field);
+        expire(&mut self.on_ground, now, timeouts.field);
+        expire(&mut self.ground_speed, now, timeouts.field);
+        expire(&mut self.track, now, timeouts.field);
+        expire(&mut self.indicated_airspeed, now, timeouts.field);
+        expire(&mut self.true_airspeed, now, timeouts.field);
+        expire(&mut self.heading, now, timeouts.field);
+        expire(&mut self.vertical_rate, now, timeouts.field);
+        expire(&mut self.vertical_rate_source, now, timeouts.field);
+        expire(&mut self.gnss_altitude_difference, now, timeouts.field);
+        expire(&mut self.mach, now, timeouts.field);
+        expire(&mut self.roll, now, timeouts.field);
+        expire(&mut self.track_rate, now, timeouts.field);
+        expire(&mut self.selected_altitude, now, timeouts.field);
+        expire(&mut self.fms_selected_altitude, now, timeouts.field);
+        expire(&mut self.selected_heading, now, timeouts.field);
+        expire(&mut self.qnh, now, timeouts.field);
+        expire(&mut self.navigation_modes, now, timeouts.field);
+        expire(&mut self.nic_supplement_a, now, timeouts.field);
+        expire(&mut self.nic_supplement_b, now, timeouts.field);
+        expire(&mut self.nic_supplement_c, now, timeouts.field);
+        expire(&mut self.nac_p, now, timeouts.field);
+        expire(&mut self.sil, now, timeouts.field);
+        expire(&mut self.gva, now, timeouts.field);
+        expire(&mut self.nic_baro, now, timeouts.field);
+        expire(&mut self.resolution_advisory, now, timeouts.field);
+        expire(&mut self.alert, now, timeouts.field);
+        expire(&mut self.emergency, now, timeouts.field);
+        expire(&mut self.emergency_status, now, timeouts.field);
+        expire(&mut self.spi, now, timeouts.field);
+        expire(&mut self.rssi, now, timeouts.field);
+    }
+
+    /// Where the aircraft is expected to be at `now`, dead reckoning from the last position
+    /// with the ground speed and track. The extrapolation stops after `limit`, so that the
+    /// aircraft does not fly away when it is no longer heard. Falls back to the last position
+    /// when the velocity is unknown.
+    pub fn predicted_position(&self, now: Instant, limit: Duration) -> Option<Position> {
+        let position = self.position?;
+        let (Some(ground_speed), Some(track)) = (self.ground_speed, self.track) else {
+            return Some(position.value);
+        };
+
+        let elapsed = position.age(now).min(limit).as_secs_f64();
+        let distance = ground_speed.value * geo::METERS_PER_NAUTICAL_MILE / 3600.0 * elapsed;
+        Some(geo::destination(position.value, track.value, distance))
+    }
+
+    /// Integrity of the last position, combining the type code of the position message with
+    /// the NIC supplements. Transmitters which did not report their version are assumed to
+    /// be version 0. UAT positions carry their NIC directly.
+    pub fn integrity(&self) -> Option<Integrity> {
+        let Some(type_code) = self.position_type_code else {
+            return self.nic.map(|nic| integrity::uat_integrity(nic.value));
+        };
+        let type_code = type_code.value;
+        let version = self.adsb_version.map_or(0, |version| version.value);
+        let supplement_bc = if (5..=8).contains(&type_code) {
+            self.nic_supplement_c
+        } else {
+            self.nic_supplement_b
+        };
+
+        integrity::integrity(
+            type_code,
+            version,
+            self.nic_supplement_a.is_some_and(|a| a.value),
+            supplement_bc.is_some_and(|bc| bc.value),
+        )
+    }
+
+    /// Time since anything was received from this aircraft.
+    pub fn age(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.last_seen)
+    }
+}
+
+/// How long the tracker keeps data which is not refreshed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Timeouts {
+    /// Aircraft which were not heard for this long are removed.
+    pub aircraft: Duration,
+    /// Positions older than this are dropped, so that the aircraft is no longer drawn at a
+    /// place it left long ago.
+    pub position: Duration,
+    /// Other fields which change in flight, like altitude or speed. Callsign, category and
+    /// squawk are kept for as long as the aircraft, and so is the ADS-B version.
+    pub field: Duration,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            aircraft: Duration::from_secs(300),
+            position: Duration::from_secs(60),
+            field: Duration::from_secs(60),
+        }
+    }
+}
+
+/// How long trails are by default.
+pub const DEFAULT_TRAIL_LENGTH: Duration = Duration::from_secs(5 * 60);
+
+/// Aircraft table keyed by address.
+#[derive(Debug)]
+pub struct Tracker {
+    aircraft: HashMap<Icao, Aircraft>,
+    timeouts: Timeouts,
+    trail_length: Duration,
+}
+
+impl Default for Tracker {
+    fn default() -> Self {
+        Self::new(Timeouts::default())
+    }
+}
+
+impl Tracker {
+    pub fn new(timeouts: Timeouts) -> Self {
+        Self {
+            aircraft: HashMap::new(),
+            timeouts,
+            trail_length: DEFAULT_TRAIL_LENGTH,
+        }
+    }
+
+    pub fn timeouts(&self) -> Timeouts {
+        self.timeouts
+    }
+
+    pub fn set_timeouts(&mut self, timeouts: Timeouts) {
+        self.timeouts = timeouts;
+    }
+
+    pub fn trail_length(&self) -> Duration {
+        self.trail_length
+    }
+
+    /// Set for how long positions are kept in the trails.
+    pub fn set_trail_length(&mut self, trail_length: Duration) {
+        self.trail_length = trail_length;
+    }
+
+    /// Merge an update, adding the aircraft if it is new.
+    pub fn update(&mut self, update: Update) {
+        self.aircraft
+            .entry(update.icao)
+            .or_insert_with(|| Aircraft::new(update.icao, update.time))
+            .merge(update, self.trail_length);
+    }
+
+    /// Remove aircraft and fields which timed out.
+    pub fn expire(&mut self, now: Instant) {
+        let timeouts = self.timeouts;
+        self.aircraft
+            .retain(|_, aircraft| aircraft.age(now) <= timeouts.aircraft);
+
+        for aircraft in self.aircraft.values_mut() {
+            aircraft.expire(now, &timeouts, self.trail_length);
+        }
+    }
+
+    pub fn get(&self, icao: Icao) -> Option<&Aircraft> {
+        self.aircraft.get(&icao)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Aircraft> {
+        self.aircraft.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.aircraft.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.aircraft.is_empty()
+    }
+}
+
+/// How often [`track`] expires old data.
+const EXPIRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Merge updates into a shared tracker until every sender is dropped. The lock is only held
+/// for a single update at a time, so that the UI can read the table while this runs.
+pub async fn track(tracker: Arc<RwLock<Tracker>>, mut updates: mpsc::Receiver<Update>) {
+    let mut expiry = tokio::time::interval(EXPIRY_INTERVAL);
+
+    loop {
+        tokio::select! {
+            update = updates.recv() => {
+                let Some(update) = update else {
+                    return;
+                };
+                tracker.write().unwrap().update(update);
+            }
+            _ = expiry.tick() => {
+                tracker.write().unwrap().expire(Instant::now());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mode_s::geo::lat_lon;
+
+    #[test]
+    fn merging_updates() {
+        let start = Instant::now();
+        let icao = Icao(0x4CA2D6);
+        let mut tracker = Tracker::default();
+
+        let mut update = Update::new(icao, start);
+        update.callsign = Some("RYR1234".to_string());
+        update.altitude = Some(37000);
+        tracker.update(update);
+
+        let mut update = Update::new(icao, start + Duration::from_secs(2));
+        update.position = Some(lat_lon(51.4, -0.1));
+        tracker.update(update);
+
+        // Late update from a slower source must not overwrite newer data.
+        let mut update = Update::new(icao, start + Duration::from_secs(1));
+        update.altitude = Some(36000);
+        update.position = Some(lat_lon(51.3, -0.1));
+        tracker.update(update);
+
+        let aircraft = tracker.get(icao).unwrap();
+        assert_eq!(tracker.len(), 1);
+        assert_eq!(aircraft.messages, 3);
+        assert_eq!(aircraft.last_seen, start + Duration::from_secs(2));
+        assert_eq!(aircraft.callsign.as_ref().unwrap().value, "RYR1234");
+        assert_eq!(
+            aircraft.altitude,
+            Some(Timestamped {
+                value: 36000,
+                updated: start + Duration::from_secs(1),
+                source: Source::Unknown,
+            })
+        );
+        assert_eq!(aircraft.position.unwrap().value, lat_lon(51.4, -0.1));
+    }
+
+    #[test]
+    fn expiring_fields_and_aircraft() {
+        let start = Instant::now();
+        let mut tracker = Tracker::new(Timeouts {
+            aircraft: Duration::from_secs(30),
+            position: Duration::from_secs(5),
+            field: Duration::from_secs(10),
+        });
+
+        let mut update = Update::new(Icao(1), start);
+        update.position = Some(lat_lon(51.4, -0.1));
+        update.altitude = Some(37000);
+        update.squawk = Some(Squawk(0x1000));
+        tracker.update(update);
+        tracker.update(Update::new(Icao(2), start + Duration::from_secs(20)));
+
+        tracker.expire(start + Duration::from_secs(6));
+        let aircraft = tracker.get(Icao(1)).unwrap();
+        assert_eq!(aircraft.position, None);
+        assert!(aircraft.altitude.is_some());
+
+        tracker.expire(start + Duration::from_secs(11));
+        let aircraft = tracker.get(Icao(1)).unwrap();
+        assert_eq!(aircraft.altitude, None);
+        assert!(aircraft.squawk.is_some());
+
+        tracker.expire(start + Duration::from_secs(31));
+        assert!(tracker.get(Icao(1)).is_none());
+        assert!(tracker.get(Icao(2)).is_some());
+    }
+
+    #[test]
+    fn predicting_positions() {
+        let start = Instant::now();
+        let mut tracker = Tracker::default();
+
+        let mut update = Update::new(Icao(1), start);
+        update.position = Some(lat_lon(50.0, 10.0));
+        update.ground_speed = Some(360.0);
+        update.track = Some(0.0);
+        tracker.update(update);
+        let aircraft = tracker.get(Icao(1)).unwrap();
+
+        // 360 knots is 6 nautical miles, i.e. a tenth of a degree of latitude, per minute.
+        let limit = Duration::from_secs(120);
+        let predicted = aircraft
+            .predicted_position(start + Duration::from_secs(60), limit)
+            .unwrap();
+        assert!((predicted.y() - 50.1).abs() < 1e-3);
+        assert!((predicted.x() - 10.0).abs() < 1e-9);
+
+        let predicted = aircraft
+            .predicted_position(start + Duration::from_secs(600), limit)
+            .unwrap();
+        assert!((predicted.y() - 50.2).abs() < 1e-3);
+    }
+
+    #[test]
+    fn keeping_trails() {
+        let start = Instant::now();
+        let icao = Icao(0x4CA2D6);
+        let mut tracker = Tracker::default();
+        tracker.set_trail_length(Duration::from_secs(60));
+
+        for (seconds, lat) in [(0, 51.0), (20, 51.1), (20, 51.1), (40, 51.2), (80, 51.3)] {
+            let mut update = Update::new(icao, start + Duration::from_secs(seconds));
+            update.altitude = Some(1000 * seconds as i32);
+            update.position = Some(lat_lon(lat, -0.1));
+            tracker.update(update);
+        }
+
+        let trail = &tracker.get(icao).unwrap().trail;
+        assert_eq!(trail.len(), 3);
+        assert_eq!(trail[0].position, lat_lon(51.1, -0.1));
+        assert_eq!(trail[0].altitude, Some(20000));
+        assert_eq!(trail[2].position, lat_lon(51.3, -0.1));
+
+        tracker.expire(start + Duration::from_secs(110));
+        assert_eq!(tracker.get(icao).unwrap().trail.len(), 1);
+    }
+
+    #[test]
+    fn combining_integrity() {
+        let start = Instant::now();
+        let icao = Icao(0x4CA2D6);
+        let mut tracker = Tracker::default();
+
+        let mut update = Update::new(icao, start);
+        update.position_type_code = Some(11);
+        update.nic_supplement_b = Some(true);
+        tracker.update(update);
+
+        // Without the operational status, the aircraft is assumed to be version 0.
+        let integrity = tracker.get(icao).unwrap().integrity().unwrap();
+        assert_eq!(integrity.nic, 8);
+
+        let mut update = Update::new(icao, start + Duration::from_secs(1));
+        update.adsb_version = Some(2);
+        update.nic_supplement_a = Some(true);
+        tracker.update(update);
+
+        let integrity = tracker.get(icao).unwrap().integrity().unwrap();
+        assert_eq!(integrity.nic, 9);
+        assert_eq!(integrity.containment_radius, Some(75.0));
+    }
+}
@@ -0,0 +1,232 @@
+//! Raw UAT feed of dump978, served on port 30978 by dump978-fa.
+//!
+//! Each line holds a single message in hexadecimal, followed by optional `key=value;` metadata,
+//! like `-0A8A...;rs=1;rssi=-20.5;`. Downlink messages from aircraft start with `-`, uplink
+//! messages from ground stations with `+`. Only downlink messages are decoded.
+
+use crate::{Link, Source, Update};
+use mode_s::Icao;
+use mode_s::adsb::AltitudeSource;
+use mode_s::commb::NavigationModes;
+use mode_s::uat::{AddressQualifier, AirGroundState, EmergencyStatus, UatMessage};
+use std::io;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+
+/// A single downlink message read from the feed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UatLine {
+    pub message: UatMessage,
+    /// Signal level in dBFS, if the line had one.
+    pub rssi: Option<f64>,
+}
+
+/// Parse a single line. Returns `None` for uplink messages and for empty or malformed lines.
+pub fn parse_line(line: &str) -> Option<UatLine> {
+    let line = line.trim();
+    let mut parts = line.strip_prefix('-')?.split(';');
+
+    let message = match UatMessage::from_hex(parts.next()?) {
+        Ok(message) => message,
+        Err(err) => {
+            log::debug!("Skipping UAT line '{line}': {err}");
+            return None;
+        }
+    };
+
+    let rssi = parts
+        .filter_map(|part| part.split_once('='))
+        .find(|(key, _)| *key == "rssi")
+        .and_then(|(_, rssi)| rssi.parse().ok());
+
+    Some(UatLine { message, rssi })
+}
+
+/// Turn a message received at `time` into an update.
+pub fn to_update(message: UatMessage, time: Instant) -> Update {
+    let address = message.header.address;
+    let icao = if message.header.qualifier.is_icao() {
+        address
+    } else {
+        Icao::non_icao(address.0)
+    };
+    let mut update = Update::new(icao, time);
+    update.link = Link::Uat;
+    update.source = match message.header.qualifier {
+        AddressQualifier::TisbIcao | AddressQualifier::TisbTrackFile => Source::Tisb,
+        AddressQualifier::AdsrIcao => Source::Adsr,
+        _ => Source::Adsb,
+    };
+
+    let mut altitude_source = None;
+    if let Some(state) = message.state_vector {
+        match state.altitude_source {
+            AltitudeSource::Barometric => update.altitude = state.altitude,
+            AltitudeSource::Gnss => update.geometric_altitude = state.altitude,
+        }
+        altitude_source = Some(state.altitude_source);
+
+        update.position = state.position;
+        update.nic = state.position.map(|_| state.nic);
+        update.on_ground = match state.air_ground_state {
+            AirGroundState::Subsonic | AirGroundState::Supersonic => Some(false),
+            AirGroundState::OnGround => Some(true),
+            AirGroundState::Reserved => None,
+        };
+        update.ground_speed = state.ground_speed;
+        update.track = state.track;
+        update.vertical_rate = state.vertical_rate;
+        update.vertical_rate_source = state.vertical_rate_source;
+    }
+
+    if let Some(status) = message.mode_status {
+        update.category = Some(status.category);
+        update.callsign = status.callsign;
+        update.squawk = status.squawk;
+        update.emergency = Some(status.emergency != EmergencyStatus::None);
+        update.emergency_status = Some(status.emergency);
+        update.spi = Some(status.ident);
+        update.nac_p = Some(status.nac_p);
+        update.sil = Some(status.sil);
+        update.nic_baro = Some(status.nic_baro);
+        update.navigation_modes = Some(NavigationModes {
+            tcas: Some(status.tcas),
+            ..Default::default()
+        });
+    }
+
+    // The auxiliary altitude is of the other kind than the primary one.
+    if let Some(auxiliary) = message.auxiliary_state_vector {
+        match altitude_source {
+            Some(AltitudeSource::Barometric) => {
+                update.geometric_altitude = auxiliary.secondary_altitude
+            }
+            Some(AltitudeSource::Gnss) => update.altitude = auxiliary.secondary_altitude,
+            None => {}
+        }
+    }
+
+    update
+}
+
+/// Read lines from `reader` until it is closed, sending the decoded updates. Returns `Ok(false)`
+/// if the receiving end of the channel was dropped.
+pub async fn read_updates<R>(reader: R, updates: &mpsc::Sender<Update>) -> io::Result<bool>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Some(line) = lines.next_line().await? {
+        if let Some(line) = parse_line(&line) {
+            let mut update = to_update(line.message, Instant::now());
+            update.rssi = line.rssi;
+            if updates.send(update).await.is_err() {
+                return Ok(false);
+            }
+        }
+    }
+
+    Ok(true)
+}
+
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// TCP client of a dump978 raw feed, which reconnects whenever the connection drops.
+pub struct UatClient {
+    addr: String,
+}
+
+impl UatClient {
+    /// Client of the feed at `addr`, e.g. `localhost:30978`.
+    pub fn new(addr: impl Into<String>) -> Self {
+        Self { addr: addr.into() }
+    }
+
+    /// Keep receiving updates until the receiving end of the channel is dropped.
+    pub async fn run(self, updates: mpsc::Sender<Update>) {
+        let mut backoff = MIN_BACKOFF;
+
+        loop {
+            match TcpStream::connect(&self.addr).await {
+                Ok(stream) => {
+                    log::info!("Connected to UAT feed at {}.", self.addr);
+                    backoff = MIN_BACKOFF;
+
+                    match read_updates(stream, &updates).await {
+                        Ok(true) => log::warn!("UAT feed at {} closed.", self.addr),
+                        Ok(false) => return,
+                        Err(err) => log::warn!("UAT feed at {} failed: {err}.", self.addr),
+                    }
+                }
+                Err(err) => {
+                    log::warn!("Can't connect to UAT feed at {}: {err}.", self.addr);
+                }
+            }
+
+            if updates.is_closed() {
+                return;
+            }
+
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mode_s::adsb::AircraftCategory;
+
+    /// Long message of payload type 1 from A1B2C3, at 45N 90W and 5000 ft barometric, flying
+    /// north at 300 knots, with callsign N12345 and a GNSS altitude of 5100 ft.
+    const LONG_MESSAGE: &str =
+        "08A1B2C34000018000000F1804B400800009D90CFC25040000000200000F50000000";
+
+    #[test]
+    fn parsing_lines() {
+        let line = parse_line(&format!("-{LONG_MESSAGE};rs=2;rssi=-20.5;t=1700000000.1;")).unwrap();
+        assert_eq!(line.rssi, Some(-20.5));
+        assert_eq!(line.message.header.address, Icao(0xA1B2C3));
+
+        assert!(parse_line(&format!("-{LONG_MESSAGE};")).is_some());
+        assert_eq!(parse_line(&format!("+{LONG_MESSAGE};")), None);
+        assert_eq!(parse_line("-0A8A;rs=1;"), None);
+        assert_eq!(parse_line(""), None);
+    }
+
+    #[test]
+    fn converting_to_updates() {
+        let line = parse_line(&format!("-{LONG_MESSAGE};")).unwrap();
+        let update = to_update(line.message, Instant::now());
+
+        assert_eq!(update.link, Link::Uat);
+        assert_eq!(update.source, Source::Adsb);
+        assert_eq!(update.icao, Icao(0xA1B2C3));
+        assert_eq!(update.position, Some(mode_s::geo::lat_lon(45.0, -90.0)));
+        assert_eq!(update.altitude, Some(5000));
+        assert_eq!(update.geometric_altitude, Some(5100));
+        assert_eq!(update.on_ground, Some(false));
+        assert_eq!(update.ground_speed, Some(300.0));
+        assert_eq!(update.track, Some(0.0));
+        assert_eq!(update.nic, Some(8));
+        assert_eq!(update.callsign.as_deref(), Some("N12345"));
+        assert_eq!(update.category, Some(AircraftCategory::Light));
+        assert_eq!(update.emergency, Some(false));
+    }
+
+    #[test]
+    fn flagging_track_file_addresses() {
+        // The same message with the TIS-B track file address qualifier.
+        let line = parse_line(&format!("-0B{};", &LONG_MESSAGE[2..])).unwrap();
+        let update = to_update(line.message, Instant::now());
+
+        assert_eq!(update.source, Source::Tisb);
+        assert_eq!(update.icao, Icao::non_icao(0xA1B2C3));
+        assert_ne!(update.icao, Icao(0xA1B2C3));
+    }
+}
@@ -0,0 +1,184 @@
+use mode_s::adsb::{AircraftCategory, EmergencyStatus, VerticalRateSource};
+use mode_s::commb::{NavigationModes, ResolutionAdvisory};
+use mode_s::{Icao, Position, Squawk};
+use std::time::Instant;
+
+/// Data link an update was received on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Link {
+    /// 1090 MHz Mode S, including extended squitter.
+    #[default]
+    ModeS,
+    /// 978 MHz Universal Access Transceiver.
+    Uat,
+}
+
+/// How the information in an update was obtained.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Source {
+    /// Broadcast by the aircraft itself over ADS-B, on either link.
+    Adsb,
+    /// ADS-B rebroadcast by a ground station on the other link.
+    Adsr,
+    /// Traffic information broadcast by a ground station, usually from radar data.
+    Tisb,
+    /// Computed by multilateration of the aircraft's transmissions.
+    Mlat,
+    /// Replies of the aircraft to Mode S interrogations.
+    ModeS,
+    /// The feed does not tell.
+    #[default]
+    Unknown,
+}
+
+impl Source {
+    /// Whether the aircraft reported the information itself, rather than a ground station or
+    /// a network of receivers working it out. Unknown sources are given the benefit of the
+    /// doubt.
+    pub fn is_self_reported(self) -> bool {
+        !matches!(self, Source::Tisb | Source::Mlat)
+    }
+
+    /// Short name, as shown next to the data.
+    pub fn name(self) -> &'static str {
+        match self {
+            Source::Adsb => "ADS-B",
+            Source::Adsr => "ADS-R",
+            Source::Tisb => "TIS-B",
+            Source::Mlat => "MLAT",
+            Source::ModeS => "Mode S",
+            Source::Unknown => "Unknown",
+        }
+    }
+}
+
+/// Information about a single aircraft, received at a single point in time. Every source,
+/// be it raw Mode S frames or one of the text feeds, is turned into these. Fields which were
+/// not part of the message are left as `None`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Update {
+    pub icao: Icao,
+    /// When the message was received.
+    pub time: Instant,
+    pub link: Link,
+    /// Source of every field of the update.
+    pub source: Source,
+    pub callsign: Option<String>,
+    pub category: Option<AircraftCategory>,
+    /// Barometric altitude in feet.
+    pub altitude: Option<i32>,
+    /// GNSS altitude in feet.
+    pub geometric_altitude: Option<i32>,
+    pub position: Option<Position>,
+    pub on_ground: Option<bool>,
+    /// Ground speed in knots.
+    pub ground_speed: Option<f64>,
+    /// Track over ground in degrees, clockwise from the true north.
+    pub track: Option<f64>,
+    /// Indicated airspeed in knots.
+    pub indicated_airspeed: Option<f64>,
+    /// True airspeed in knots.
+    pub true_airspeed: Option<f64>,
+    /// Heading in degrees, clockwise from the magnetic north.
+    pub heading: Option<f64>,
+    /// Vertical rate in feet per minute, positive when climbing.
+    pub vertical_rate: Option<i32>,
+    pub vertical_rate_source: Option<VerticalRateSource>,
+    /// GNSS altitude minus barometric altitude, in feet.
+    pub gnss_altitude_difference: Option<i32>,
+    /// Mach number.
+    pub mach: Option<f64>,
+    /// Roll angle in degrees, positive when the right wing is down.
+    pub roll: Option<f64>,
+    /// Rate of the track change in degrees per second, positive when turning right.
+    pub track_rate: Option<f64>,
+    /// Altitude selected on the MCP or FCU, in feet.
+    pub selected_altitude: Option<i32>,
+    /// Altitude selected in the FMS, in feet.
+    pub fms_selected_altitude: Option<i32>,
+    /// Heading selected on the MCP or FCU, in degrees.
+    pub selected_heading: Option<f64>,
+    /// Barometric pressure setting in hPa.
+    pub qnh: Option<f64>,
+    pub navigation_modes: Option<NavigationModes>,
+    /// ADS-B version of the transmitter.
+    pub adsb_version: Option<u8>,
+    /// Type code of the position message, which tells the navigation integrity category.
+    pub position_type_code: Option<u8>,
+    /// Navigation integrity category, for links which broadcast it directly.
+    pub nic: Option<u8>,
+    pub nic_supplement_a: Option<bool>,
+    pub nic_supplement_b: Option<bool>,
+    pub nic_supplement_c: Option<bool>,
+    /// Navigation accuracy category for position.
+    pub nac_p: Option<u8>,
+    /// Source integrity level.
+    pub sil: Option<u8>,
+    /// Geometric vertical accuracy.
+    pub gva: Option<u8>,
+    /// Whether the barometric altitude has been cross-checked against another source.
+    pub nic_baro: Option<bool>,
+    pub resolution_advisory: Option<ResolutionAdvisory>,
+    pub squawk: Option<Squawk>,
+    /// The Mode A code was changed recently.
+    pub alert: Option<bool>,
+    /// The aircraft declared an emergency.
+    pub emergency: Option<bool>,
+    /// Kind of emergency, when the source tells it.
+    pub emergency_status: Option<EmergencyStatus>,
+    /// Special position identification, i.e. the pilot pressed IDENT.
+    pub spi: Option<bool>,
+    /// Signal level in dBFS.
+    pub rssi: Option<f64>,
+}
+
+impl Update {
+    /// An update which only tells that the aircraft was heard on 1090 MHz.
+    pub fn new(icao: Icao, time: Instant) -> Self {
+        Self {
+            icao,
+            time,
+            link: Link::default(),
+            source: Source::default(),
+            callsign: None,
+            category: None,
+            altitude: None,
+            geometric_altitude: None,
+            position: None,
+            on_ground: None,
+            ground_speed: None,
+            track: None,
+            indicated_airspeed: None,
+            true_airspeed: None,
+            heading: None,
+            vertical_rate: None,
+            vertical_rate_source: None,
+            gnss_altitude_difference: None,
+            mach: None,
+            roll: None,
+            track_rate: None,
+            selected_altitude: None,
+            fms_selected_altitude: None,
+            selected_heading: None,
+            qnh: None,
+            navigation_modes: None,
+            adsb_version: None,
+            position_type_code: None,
+            nic: None,
+            nic_supplement_a: None,
+            nic_supplement_b: None,
+            nic_supplement_c: None,
+            nac_p: None,
+            sil: None,
+            gva: None,
+            nic_baro: None,
+            resolution_advisory: None,
+            squawk: None,
+            alert: None,
+            emergency: None,
+            emergency_status: None,
+            spi: None,
+            rssi: None,
+        }
+    }
+}
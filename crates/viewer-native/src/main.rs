@@ -1,8 +1,22 @@
+use mode_s::ErrorCorrection;
+use receiver::Tracker;
+use receiver::aircraft_json::AircraftJsonPoller;
+use receiver::alerts::Alerts;
+use receiver::avr::{AvrClient, AvrSource};
+use receiver::beast::BeastClient;
+use receiver::coverage::Coverage;
+use receiver::mode_ac::ModeAcAssociator;
+use receiver::sbs::SbsClient;
+use receiver::uat::UatClient;
 use sqlx::Row;
 use sqlx::sqlite::SqlitePoolOptions;
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
 use utils::path::{config_path, variable_data_path};
+use viewer::components::aircraft_list::AircraftList;
 use viewer::components::map::map_overlay::MapOverlay;
+use viewer::db::registry::Registry;
+use viewer::location::ReceiverLocation;
 use viewer::viewer::Viewer;
 use viewer::{Airport, AppState};
 
@@ -10,6 +24,7 @@ use viewer::{Airport, AppState};
 #[tokio::main]
 async fn main() -> Result<(), eframe::Error> {
     env_logger::init();
+    let args: Vec<String> = std::env::args().collect();
     let key_store = kv_sys::KVStore::new(config_path() + "settings.toml");
 
     start_puffin_server();
@@ -54,7 +69,7 @@ async fn main() -> Result<(), eframe::Error> {
             city: row.get("city"),
             country: row.get("country"),
             lat,
-            lon
+            lon,
         };
 
         airports.push(airport);
@@ -65,9 +80,125 @@ async fn main() -> Result<(), eframe::Error> {
         println!("Tabla: {}", name);
     }
 
+    viewer::db::registry::create_table(&conn)
+        .await
+        .expect("Can't create the registry table");
+    // `--import-registry <csv>` fills the registry and exits.
+    match args.as_slice() {
+        [_, flag, path] if flag == "--import-registry" => {
+            match viewer::db::registry::import(&conn, path).await {
+                Ok(count) => println!("Imported {count} aircraft into the registry."),
+                Err(err) => eprintln!("Can't import the registry from {path}: {err}"),
+            }
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let sbs_address = key_store
+        .get_as_str("receiver.sbs_address")
+        .unwrap_or("localhost:30003")
+        .to_string();
+
+    let receiver_location = Arc::new(RwLock::new(
+        ReceiverLocation::load(&key_store).map(|location| location.position()),
+    ));
+
+    let tracker = Arc::new(RwLock::new(Tracker::default()));
+    let (updates_tx, updates_rx) = tokio::sync::mpsc::channel(1024);
+    tokio::spawn(receiver::tracker::track(Arc::clone(&tracker), updates_rx));
+
+    let mut coverage = Coverage::default();
+    coverage.set_receiver(*receiver_location.read().unwrap());
+    viewer::db::coverage::create_table(&conn)
+        .await
+        .expect("Can't create the coverage table");
+    if let Err(err) = viewer::db::coverage::load(&conn, &mut coverage).await {
+        log::warn!("Can't load the coverage: {err}.");
+    }
+    let coverage = Arc::new(RwLock::new(coverage));
+    tokio::spawn(receiver::coverage::record(
+        Arc::clone(&coverage),
+        Arc::clone(&tracker),
+        Arc::clone(&receiver_location),
+    ));
+    tokio::spawn(viewer::db::coverage::persist(
+        conn.clone(),
+        Arc::clone(&coverage),
+    ));
+
+    let alerts = Arc::new(RwLock::new(Alerts::new()));
+    viewer::db::alerts::create_table(&conn)
+        .await
+        .expect("Can't create the alerts table");
+    tokio::spawn(receiver::alerts::watch(
+        Arc::clone(&alerts),
+        Arc::clone(&tracker),
+    ));
+    tokio::spawn(viewer::db::alerts::persist(
+        conn.clone(),
+        Arc::clone(&alerts),
+    ));
+
+    let registry = Arc::new(Registry::new(conn.clone()));
+    tokio::spawn(viewer::db::registry::enrich(
+        Arc::clone(&registry),
+        Arc::clone(&tracker),
+    ));
+
+    let mode_ac = Arc::new(RwLock::new(ModeAcAssociator::new()));
+    let (mode_ac_tx, mode_ac_rx) = tokio::sync::mpsc::channel(1024);
+    tokio::spawn(receiver::mode_ac::associate(
+        Arc::clone(&mode_ac),
+        Arc::clone(&tracker),
+        mode_ac_rx,
+    ));
+
+    // Beast carries Mode A/C replies, which the SBS feed lacks.
+    if let Some(beast_address) = key_store.get_as_str("receiver.beast_address") {
+        let client = BeastClient::new(beast_address, ErrorCorrection::SingleBit)
+            .with_receiver(Arc::clone(&receiver_location));
+        tokio::spawn(client.run(updates_tx.clone(), mode_ac_tx));
+    }
+    // dump978 is only run where UAT is in use, so there is no default address.
+    if let Some(uat_address) = key_store.get_as_str("receiver.uat_address") {
+        tokio::spawn(UatClient::new(uat_address).run(updates_tx.clone()));
+    }
+    if let Some(avr_address) = key_store.get_as_str("receiver.avr_address") {
+        let client = AvrClient::new(
+            AvrSource::Tcp(avr_address.into()),
+            ErrorCorrection::SingleBit,
+        )
+        .with_receiver(Arc::clone(&receiver_location));
+        tokio::spawn(client.run(updates_tx.clone()));
+    }
+    // tar1090 and readsb rewrite the file every second.
+    if let Some(url) = key_store.get_as_str("receiver.aircraft_json_url") {
+        let poller = AircraftJsonPoller::new(url, Duration::from_secs(1));
+        tokio::spawn(poller.run(updates_tx.clone()));
+    }
+    // `--avr <file|->` replays a captured log, or reads e.g. `rtl_adsb` from the standard input.
+    if let Some(source) = args
+        .windows(2)
+        .find(|pair| pair[0] == "--avr")
+        .map(|pair| AvrSource::from_arg(&pair[1]))
+    {
+        let client = AvrClient::new(source, ErrorCorrection::SingleBit)
+            .with_receiver(Arc::clone(&receiver_location));
+        tokio::spawn(client.run(updates_tx.clone()));
+    }
+    tokio::spawn(SbsClient::new(sbs_address).run(updates_tx));
+
     let app_state = AppState {
         store: Arc::new(RwLock::new(key_store)),
         airports: Arc::new(RwLock::new(airports)),
+        tracker,
+        mode_ac,
+        receiver_location,
+        coverage,
+        alerts,
+        selection: Arc::new(RwLock::new(None)),
+        registry,
     };
 
     eframe::run_native(
@@ -83,6 +214,7 @@ async fn main() -> Result<(), eframe::Error> {
             let map_memory = Arc::clone(&viewer.map_info.map_memory);
 
             viewer.add_component(MapOverlay::new(map_memory, Vec::new()));
+            viewer.add_component(AircraftList::new(Arc::clone(&viewer.app_state)));
 
             Ok(Box::new(viewer))
         }),
@@ -111,4 +243,4 @@ fn start_puffin_server() {
             log::error!("Failed to start puffin server: {err}");
         }
     };
-}
\ No newline at end of file
+}
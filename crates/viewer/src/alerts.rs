@@ -0,0 +1,26 @@
+//! How the viewer reacts to alerts.
+
+const CENTER_ON_EMERGENCY_KEY: &str = "viewer.alerts.center_on_emergency";
+const EMERGENCIES_ONLY_KEY: &str = "viewer.alerts.emergencies_only";
+
+/// Alert settings, persisted in the key-value store.
+pub struct AlertSettings {
+    /// Center the map on aircraft raising a new emergency alert.
+    pub center_on_emergency: bool,
+    /// Don't alert on the SPI and alert flags.
+    pub emergencies_only: bool,
+}
+
+impl AlertSettings {
+    pub fn load(store: &kv_sys::KVStore) -> Self {
+        Self {
+            center_on_emergency: store.get_as_bool_or_default(CENTER_ON_EMERGENCY_KEY, false),
+            emergencies_only: store.get_as_bool_or_default(EMERGENCIES_ONLY_KEY, false),
+        }
+    }
+
+    pub fn save(&self, store: &mut kv_sys::KVStore) {
+        store.set(CENTER_ON_EMERGENCY_KEY, self.center_on_emergency);
+        store.set(EMERGENCIES_ONLY_KEY, self.emergencies_only);
+    }
+}
@@ -0,0 +1,329 @@
+use crate::AppState;
+use crate::components::{Component, Window};
+use crate::filter::FilterSettings;
+use egui::{Context, RichText, Sense, TextEdit};
+use egui_extras::{Column, TableBuilder};
+use mode_s::{Icao, geo};
+use std::cmp::Ordering;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+const OPEN_KEY: &str = "viewer.windows.aircraft_list_open";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SortColumn {
+    Callsign,
+    Registration,
+    Type,
+    Altitude,
+    Speed,
+    Distance,
+    Age,
+    Squawk,
+}
+
+impl SortColumn {
+    const ALL: [SortColumn; 8] = [
+        SortColumn::Callsign,
+        SortColumn::Registration,
+        SortColumn::Type,
+        SortColumn::Altitude,
+        SortColumn::Speed,
+        SortColumn::Distance,
+        SortColumn::Age,
+        SortColumn::Squawk,
+    ];
+
+    fn title(self) -> &'static str {
+        match self {
+            SortColumn::Callsign => "Callsign",
+            SortColumn::Registration => "Registration",
+            SortColumn::Type => "Type",
+            SortColumn::Altitude => "Altitude",
+            SortColumn::Speed => "Speed",
+            SortColumn::Distance => "Distance",
+            SortColumn::Age => "Age",
+            SortColumn::Squawk => "Squawk",
+        }
+    }
+}
+
+/// Snapshot of an aircraft, taken so the tracker is not locked while the table is drawn.
+struct Row {
+    icao: Icao,
+    callsign: Option<String>,
+    /// From the registry.
+    registration: Option<String>,
+    /// ICAO type designator, from the registry.
+    type_code: Option<String>,
+    altitude: Option<i32>,
+    on_ground: bool,
+    /// Knots.
+    speed: Option<f64>,
+    /// Nautical miles from the receiver.
+    distance: Option<f64>,
+    age: Duration,
+    squawk: Option<String>,
+    /// Matches the highlighting filter.
+    highlighted: bool,
+}
+
+impl Row {
+    fn name(&self) -> String {
+        self.callsign
+            .clone()
+            .unwrap_or_else(|| self.icao.to_string())
+    }
+
+    fn matches(&self, filter: &str) -> bool {
+        let filter = filter.trim().to_uppercase();
+        filter.is_empty()
+            || self.icao.to_string().contains(&filter)
+            || self
+                .callsign
+                .as_ref()
+                .is_some_and(|callsign| callsign.to_uppercase().contains(&filter))
+            || self
+                .registration
+                .as_ref()
+                .is_some_and(|registration| registration.to_uppercase().contains(&filter))
+            || self
+                .squawk
+                .as_ref()
+                .is_some_and(|squawk| squawk.contains(&filter))
+    }
+
+    /// Compare by `column`. Missing values always go last, whatever the direction.
+    fn compare(&self, other: &Row, column: SortColumn, ascending: bool) -> Ordering {
+        fn by<T: PartialOrd>(a: Option<T>, b: Option<T>, ascending: bool) -> Ordering {
+            match (a, b) {
+                (Some(a), Some(b)) => {
+                    let ordering = a.partial_cmp(&b).unwrap_or(Ordering::Equal);
+                    if ascending {
+                        ordering
+                    } else {
+                        ordering.reverse()
+                    }
+                }
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            }
+        }
+
+        match column {
+            SortColumn::Callsign => by(Some(self.name()), Some(other.name()), ascending),
+            SortColumn::Registration => by(
+                self.registration.as_ref(),
+                other.registration.as_ref(),
+                ascending,
+            ),
+            SortColumn::Type => by(self.type_code.as_ref(), other.type_code.as_ref(), ascending),
+            SortColumn::Altitude => by(self.altitude, other.altitude, ascending),
+            SortColumn::Speed => by(self.speed, other.speed, ascending),
+            SortColumn::Distance => by(self.distance, other.distance, ascending),
+            SortColumn::Age => by(Some(self.age), Some(other.age), ascending),
+            SortColumn::Squawk => by(self.squawk.as_ref(), other.squawk.as_ref(), ascending),
+        }
+    }
+}
+
+/// Table of the tracked aircraft not hidden by the active filter, which can be sorted by clicking
+/// on the column headers and searched by callsign, ICAO address, registration or squawk. Clicking
+/// on a row selects the aircraft.
+pub struct AircraftList {
+    app_state: Arc<RwLock<AppState>>,
+    filter: String,
+    sort: SortColumn,
+    ascending: bool,
+}
+
+impl AircraftList {
+    pub fn new(app_state: Arc<RwLock<AppState>>) -> Self {
+        Self {
+            app_state,
+            filter: String::new(),
+            sort: SortColumn::Distance,
+            ascending: true,
+        }
+    }
+
+    fn rows(&self) -> Vec<Row> {
+        let app_state = self.app_state.read().unwrap();
+        let receiver = *app_state.receiver_location.read().unwrap();
+        let settings = FilterSettings::load(&app_state.store.read().unwrap());
+        let hiding = settings.hiding(receiver);
+        let highlighting = settings.highlighting(receiver);
+        let tracker = app_state.tracker.read().unwrap();
+        let now = Instant::now();
+
+        let mut rows: Vec<_> = tracker
+            .iter()
+            .filter(|aircraft| {
+                hiding
+                    .as_ref()
+                    .is_none_or(|filter| filter.matches(aircraft))
+            })
+            .map(|aircraft| {
+                let entry = app_state.registry.cached(aircraft.icao).unwrap_or_default();
+                Row {
+                    icao: aircraft.icao,
+                    callsign: aircraft
+                        .callsign
+                        .as_ref()
+                        .map(|callsign| callsign.value.clone()),
+                    registration: entry.registration,
+                    type_code: entry.type_code,
+                    altitude: aircraft.altitude.map(|altitude| altitude.value),
+                    on_ground: aircraft.on_ground.is_some_and(|on_ground| on_ground.value),
+                    speed: aircraft.ground_speed.map(|speed| speed.value),
+                    distance: receiver.zip(aircraft.position).map(|(receiver, position)| {
+                        geo::distance(receiver, position.value) / geo::METERS_PER_NAUTICAL_MILE
+                    }),
+                    age: aircraft.age(now),
+                    squawk: aircraft.squawk.map(|squawk| squawk.value.to_string()),
+                    highlighted: highlighting
+                        .as_ref()
+                        .is_some_and(|filter| filter.matches(aircraft)),
+                }
+            })
+            .filter(|row| row.matches(&self.filter))
+            .collect();
+
+        rows.sort_by(|a, b| a.compare(b, self.sort, self.ascending));
+        rows
+    }
+
+    fn table(&mut self, ui: &mut egui::Ui) {
+        let rows = self.rows();
+        let selection = Arc::clone(&self.app_state.read().unwrap().selection);
+        let selected = *selection.read().unwrap();
+        let mut clicked = None;
+
+        TableBuilder::new(ui)
+            .striped(true)
+            .sense(Sense::click())
+            .column(Column::auto().at_least(80.0))
+            .columns(Column::auto().at_least(60.0), SortColumn::ALL.len() - 1)
+            .header(20.0, |mut header| {
+                for column in SortColumn::ALL {
+                    header.col(|ui| {
+                        let arrow = match (self.sort == column, self.ascending) {
+                            (false, _) => "",
+                            (true, true) => " ⏶",
+                            (true, false) => " ⏷",
+                        };
+                        if ui.button(format!("{}{arrow}", column.title())).clicked() {
+                            if self.sort == column {
+                                self.ascending = !self.ascending;
+                            } else {
+                                self.sort = column;
+                                self.ascending = true;
+                            }
+                        }
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(18.0, rows.len(), |mut table_row| {
+                    let row = &rows[table_row.index()];
+                    table_row.set_selected(selected == Some(row.icao));
+
+                    table_row.col(|ui| {
+                        if row.highlighted {
+                            ui.label(RichText::new(row.name()).strong());
+                        } else {
+                            ui.label(row.name());
+                        }
+                    });
+                    table_row.col(|ui| {
+                        ui.label(row.registration.as_deref().unwrap_or("-"));
+                    });
+                    table_row.col(|ui| {
+                        ui.label(row.type_code.as_deref().unwrap_or("-"));
+                    });
+                    table_row.col(|ui| {
+                        match row.altitude {
+                            _ if row.on_ground => ui.label("GND"),
+                            Some(altitude) => ui.label(format!("{altitude} ft")),
+                            None => ui.label("-"),
+                        };
+                    });
+                    table_row.col(|ui| {
+                        ui.label(
+                            row.speed
+                                .map_or("-".into(), |speed| format!("{speed:.0} kt")),
+                        );
+                    });
+                    table_row.col(|ui| {
+                        ui.label(
+                            row.distance
+                                .map_or("-".into(), |distance| format!("{distance:.1} NM")),
+                        );
+                    });
+                    table_row.col(|ui| {
+                        ui.label(format!("{} s", row.age.as_secs()));
+                    });
+                    table_row.col(|ui| {
+                        ui.label(row.squawk.as_deref().unwrap_or("-"));
+                    });
+
+                    if table_row.response().clicked() {
+                        clicked = Some(row.icao);
+                    }
+                });
+            });
+
+        if clicked.is_some() {
+            *selection.write().unwrap() = clicked;
+        }
+    }
+}
+
+impl Component for AircraftList {
+    fn draw(&mut self, ctx: &Context) {
+        let mut open = self.is_open();
+        if !open {
+            return;
+        }
+
+        egui::Window::new("Aircraft")
+            .resizable(true)
+            .collapsible(true)
+            .default_size([480.0, 360.0])
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Filter");
+                    ui.add(
+                        TextEdit::singleline(&mut self.filter)
+                            .hint_text("Callsign, ICAO, registration or squawk"),
+                    );
+                });
+                ui.separator();
+                self.table(ui);
+            });
+
+        if !open {
+            self.close();
+        }
+    }
+}
+
+impl Window for AircraftList {
+    fn open(&mut self) {
+        let app_state = self.app_state.read().unwrap();
+        app_state.store.write().unwrap().set(OPEN_KEY, true);
+    }
+
+    fn close(&mut self) {
+        let app_state = self.app_state.read().unwrap();
+        app_state.store.write().unwrap().set(OPEN_KEY, false);
+    }
+
+    fn is_open(&self) -> bool {
+        let app_state = self.app_state.read().unwrap();
+        let store = app_state.store.read().unwrap();
+        store.get_as_bool_or_default(OPEN_KEY, false)
+    }
+}
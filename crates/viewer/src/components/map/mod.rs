@@ -1 +1 @@
-pub mod map_overlay;
\ No newline at end of file
+pub mod map_overlay;
@@ -1,3 +1,4 @@
+pub mod aircraft_list;
 pub mod map;
 
 pub trait Component {
@@ -8,4 +9,4 @@ pub trait Window: Component {
     fn open(&mut self);
     fn close(&mut self);
     fn is_open(&self) -> bool;
-}
\ No newline at end of file
+}
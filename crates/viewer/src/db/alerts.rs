@@ -0,0 +1,86 @@
+//! Log of the alerts: when they were raised, acknowledged and ended.
+
+use crate::ArcRwLock;
+use receiver::alerts::{Alert, AlertEvent, Alerts};
+use sqlx::SqlitePool;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How often new events are written to the database.
+const SAVE_INTERVAL: Duration = Duration::from_secs(1);
+
+pub async fn create_table(pool: &SqlitePool) -> sqlx::Result<()> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS alerts (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            icao TEXT NOT NULL,
+            callsign TEXT,
+            squawk TEXT,
+            kind TEXT NOT NULL,
+            raised INTEGER NOT NULL,
+            acknowledged INTEGER,
+            ended INTEGER
+        )",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+fn millis(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+/// Set `column` to now on the row of `alert`.
+async fn stamp(pool: &SqlitePool, alert: &Alert, column: &str) -> sqlx::Result<()> {
+    sqlx::query(&format!(
+        "UPDATE alerts SET {column} = ? WHERE icao = ? AND kind = ? AND raised = ?"
+    ))
+    .bind(millis(SystemTime::now()))
+    .bind(alert.icao.to_string())
+    .bind(alert.kind.description())
+    .bind(millis(alert.raised))
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn save(pool: &SqlitePool, event: &AlertEvent) -> sqlx::Result<()> {
+    match event {
+        AlertEvent::Raised(alert) => {
+            sqlx::query(
+                "INSERT INTO alerts (icao, callsign, squawk, kind, raised) VALUES (?, ?, ?, ?, ?)",
+            )
+            .bind(alert.icao.to_string())
+            .bind(alert.callsign.as_deref())
+            .bind(alert.squawk.map(|squawk| squawk.to_string()))
+            .bind(alert.kind.description())
+            .bind(millis(alert.raised))
+            .execute(pool)
+            .await?;
+        }
+        AlertEvent::Acknowledged(alert) => stamp(pool, alert, "acknowledged").await?,
+        AlertEvent::Ended(alert) => stamp(pool, alert, "ended").await?,
+    }
+
+    Ok(())
+}
+
+/// Write the alert events as they happen, forever.
+pub async fn persist(pool: SqlitePool, alerts: ArcRwLock<Alerts>) {
+    let mut interval = tokio::time::interval(SAVE_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let events = alerts.write().unwrap().take_events();
+        for event in &events {
+            if let Err(err) = save(&pool, event).await {
+                log::warn!("Can't save the alert: {err}.");
+            }
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! Persists the coverage of the receiver so it survives restarts. Only the farthest positions
+//! are stored: their ranges and buckets are worked out again from the receiver location.
+
+use crate::ArcRwLock;
+use mode_s::geo::lat_lon;
+use receiver::coverage::{AltitudeBand, Coverage};
+use sqlx::{Row, SqlitePool};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How often the coverage is written to the database.
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+pub async fn create_table(pool: &SqlitePool) -> sqlx::Result<()> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS coverage (
+            band INTEGER NOT NULL,
+            latitude REAL NOT NULL,
+            longitude REAL NOT NULL,
+            seen INTEGER NOT NULL
+        )",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Record the stored positions into `coverage`, whose receiver must already be set.
+pub async fn load(pool: &SqlitePool, coverage: &mut Coverage) -> sqlx::Result<()> {
+    let rows = sqlx::query("SELECT band, latitude, longitude, seen FROM coverage")
+        .fetch_all(pool)
+        .await?;
+
+    for row in rows {
+        let Some(band) = AltitudeBand::from_index(row.get::<i64, _>("band") as usize) else {
+            continue;
+        };
+        let position = lat_lon(row.get("latitude"), row.get("longitude"));
+        let seen = UNIX_EPOCH + Duration::from_secs(row.get::<i64, _>("seen").max(0) as u64);
+        coverage.record(band, position, seen);
+    }
+
+    coverage.expire(SystemTime::now());
+    Ok(())
+}
+
+/// Replace the stored positions with those of `coverage`. Nothing is saved while the receiver
+/// location is unknown, as nothing could be recorded, so that the stored coverage is kept for
+/// when it is set again.
+pub async fn save(pool: &SqlitePool, coverage: &Coverage) -> sqlx::Result<()> {
+    if coverage.receiver().is_none() {
+        return Ok(());
+    }
+
+    let mut transaction = pool.begin().await?;
+
+    sqlx::query("DELETE FROM coverage")
+        .execute(&mut *transaction)
+        .await?;
+
+    for (band, point) in coverage.iter() {
+        let seen = point
+            .seen
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+
+        sqlx::query("INSERT INTO coverage (band, latitude, longitude, seen) VALUES (?, ?, ?, ?)")
+            .bind(band.index() as i64)
+            .bind(point.position.y())
+            .bind(point.position.x())
+            .bind(seen)
+            .execute(&mut *transaction)
+            .await?;
+    }
+
+    transaction.commit().await
+}
+
+/// Save the coverage every minute, forever.
+pub async fn persist(pool: SqlitePool, coverage: ArcRwLock<Coverage>) {
+    let mut interval = tokio::time::interval(SAVE_INTERVAL);
+    // The first tick completes immediately, when there is nothing new to save.
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+
+        let snapshot = coverage.read().unwrap().clone();
+        if let Err(err) = save(&pool, &snapshot).await {
+            log::warn!("Can't save the coverage: {err}.");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    #[tokio::test]
+    async fn keeping_coverage_without_receiver() {
+        // Every connection to `:memory:` opens a database of its own.
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        create_table(&pool).await.unwrap();
+
+        let receiver = lat_lon(51.5, -0.1);
+        let mut coverage = Coverage::default();
+        coverage.set_receiver(Some(receiver));
+        coverage.record(AltitudeBand::High, lat_lon(52.5, -0.1), SystemTime::now());
+        save(&pool, &coverage).await.unwrap();
+
+        // Saved at startup before the receiver location is set.
+        save(&pool, &Coverage::default()).await.unwrap();
+
+        let mut loaded = Coverage::default();
+        loaded.set_receiver(Some(receiver));
+        load(&pool, &mut loaded).await.unwrap();
+        assert_eq!(loaded.iter().count(), 1);
+    }
+}
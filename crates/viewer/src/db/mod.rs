@@ -0,0 +1,5 @@
+//! Tables the viewer keeps in the SQLite database, next to the airports.
+
+pub mod alerts;
+pub mod coverage;
+pub mod registry;
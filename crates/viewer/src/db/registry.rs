@@ -0,0 +1,353 @@
+//! Registry of aircraft by address, imported from a CSV export of the OpenSky aircraft database
+//! or of a BaseStation.sqb `Aircraft` table, and looked up as aircraft show up.
+
+use crate::ArcRwLock;
+use mode_s::Icao;
+use receiver::Tracker;
+use sqlx::{Row, SqlitePool};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::RwLock;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+/// How often the tracker is checked for aircraft which were not looked up yet.
+const LOOKUP_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Rows inserted per transaction while importing.
+const IMPORT_BATCH: usize = 10_000;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ImportError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error("The file is empty.")]
+    Empty,
+    #[error("The header has no `{0}` column.")]
+    MissingColumn(&'static str),
+}
+
+/// What the registry knows about an aircraft.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RegistryEntry {
+    pub registration: Option<String>,
+    /// ICAO type designator, e.g. `B738`.
+    pub type_code: Option<String>,
+    pub operator: Option<String>,
+    pub built: Option<i64>,
+}
+
+pub async fn create_table(pool: &SqlitePool) -> sqlx::Result<()> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS registry (
+            icao TEXT PRIMARY KEY,
+            registration TEXT,
+            type_code TEXT,
+            operator TEXT,
+            built INTEGER
+        )",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Split a CSV line into its fields. Fields may be quoted with double quotes, or with the
+/// single quotes of the recent OpenSky exports, and quotes are escaped by doubling them.
+fn split_csv(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quote = None;
+    let mut chars = line.trim_end_matches(['\r', '\n']).chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match quote {
+            Some(q) if c == q => {
+                if chars.peek() == Some(&q) {
+                    field.push(q);
+                    chars.next();
+                } else {
+                    quote = None;
+                }
+            }
+            Some(_) => field.push(c),
+            None if (c == '"' || c == '\'') && field.is_empty() => quote = Some(c),
+            None if c == ',' => fields.push(std::mem::take(&mut field)),
+            None => field.push(c),
+        }
+    }
+    fields.push(field);
+
+    fields
+}
+
+/// Where the fields are in the rows, found from the names in the header.
+struct Columns {
+    icao: usize,
+    registration: Option<usize>,
+    type_code: Option<usize>,
+    operator: Option<usize>,
+    built: Option<usize>,
+}
+
+impl Columns {
+    /// Columns of the header line, which may start with a byte order mark.
+    fn from_header(header: &str) -> Result<Self, ImportError> {
+        let header = split_csv(header.trim_start_matches('\u{feff}'));
+        let find = |names: &[&str]| {
+            names.iter().find_map(|name| {
+                header
+                    .iter()
+                    .position(|column| column.trim().eq_ignore_ascii_case(name))
+            })
+        };
+
+        Ok(Self {
+            // OpenSky first, then BaseStation.
+            icao: find(&["icao24", "ModeS"]).ok_or(ImportError::MissingColumn("icao24"))?,
+            registration: find(&["registration"]),
+            type_code: find(&["typecode", "ICAOTypeCode"]),
+            operator: find(&["operator", "RegisteredOwners", "owner"]),
+            built: find(&["built", "YearBuilt"]),
+        })
+    }
+
+    fn parse(&self, fields: &[String]) -> Option<(Icao, RegistryEntry)> {
+        let field = |index: Option<usize>| {
+            index
+                .and_then(|index| fields.get(index))
+                .map(|field| field.trim())
+                .filter(|field| !field.is_empty())
+        };
+
+        let icao = Icao::parse(field(Some(self.icao))?)?;
+        let entry = RegistryEntry {
+            registration: field(self.registration).map(str::to_string),
+            type_code: field(self.type_code).map(str::to_uppercase),
+            operator: field(self.operator).map(str::to_string),
+            // Either a year or a date starting with it.
+            built: field(self.built)
+                .and_then(|built| built.get(..4))
+                .and_then(|year| year.parse().ok()),
+        };
+
+        (entry != RegistryEntry::default()).then_some((icao, entry))
+    }
+}
+
+async fn insert(pool: &SqlitePool, rows: &[(Icao, RegistryEntry)]) -> sqlx::Result<()> {
+    let mut transaction = pool.begin().await?;
+
+    for (icao, entry) in rows {
+        sqlx::query(
+            "INSERT OR REPLACE INTO registry (icao, registration, type_code, operator, built)
+             VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(icao.to_string())
+        .bind(entry.registration.as_deref())
+        .bind(entry.type_code.as_deref())
+        .bind(entry.operator.as_deref())
+        .bind(entry.built)
+        .execute(&mut *transaction)
+        .await?;
+    }
+
+    transaction.commit().await
+}
+
+/// Import the registry CSV at `path`, replacing the entries of the addresses it contains.
+/// Returns the number of imported aircraft.
+pub async fn import(pool: &SqlitePool, path: impl AsRef<Path>) -> Result<usize, ImportError> {
+    let file = tokio::fs::File::open(path).await?;
+    let mut lines = BufReader::new(file).lines();
+
+    let header = lines.next_line().await?.ok_or(ImportError::Empty)?;
+    let columns = Columns::from_header(&header)?;
+
+    let mut batch = Vec::with_capacity(IMPORT_BATCH);
+    let mut imported = 0;
+    while let Some(line) = lines.next_line().await? {
+        if let Some(row) = columns.parse(&split_csv(&line)) {
+            batch.push(row);
+        }
+        if batch.len() == IMPORT_BATCH {
+            insert(pool, &batch).await?;
+            imported += batch.len();
+            batch.clear();
+        }
+    }
+    insert(pool, &batch).await?;
+    imported += batch.len();
+
+    Ok(imported)
+}
+
+/// Registry entries of the aircraft seen so far, filled in the background so that the frame
+/// never waits for the database.
+#[derive(Debug)]
+pub struct Registry {
+    pool: SqlitePool,
+    /// `None` for the addresses which are not in the registry.
+    cache: RwLock<HashMap<Icao, Option<RegistryEntry>>>,
+}
+
+impl Registry {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self {
+            pool,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Entry of `icao` if it was already looked up. Never blocks on the database.
+    pub fn cached(&self, icao: Icao) -> Option<RegistryEntry> {
+        self.cache.read().unwrap().get(&icao).cloned().flatten()
+    }
+
+    fn is_cached(&self, icao: Icao) -> bool {
+        self.cache.read().unwrap().contains_key(&icao)
+    }
+
+    /// Entry of `icao`, from the cache or else the database.
+    pub async fn lookup(&self, icao: Icao) -> sqlx::Result<Option<RegistryEntry>> {
+        if let Some(entry) = self.cache.read().unwrap().get(&icao) {
+            return Ok(entry.clone());
+        }
+
+        let row = sqlx::query(
+            "SELECT registration, type_code, operator, built FROM registry WHERE icao = ?",
+        )
+        .bind(icao.to_string())
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let entry = row.map(|row| RegistryEntry {
+            registration: row.get("registration"),
+            type_code: row.get("type_code"),
+            operator: row.get("operator"),
+            built: row.get("built"),
+        });
+        self.cache.write().unwrap().insert(icao, entry.clone());
+
+        Ok(entry)
+    }
+
+    /// Forget every entry, e.g. after importing a new registry.
+    pub fn clear(&self) {
+        self.cache.write().unwrap().clear();
+    }
+}
+
+/// Look up the aircraft of `tracker` as they show up, forever.
+pub async fn enrich(registry: std::sync::Arc<Registry>, tracker: ArcRwLock<Tracker>) {
+    let mut interval = tokio::time::interval(LOOKUP_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        let new: HashSet<Icao> = tracker
+            .read()
+            .unwrap()
+            .iter()
+            .map(|aircraft| aircraft.icao)
+            .filter(|icao| !registry.is_cached(*icao))
+            .collect();
+
+        for icao in new {
+            if let Err(err) = registry.lookup(icao).await {
+                log::warn!("Can't look up {icao} in the registry: {err}.");
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(fields: &[&str]) -> Vec<String> {
+        fields.iter().map(|field| field.to_string()).collect()
+    }
+
+    #[test]
+    fn splitting_lines() {
+        assert_eq!(split_csv("a,b,,c\r\n"), fields(&["a", "b", "", "c"]));
+        assert_eq!(
+            split_csv(r#""4ca2d6","Ryanair, DAC","B738""#),
+            fields(&["4ca2d6", "Ryanair, DAC", "B738"])
+        );
+        assert_eq!(
+            split_csv("'4ca2d6','Aer Lingus','O''Brien'"),
+            fields(&["4ca2d6", "Aer Lingus", "O'Brien"])
+        );
+        assert_eq!(
+            split_csv(r#""The ""Spirit"" Co",x"#),
+            fields(&[r#"The "Spirit" Co"#, "x"])
+        );
+        // Quotes in the middle of a field are kept.
+        assert_eq!(split_csv("O'Brien,x"), fields(&["O'Brien", "x"]));
+    }
+
+    #[test]
+    fn parsing_opensky_rows() {
+        let header =
+            "\u{feff}'icao24','registration','manufacturericao','typecode','operator','built'";
+        let columns = Columns::from_header(header).unwrap();
+
+        let (icao, entry) = columns
+            .parse(&split_csv(
+                "'4ca2d6','EI-DWF','BOEING','b738','Ryanair','2007-03-01'",
+            ))
+            .unwrap();
+        assert_eq!(icao, Icao(0x4CA2D6));
+        assert_eq!(
+            entry,
+            RegistryEntry {
+                registration: Some("EI-DWF".to_string()),
+                type_code: Some("B738".to_string()),
+                operator: Some("Ryanair".to_string()),
+                built: Some(2007),
+            }
+        );
+
+        // Rows with nothing but the address, or without a valid one, are skipped.
+        assert_eq!(columns.parse(&split_csv("'4ca2d7','','','','',''")), None);
+        assert_eq!(columns.parse(&split_csv("'','EI-DWG','','','',''")), None);
+        assert_eq!(
+            columns.parse(&split_csv("'nothex','EI-DWG','','','',''")),
+            None
+        );
+    }
+
+    #[test]
+    fn parsing_basestation_rows() {
+        let header = "AircraftID,ModeS,Registration,ICAOTypeCode,RegisteredOwners,YearBuilt";
+        let columns = Columns::from_header(header).unwrap();
+
+        let (icao, entry) = columns
+            .parse(&split_csv("12,400940,G-EZAN,A319,easyJet,2004"))
+            .unwrap();
+        assert_eq!(icao, Icao(0x400940));
+        assert_eq!(entry.registration.as_deref(), Some("G-EZAN"));
+        assert_eq!(entry.type_code.as_deref(), Some("A319"));
+        assert_eq!(entry.operator.as_deref(), Some("easyJet"));
+        assert_eq!(entry.built, Some(2004));
+
+        // Short rows and unparsable years leave the fields out.
+        let (_, entry) = columns.parse(&split_csv("13,400941,G-EZAO")).unwrap();
+        assert_eq!(entry.type_code, None);
+        let (_, entry) = columns
+            .parse(&split_csv("14,400942,G-EZAP,A319,,n/a"))
+            .unwrap();
+        assert_eq!(entry.built, None);
+
+        assert!(matches!(
+            Columns::from_header("Registration,ICAOTypeCode"),
+            Err(ImportError::MissingColumn("icao24"))
+        ));
+    }
+}
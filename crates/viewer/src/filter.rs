@@ -0,0 +1,62 @@
+//! The filter applied to the aircraft on the map, in the aircraft list and to the alerts.
+
+use receiver::filter::{Filter, FilterError, FilterMode};
+use std::collections::BTreeMap;
+use walkers::Position;
+
+const EXPRESSION_KEY: &str = "viewer.filter.expression";
+const MODE_KEY: &str = "viewer.filter.mode";
+const PRESETS_KEY: &str = "viewer.filter.presets";
+
+/// Filter settings, persisted in the key-value store.
+pub struct FilterSettings {
+    /// Active expression, empty for no filter.
+    pub expression: String,
+    pub mode: FilterMode,
+    /// Expressions saved by name.
+    pub presets: BTreeMap<String, String>,
+}
+
+impl FilterSettings {
+    pub fn load(store: &kv_sys::KVStore) -> Self {
+        Self {
+            expression: store.get_as_str(EXPRESSION_KEY).unwrap_or("").to_string(),
+            mode: store
+                .get_as_str(MODE_KEY)
+                .and_then(FilterMode::parse)
+                .unwrap_or_default(),
+            presets: store.get_as_str_map(PRESETS_KEY).unwrap_or_default(),
+        }
+    }
+
+    pub fn save(&self, store: &mut kv_sys::KVStore) {
+        store.set(EXPRESSION_KEY, self.expression.as_str());
+        store.set(MODE_KEY, self.mode.name());
+        store.set(PRESETS_KEY, self.presets.clone());
+    }
+
+    pub fn parse(&self) -> Result<Filter, FilterError> {
+        Filter::parse(&self.expression)
+    }
+
+    /// Active filter, measuring distances from `receiver`. `None` when the expression is empty
+    /// or invalid, so that nothing is filtered while it is being typed.
+    pub fn filter(&self, receiver: Option<Position>) -> Option<Filter> {
+        self.parse()
+            .ok()
+            .filter(|filter| !filter.is_empty())
+            .map(|filter| filter.with_receiver(receiver))
+    }
+
+    /// Active filter if it hides the aircraft which don't match it.
+    pub fn hiding(&self, receiver: Option<Position>) -> Option<Filter> {
+        self.filter(receiver)
+            .filter(|_| self.mode == FilterMode::Hide)
+    }
+
+    /// Active filter if it highlights the aircraft which match it.
+    pub fn highlighting(&self, receiver: Option<Position>) -> Option<Filter> {
+        self.filter(receiver)
+            .filter(|_| self.mode == FilterMode::Highlight)
+    }
+}
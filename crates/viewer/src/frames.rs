@@ -1,6 +1,26 @@
+use crate::alerts::AlertSettings;
+use crate::db::registry::RegistryEntry;
+use crate::filter::FilterSettings;
+use crate::location::ReceiverLocation;
+use crate::plugins::aircraft::{AircraftSettings, country_text};
+use crate::plugins::coverage::{CoverageSettings, band_color, bucket_ranges, draw_coverage};
+use crate::plugins::range_rings::RangeRingSettings;
+use crate::plugins::trails::{AltitudeGradient, TrailSettings};
+use crate::selection::SelectionSettings;
 use crate::viewer::{Viewer, ViewerMapInfo};
-use egui::{Align2, ComboBox, Image, MenuBar, RichText, Ui, Window};
-use walkers::{MapMemory, sources::Attribution};
+use egui::{
+    Align2, ComboBox, DragValue, FontId, Grid, Image, MenuBar, RichText, Sense, Slider, Stroke,
+    TextEdit, Ui, Vec2, Window, vec2,
+};
+use mode_s::Icao;
+use mode_s::geo;
+use receiver::alerts::Alerts;
+use receiver::coverage::{AltitudeBand, Coverage, bucket_bearing};
+use receiver::filter::FilterMode;
+use receiver::mode_ac::ModeAcTarget;
+use receiver::tracker::{Aircraft, Timestamped};
+use std::time::{Instant, SystemTime};
+use walkers::{MapMemory, Position, sources::Attribution};
 
 pub fn controls(
     ui: &mut Ui,
@@ -34,6 +54,498 @@ pub fn controls(
     });
 }
 
+pub fn aircraft_controls(ui: &mut Ui, store: &mut kv_sys::KVStore) {
+    ui.collapsing("Aircraft", |ui| {
+        let mut settings = AircraftSettings::load(store);
+        let mut changed = false;
+
+        changed |= ui
+            .checkbox(&mut settings.predict, "Predict positions between updates")
+            .changed();
+        changed |= ui
+            .add(
+                Slider::new(&mut settings.speed_vector_seconds, 0..=300)
+                    .text("Speed vectors (seconds)"),
+            )
+            .changed();
+        changed |= ui
+            .checkbox(
+                &mut settings.uncertainty_circles,
+                "Circle positions with poor integrity",
+            )
+            .changed();
+
+        if changed {
+            settings.save(store);
+        }
+    });
+}
+
+pub fn trail_controls(ui: &mut Ui, store: &mut kv_sys::KVStore) {
+    ui.collapsing("Trails", |ui| {
+        let mut settings = TrailSettings::load(store);
+        let mut changed = false;
+
+        changed |= ui.checkbox(&mut settings.enabled, "Show trails").changed();
+        changed |= ui
+            .add(Slider::new(&mut settings.minutes, 1..=60).text("Length (minutes)"))
+            .changed();
+
+        ui.label("Altitude colors");
+        for (altitude, color) in settings.gradient.stops_mut() {
+            ui.horizontal(|ui| {
+                changed |= ui.color_edit_button_srgba(color).changed();
+                changed |= ui
+                    .add(DragValue::new(altitude).speed(100).suffix(" ft"))
+                    .changed();
+            });
+        }
+
+        if ui.button("Reset colors").clicked() {
+            settings.gradient = AltitudeGradient::default();
+            changed = true;
+        }
+
+        if changed {
+            settings.save(store);
+        }
+    });
+}
+
+pub fn range_ring_controls(ui: &mut Ui, store: &mut kv_sys::KVStore) {
+    ui.collapsing("Range Rings", |ui| {
+        let mut settings = RangeRingSettings::load(store);
+        let mut changed = false;
+
+        changed |= ui
+            .checkbox(&mut settings.enabled, "Show range rings")
+            .changed();
+        changed |= ui
+            .add(Slider::new(&mut settings.interval_nm, 5..=100).text("Interval (NM)"))
+            .changed();
+        changed |= ui
+            .add(Slider::new(&mut settings.count, 1..=10).text("Rings"))
+            .changed();
+        changed |= ui
+            .checkbox(&mut settings.bearing_lines, "Bearing lines")
+            .changed();
+
+        if ReceiverLocation::load(store).is_none() {
+            ui.label("Set the receiver location to show the rings around it.");
+        }
+
+        if changed {
+            settings.save(store);
+        }
+    });
+}
+
+pub fn coverage_controls(ui: &mut Ui, store: &mut kv_sys::KVStore) {
+    ui.collapsing("Coverage", |ui| {
+        let mut settings = CoverageSettings::load(store);
+
+        if ui
+            .checkbox(&mut settings.show_on_map, "Show coverage on the map")
+            .changed()
+        {
+            settings.save(store);
+        }
+    });
+}
+
+/// Edits the filter expression and mode, and saves and loads it as named presets.
+pub fn filter_controls(ui: &mut Ui, store: &mut kv_sys::KVStore) {
+    let mut settings = FilterSettings::load(store);
+    let mut changed = false;
+
+    ui.horizontal(|ui| {
+        ui.label("Filter");
+        changed |= ui
+            .add(
+                TextEdit::singleline(&mut settings.expression)
+                    .hint_text("alt:>10000 !src:mlat mil")
+                    .desired_width(240.0),
+            )
+            .changed();
+        if ui.button("Clear").clicked() {
+            settings.expression.clear();
+            changed = true;
+        }
+    });
+
+    if let Err(err) = settings.parse() {
+        ui.colored_label(ui.visuals().error_fg_color, err.to_string());
+    }
+
+    ui.horizontal(|ui| {
+        changed |= ui
+            .radio_value(&mut settings.mode, FilterMode::Hide, "Hide the others")
+            .changed();
+        changed |= ui
+            .radio_value(
+                &mut settings.mode,
+                FilterMode::Highlight,
+                "Highlight matches",
+            )
+            .changed();
+    });
+
+    ui.collapsing("Syntax", |ui| {
+        Grid::new("filter_syntax").num_columns(2).show(ui, |ui| {
+            for (term, description) in [
+                ("alt:1000-5000", "Altitude range in feet, or >N and <N"),
+                ("alt:low", "Altitude band: low, medium or high"),
+                ("speed:>250", "Ground speed in knots"),
+                ("dist:<50", "Distance from the receiver in NM"),
+                ("call:BAW", "Callsign prefix"),
+                ("icao:4CA", "Address prefix"),
+                ("squawk:7700", "Squawk prefix"),
+                ("cat:A3", "Emitter category"),
+                ("src:adsb,mlat", "Position source"),
+                ("mil", "Military address"),
+                ("!term", "Negates the term"),
+            ] {
+                ui.monospace(term);
+                ui.label(description);
+                ui.end_row();
+            }
+        });
+    });
+
+    ui.separator();
+    ui.label("Presets");
+
+    let mut load = None;
+    let mut delete = None;
+    Grid::new("filter_presets").num_columns(3).show(ui, |ui| {
+        for (name, expression) in &settings.presets {
+            ui.label(name).on_hover_text(expression);
+            if ui.button("Load").clicked() {
+                load = Some(expression.clone());
+            }
+            if ui.button("Delete").clicked() {
+                delete = Some(name.clone());
+            }
+            ui.end_row();
+        }
+    });
+
+    if let Some(expression) = load {
+        settings.expression = expression;
+        changed = true;
+    }
+    if let Some(name) = delete {
+        settings.presets.remove(&name);
+        changed = true;
+    }
+
+    // The name being typed is only kept for as long as the viewer runs.
+    let name_id = ui.id().with("filter_preset_name");
+    let mut name = ui.data_mut(|data| data.get_temp::<String>(name_id).unwrap_or_default());
+    ui.horizontal(|ui| {
+        ui.add(
+            TextEdit::singleline(&mut name)
+                .hint_text("Preset name")
+                .desired_width(160.0),
+        );
+        let valid = !name.trim().is_empty() && settings.parse().is_ok();
+        if ui
+            .add_enabled(valid, egui::Button::new("Save current"))
+            .clicked()
+        {
+            settings
+                .presets
+                .insert(name.trim().to_string(), settings.expression.clone());
+            name.clear();
+            changed = true;
+        }
+    });
+    ui.data_mut(|data| data.insert_temp(name_id, name));
+
+    if changed {
+        settings.save(store);
+    }
+}
+
+/// Polar chart of the farthest ranges by bearing, with a ring every `RING_STEP` nautical miles.
+pub fn coverage_chart(ui: &mut Ui, coverage: &mut Coverage) {
+    const RING_STEP: f64 = 50.0;
+
+    let max_range = AltitudeBand::ALL
+        .into_iter()
+        .filter_map(|band| coverage.max_range(band))
+        .max_by(f64::total_cmp);
+
+    Grid::new("coverage_legend").num_columns(2).show(ui, |ui| {
+        for band in AltitudeBand::ALL {
+            ui.colored_label(band_color(band), band.name());
+            match coverage.max_range(band) {
+                Some(range) => ui.label(format!("{:.0} NM", range / geo::METERS_PER_NAUTICAL_MILE)),
+                None => ui.label("-"),
+            };
+            ui.end_row();
+        }
+    });
+
+    if coverage.receiver().is_none() {
+        ui.label("Set the receiver location to record the coverage.");
+        return;
+    }
+
+    if ui.button("Clear").clicked() {
+        coverage.clear();
+    }
+
+    let side = ui.available_width().min(ui.available_height()).max(200.0);
+    let (response, painter) = ui.allocate_painter(Vec2::splat(side), Sense::hover());
+    let center = response.rect.center();
+    let radius = side / 2.0 - 24.0;
+
+    let rings = max_range
+        .map_or(1.0, |range| {
+            (range / geo::METERS_PER_NAUTICAL_MILE / RING_STEP).ceil()
+        })
+        .max(1.0);
+    let scale = radius / (rings * RING_STEP * geo::METERS_PER_NAUTICAL_MILE) as f32;
+    let at = |bearing: f64, range: f64| {
+        let bearing = bearing.to_radians() as f32;
+        center + vec2(bearing.sin(), -bearing.cos()) * range as f32 * scale
+    };
+
+    let grid = Stroke::new(1.0, ui.visuals().weak_text_color());
+    for ring in 1..=rings as u32 {
+        let range = ring as f64 * RING_STEP;
+        painter.circle_stroke(center, radius * ring as f32 / rings as f32, grid);
+        painter.text(
+            at(0.0, range * geo::METERS_PER_NAUTICAL_MILE),
+            Align2::LEFT_BOTTOM,
+            format!("{range:.0} NM"),
+            FontId::proportional(10.0),
+            ui.visuals().weak_text_color(),
+        );
+    }
+    for bearing in (0..360).step_by(30) {
+        let end = center + (at(bearing as f64, 1.0) - center).normalized() * radius;
+        painter.line_segment([center, end], grid);
+        painter.text(
+            center + (end - center) * 1.08,
+            Align2::CENTER_CENTER,
+            format!("{bearing:03}"),
+            FontId::proportional(10.0),
+            ui.visuals().text_color(),
+        );
+    }
+
+    for band in AltitudeBand::ALL.into_iter().rev() {
+        if coverage.max_range(band).is_none() {
+            continue;
+        }
+
+        let points = bucket_ranges(coverage, band)
+            .iter()
+            .enumerate()
+            .map(|(bucket, range)| at(bucket_bearing(bucket), *range))
+            .collect();
+        draw_coverage(ui, center, points, band);
+    }
+}
+
+/// Lists the alerts, newest first, and lets them be acknowledged. Returns the aircraft whose
+/// "Show" button was clicked.
+pub fn alerts_panel(ui: &mut Ui, alerts: &mut Alerts, store: &mut kv_sys::KVStore) -> Option<Icao> {
+    let mut settings = AlertSettings::load(store);
+    let mut changed = false;
+
+    ui.collapsing("Settings", |ui| {
+        changed |= ui
+            .checkbox(
+                &mut settings.center_on_emergency,
+                "Center the map on new emergencies",
+            )
+            .changed();
+        changed |= ui
+            .checkbox(
+                &mut settings.emergencies_only,
+                "Ignore ident and squawk changes",
+            )
+            .changed();
+    });
+    if changed {
+        settings.save(store);
+    }
+
+    if ui.button("Acknowledge all").clicked() {
+        alerts.acknowledge_all();
+    }
+
+    let now = SystemTime::now();
+    let mut acknowledged = None;
+    let mut shown = None;
+
+    Grid::new("alerts").striped(true).show(ui, |ui| {
+        for alert in alerts.iter() {
+            let color = if alert.acknowledged {
+                ui.visuals().text_color()
+            } else if alert.kind.is_emergency() {
+                ui.visuals().error_fg_color
+            } else {
+                ui.visuals().warn_fg_color
+            };
+            let age = now.duration_since(alert.raised).unwrap_or_default();
+
+            ui.colored_label(color, format!("{} min ago", age.as_secs() / 60));
+            ui.colored_label(
+                color,
+                alert
+                    .callsign
+                    .clone()
+                    .unwrap_or_else(|| alert.icao.to_string()),
+            );
+            ui.colored_label(color, alert.kind.description());
+            ui.label(if alert.active { "Active" } else { "Ended" });
+
+            if !alert.acknowledged && ui.button("Acknowledge").clicked() {
+                acknowledged = Some(alert.id);
+            }
+            if alert.active && ui.button("Show").clicked() {
+                shown = Some(alert.icao);
+            }
+            ui.end_row();
+        }
+    });
+
+    if let Some(id) = acknowledged {
+        alerts.acknowledge(id);
+    }
+
+    shown
+}
+
+/// Grid row with the value of a tracked field and where it came from.
+fn field_row<T>(
+    ui: &mut Ui,
+    name: &str,
+    field: &Option<Timestamped<T>>,
+    format: impl Fn(&T) -> String,
+) {
+    ui.label(name);
+    match field {
+        Some(field) => {
+            ui.label(format(&field.value));
+            ui.weak(field.source.name());
+        }
+        None => {
+            ui.label("-");
+            ui.label("");
+        }
+    }
+    ui.end_row();
+}
+
+/// Everything known about `aircraft`.
+pub fn aircraft_details(
+    ui: &mut Ui,
+    aircraft: &Aircraft,
+    registry: Option<RegistryEntry>,
+    receiver: Option<Position>,
+    store: &mut kv_sys::KVStore,
+) {
+    let mut settings = SelectionSettings::load(store);
+    if ui
+        .checkbox(&mut settings.follow, "Follow on the map")
+        .changed()
+    {
+        settings.save(store);
+    }
+
+    let now = Instant::now();
+
+    Grid::new("aircraft_details")
+        .num_columns(3)
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("ICAO24");
+            ui.label(aircraft.icao.to_string());
+            ui.end_row();
+
+            ui.label("Country");
+            ui.label(country_text(aircraft.icao).unwrap_or_else(|| "-".into()));
+            ui.end_row();
+
+            let registry = registry.unwrap_or_default();
+            for (name, value) in [
+                ("Registration", registry.registration),
+                ("Type", registry.type_code),
+                ("Operator", registry.operator),
+                ("Built", registry.built.map(|year| year.to_string())),
+            ] {
+                ui.label(name);
+                ui.label(value.as_deref().unwrap_or("-"));
+                ui.weak("Registry");
+                ui.end_row();
+            }
+
+            field_row(ui, "Callsign", &aircraft.callsign, |callsign| {
+                callsign.clone()
+            });
+            field_row(ui, "Category", &aircraft.category, |category| {
+                format!("{category:?}")
+            });
+            field_row(ui, "Squawk", &aircraft.squawk, |squawk| squawk.to_string());
+            field_row(ui, "Altitude", &aircraft.altitude, |altitude| {
+                format!("{altitude} ft")
+            });
+            field_row(
+                ui,
+                "Geometric altitude",
+                &aircraft.geometric_altitude,
+                |altitude| format!("{altitude} ft"),
+            );
+            field_row(ui, "Ground speed", &aircraft.ground_speed, |speed| {
+                format!("{speed:.0} kt")
+            });
+            field_row(ui, "Track", &aircraft.track, |track| format!("{track:.0}°"));
+            field_row(ui, "Vertical rate", &aircraft.vertical_rate, |rate| {
+                format!("{rate} ft/min")
+            });
+            field_row(ui, "Position", &aircraft.position, |position| {
+                format!("{:.4} {:.4}", position.y(), position.x())
+            });
+
+            ui.label("Distance");
+            match (receiver, aircraft.position) {
+                (Some(receiver), Some(position)) => ui.label(format!(
+                    "{:.1} NM",
+                    geo::distance(receiver, position.value) / geo::METERS_PER_NAUTICAL_MILE
+                )),
+                _ => ui.label("-"),
+            };
+            ui.end_row();
+
+            ui.label("NIC");
+            match aircraft.integrity() {
+                Some(integrity) => ui.label(integrity.nic.to_string()),
+                None => ui.label("-"),
+            };
+            ui.end_row();
+
+            field_row(ui, "NACp", &aircraft.nac_p, |nac_p| nac_p.to_string());
+            field_row(ui, "SIL", &aircraft.sil, |sil| sil.to_string());
+            field_row(ui, "ADS-B version", &aircraft.adsb_version, |version| {
+                version.to_string()
+            });
+            field_row(ui, "RSSI", &aircraft.rssi, |rssi| format!("{rssi:.1} dBFS"));
+
+            ui.label("Messages");
+            ui.label(aircraft.messages.to_string());
+            ui.end_row();
+
+            ui.label("Last message");
+            ui.label(format!("{:.1} s ago", aircraft.age(now).as_secs_f32()));
+            ui.end_row();
+        });
+}
+
 pub fn zoom(ui: &mut Ui, map_memory: &mut MapMemory) {
     ui.horizontal(|ui| {
         if ui.button(RichText::new("➕").heading()).clicked() {
@@ -46,7 +558,7 @@ pub fn zoom(ui: &mut Ui, map_memory: &mut MapMemory) {
     });
 }
 
-pub fn go_to_my_position(ui: &mut Ui, map_memory: &mut MapMemory) {
+pub fn go_to_my_position(ui: &mut Ui, map_memory: &mut MapMemory, receiver: Option<Position>) {
     if let Some(position) = map_memory.detached() {
         Window::new("Go to my position")
             .collapsible(false)
@@ -55,11 +567,14 @@ pub fn go_to_my_position(ui: &mut Ui, map_memory: &mut MapMemory) {
             .anchor(Align2::RIGHT_BOTTOM, [-10., -10.])
             .fixed_size([500., 50.])
             .show(ui.ctx(), |ui| {
-                ui.label(format!(
-                    "center at {:.04} {:.04}",
-                    position.x(),
-                    position.y()
-                ));
+                let mut label = format!("center at {:.04} {:.04}", position.x(), position.y());
+                if let Some(receiver) = receiver {
+                    let distance =
+                        geo::distance(receiver, position) / geo::METERS_PER_NAUTICAL_MILE;
+                    label += &format!(", {distance:.1} NM from the receiver");
+                }
+                ui.label(label);
+
                 if ui
                     .button(RichText::new("Go to the starting point").heading())
                     .clicked()
@@ -70,10 +585,106 @@ pub fn go_to_my_position(ui: &mut Ui, map_memory: &mut MapMemory) {
     }
 }
 
+/// Edits the receiver location. Returns whether it was changed.
+pub fn receiver_controls(ui: &mut Ui, store: &mut kv_sys::KVStore, map_center: Position) -> bool {
+    let mut changed = false;
+
+    match ReceiverLocation::load(store) {
+        Some(mut location) => {
+            Grid::new("receiver_location")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Latitude");
+                    changed |= ui
+                        .add(
+                            DragValue::new(&mut location.lat)
+                                .speed(0.0001)
+                                .range(-90.0..=90.0)
+                                .max_decimals(6)
+                                .suffix("°"),
+                        )
+                        .changed();
+                    ui.end_row();
+
+                    ui.label("Longitude");
+                    changed |= ui
+                        .add(
+                            DragValue::new(&mut location.lon)
+                                .speed(0.0001)
+                                .range(-180.0..=180.0)
+                                .max_decimals(6)
+                                .suffix("°"),
+                        )
+                        .changed();
+                    ui.end_row();
+                });
+
+            let mut clear = false;
+            ui.horizontal(|ui| {
+                if ui.button("Use map center").clicked() {
+                    location = ReceiverLocation::from_position(map_center);
+                    changed = true;
+                }
+                clear = ui.button("Clear").clicked();
+            });
+
+            if clear {
+                ReceiverLocation::clear(store);
+                changed = true;
+            } else if changed {
+                location.save(store);
+            }
+        }
+        None => {
+            ui.label("The receiver location is not set. Surface positions can't be decoded.");
+            if ui.button("Use map center").clicked() {
+                ReceiverLocation::from_position(map_center).save(store);
+                changed = true;
+            }
+        }
+    }
+
+    changed
+}
+
 pub fn can_go_to_my_position(map_memory: &MapMemory) -> bool {
     map_memory.detached().is_some()
 }
 
+/// Lists the Mode A/C targets which could not be associated with a Mode S aircraft. They have
+/// no position, so they can't be drawn on the map.
+pub fn mode_ac_panel(ui: &mut Ui, targets: &[ModeAcTarget]) {
+    ui.heading("Mode A/C targets");
+    ui.label(format!("{} without Mode S", targets.len()));
+    ui.separator();
+
+    let now = Instant::now();
+    Grid::new("mode_ac_targets")
+        .striped(true)
+        .num_columns(4)
+        .show(ui, |ui| {
+            ui.strong("Mode A");
+            ui.strong("Mode C");
+            ui.strong("Replies");
+            ui.strong("Seen");
+            ui.end_row();
+
+            for target in targets {
+                ui.label(target.squawk().to_string());
+                match target.altitude() {
+                    Some(altitude) => ui.label(format!("{altitude} ft")),
+                    None => ui.label("-"),
+                };
+                ui.label(target.replies.to_string());
+                ui.label(format!(
+                    "{} s ago",
+                    now.saturating_duration_since(target.last_seen).as_secs()
+                ));
+                ui.end_row();
+            }
+        });
+}
+
 pub fn menu_bar(ui: &mut Ui, app: &mut Viewer) {
     MenuBar::new().ui(ui, |ui| {
         ui.menu_button("File", |ui| {
@@ -91,6 +702,60 @@ pub fn menu_bar(ui: &mut Ui, app: &mut Viewer) {
                     store_write.get_as_bool_or_default("viewer.windows.map_controls_open", false);
                 store_write.set("viewer.windows.map_controls_open", !map_controls_open);
             }
+
+            if ui.button("Mode A/C Targets").clicked() {
+                let app_state_write = app.app_state.write().unwrap();
+                let mut store_write = app_state_write.store.write().unwrap();
+
+                let mode_ac_open =
+                    store_write.get_as_bool_or_default("viewer.windows.mode_ac_open", true);
+                store_write.set("viewer.windows.mode_ac_open", !mode_ac_open);
+            }
+
+            if ui.button("Aircraft List").clicked() {
+                let app_state_write = app.app_state.write().unwrap();
+                let mut store_write = app_state_write.store.write().unwrap();
+
+                let list_open =
+                    store_write.get_as_bool_or_default("viewer.windows.aircraft_list_open", false);
+                store_write.set("viewer.windows.aircraft_list_open", !list_open);
+            }
+
+            if ui.button("Alerts").clicked() {
+                let app_state_write = app.app_state.write().unwrap();
+                let mut store_write = app_state_write.store.write().unwrap();
+
+                let alerts_open =
+                    store_write.get_as_bool_or_default("viewer.windows.alerts_open", false);
+                store_write.set("viewer.windows.alerts_open", !alerts_open);
+            }
+
+            if ui.button("Filter").clicked() {
+                let app_state_write = app.app_state.write().unwrap();
+                let mut store_write = app_state_write.store.write().unwrap();
+
+                let filter_open =
+                    store_write.get_as_bool_or_default("viewer.windows.filter_open", false);
+                store_write.set("viewer.windows.filter_open", !filter_open);
+            }
+
+            if ui.button("Coverage").clicked() {
+                let app_state_write = app.app_state.write().unwrap();
+                let mut store_write = app_state_write.store.write().unwrap();
+
+                let coverage_open =
+                    store_write.get_as_bool_or_default("viewer.windows.coverage_open", false);
+                store_write.set("viewer.windows.coverage_open", !coverage_open);
+            }
+
+            if ui.button("Receiver Settings").clicked() {
+                let app_state_write = app.app_state.write().unwrap();
+                let mut store_write = app_state_write.store.write().unwrap();
+
+                let receiver_open =
+                    store_write.get_as_bool_or_default("viewer.windows.receiver_open", false);
+                store_write.set("viewer.windows.receiver_open", !receiver_open);
+            }
         });
     });
 }
@@ -6,8 +6,14 @@ use walkers::extras::{
 };
 use walkers::{Plugin, lat_lon};
 
+pub mod alerts;
 pub mod components;
+pub mod db;
+pub mod filter;
 pub mod frames;
+pub mod location;
+pub mod plugins;
+pub mod selection;
 pub mod tiles;
 pub mod viewer;
 
@@ -80,4 +86,14 @@ pub fn airport_plugin(
 pub struct AppState {
     pub store: ArcRwLock<kv_sys::KVStore>,
     pub airports: ArcRwLock<Vec<Airport>>,
+    pub tracker: ArcRwLock<receiver::Tracker>,
+    pub mode_ac: ArcRwLock<receiver::mode_ac::ModeAcAssociator>,
+    /// Receiver location shared with the feeds which decode positions, `None` until it is set.
+    pub receiver_location: ArcRwLock<Option<walkers::Position>>,
+    pub coverage: ArcRwLock<receiver::coverage::Coverage>,
+    pub alerts: ArcRwLock<receiver::alerts::Alerts>,
+    /// Aircraft selected on the map or in a list.
+    pub selection: ArcRwLock<Option<mode_s::Icao>>,
+    /// Registration, type and operator of the aircraft, looked up in the background.
+    pub registry: Arc<db::registry::Registry>,
 }
@@ -0,0 +1,55 @@
+//! Location of the receiver, persisted in the settings. It is the reference for decoding
+//! positions and measuring distances, and where the map starts.
+
+use walkers::{Position, lat_lon};
+
+const LATITUDE_KEY: &str = "receiver.latitude";
+const LONGITUDE_KEY: &str = "receiver.longitude";
+
+/// Where the map starts until the receiver location is set.
+pub const DEFAULT_MAP_CENTER: (f64, f64) = (36.67680681137922, -4.49391784930664);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReceiverLocation {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+impl ReceiverLocation {
+    /// The stored location, or `None` if it was never set.
+    pub fn load(store: &kv_sys::KVStore) -> Option<Self> {
+        Some(Self {
+            lat: store.get_as_float(LATITUDE_KEY)?,
+            lon: store.get_as_float(LONGITUDE_KEY)?,
+        })
+    }
+
+    pub fn save(&self, store: &mut kv_sys::KVStore) {
+        store.set(LATITUDE_KEY, self.lat);
+        store.set(LONGITUDE_KEY, self.lon);
+    }
+
+    pub fn clear(store: &mut kv_sys::KVStore) {
+        store.delete(LATITUDE_KEY);
+        store.delete(LONGITUDE_KEY);
+    }
+
+    pub fn from_position(position: Position) -> Self {
+        Self {
+            lat: position.y(),
+            lon: position.x(),
+        }
+    }
+
+    pub fn position(&self) -> Position {
+        lat_lon(self.lat, self.lon)
+    }
+}
+
+/// Position the map starts at and returns to: the receiver, or a fixed point if it is not set.
+pub fn map_center(location: Option<ReceiverLocation>) -> Position {
+    location.map_or_else(
+        || lat_lon(DEFAULT_MAP_CENTER.0, DEFAULT_MAP_CENTER.1),
+        |location| location.position(),
+    )
+}
@@ -0,0 +1,444 @@
+use crate::ArcRwLock;
+use egui::{
+    Align2, Color32, ColorImage, FontId, Rect, Response, Sense, Stroke, StrokeKind, Ui, Vec2, vec2,
+};
+use mode_s::adsb::AircraftCategory;
+use mode_s::{Icao, country, geo};
+use receiver::filter::Filter;
+use receiver::tracker::Aircraft;
+use receiver::{Source, Tracker};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+use walkers::extras::{Image, Place, Polyline, Texture};
+use walkers::{MapMemory, Plugin, Position, Projector};
+
+/// Side of the aircraft icon texture, in pixels.
+const ICON_SIZE: usize = 48;
+
+/// Outline of an airliner seen from above, nose up, in a unit square centered at the origin.
+const SILHOUETTE: [(f32, f32); 18] = [
+    (0.0, -0.48),
+    (0.05, -0.4),
+    (0.05, -0.12),
+    (0.46, 0.08),
+    (0.46, 0.14),
+    (0.05, 0.04),
+    (0.05, 0.3),
+    (0.18, 0.4),
+    (0.18, 0.45),
+    (0.0, 0.41),
+    (-0.18, 0.45),
+    (-0.18, 0.4),
+    (-0.05, 0.3),
+    (-0.05, 0.04),
+    (-0.46, 0.14),
+    (-0.46, 0.08),
+    (-0.05, -0.12),
+    (-0.05, -0.4),
+];
+
+fn inside(x: f32, y: f32) -> bool {
+    let mut inside = false;
+    let mut j = SILHOUETTE.len() - 1;
+    for (i, &(xi, yi)) in SILHOUETTE.iter().enumerate() {
+        let (xj, yj) = SILHOUETTE[j];
+        if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+/// Rasterize the aircraft silhouette, with 4x4 supersampling to smooth the edges.
+pub fn aircraft_icon(ctx: &egui::Context, color: Color32) -> Texture {
+    const SAMPLES: usize = 4;
+
+    let mut image = ColorImage::filled([ICON_SIZE, ICON_SIZE], Color32::TRANSPARENT);
+    for row in 0..ICON_SIZE {
+        for column in 0..ICON_SIZE {
+            let mut covered = 0;
+            for sample in 0..SAMPLES * SAMPLES {
+                let x = (column as f32 + ((sample % SAMPLES) as f32 + 0.5) / SAMPLES as f32)
+                    / ICON_SIZE as f32
+                    - 0.5;
+                let y = (row as f32 + ((sample / SAMPLES) as f32 + 0.5) / SAMPLES as f32)
+                    / ICON_SIZE as f32
+                    - 0.5;
+                if inside(x, y) {
+                    covered += 1;
+                }
+            }
+
+            let coverage = covered as f32 / (SAMPLES * SAMPLES) as f32;
+            image[(column, row)] = color.gamma_multiply(coverage);
+        }
+    }
+
+    Texture::from_color_image(image, ctx)
+}
+
+/// Icon colors, which tell whether the position was reported by the aircraft itself.
+const SELF_REPORTED_COLOR: Color32 = Color32::from_rgb(255, 200, 40);
+const MLAT_COLOR: Color32 = Color32::from_rgb(80, 180, 255);
+const TISB_COLOR: Color32 = Color32::from_rgb(200, 120, 220);
+
+/// Ring around the aircraft matching a highlighting filter.
+const MATCH_COLOR: Color32 = Color32::from_rgb(0, 220, 220);
+
+/// Aircraft icons, in one color per kind of position source.
+#[derive(Clone)]
+pub struct AircraftIcons {
+    self_reported: Texture,
+    mlat: Texture,
+    tisb: Texture,
+}
+
+impl AircraftIcons {
+    pub fn new(ctx: &egui::Context) -> Self {
+        Self {
+            self_reported: aircraft_icon(ctx, SELF_REPORTED_COLOR),
+            mlat: aircraft_icon(ctx, MLAT_COLOR),
+            tisb: aircraft_icon(ctx, TISB_COLOR),
+        }
+    }
+
+    fn get(&self, source: Source) -> &Texture {
+        match source {
+            Source::Mlat => &self.mlat,
+            Source::Tisb => &self.tisb,
+            _ => &self.self_reported,
+        }
+    }
+}
+
+/// Relative size of the icon, so that heavies stand out from light aircraft.
+fn category_scale(category: Option<AircraftCategory>) -> f32 {
+    match category {
+        Some(AircraftCategory::Heavy) => 1.2,
+        Some(AircraftCategory::HighVortexLarge) => 1.1,
+        Some(AircraftCategory::Large) => 1.0,
+        Some(AircraftCategory::HighPerformance) => 0.85,
+        Some(AircraftCategory::Small) => 0.8,
+        Some(AircraftCategory::Rotorcraft) => 0.7,
+        Some(
+            AircraftCategory::Light
+            | AircraftCategory::Glider
+            | AircraftCategory::Ultralight
+            | AircraftCategory::Uav
+            | AircraftCategory::LighterThanAir
+            | AircraftCategory::Parachutist,
+        ) => 0.6,
+        _ => 0.9,
+    }
+}
+
+/// Flag and name of the country the address is allocated to, marked when it is in a military
+/// block.
+pub(crate) fn country_text(icao: Icao) -> Option<String> {
+    let country = country::lookup(icao)?;
+    let mut text = match country.flag() {
+        Some(flag) => format!("{flag} {}", country.name),
+        None => country.name.to_string(),
+    };
+    if country::is_military(icao) {
+        text.push_str(", military");
+    }
+    Some(text)
+}
+
+/// Label with the callsign, or the address if there is none, the country code and the
+/// altitude. Military aircraft and positions which were not reported by the aircraft itself
+/// are marked.
+fn label(aircraft: &Aircraft) -> String {
+    let mut name = match &aircraft.callsign {
+        Some(callsign) => callsign.value.clone(),
+        None => aircraft.icao.to_string(),
+    };
+    let country = country::lookup(aircraft.icao).filter(|country| !country.code.is_empty());
+    if let Some(country) = country {
+        name = format!("{name} {}", country.code);
+    }
+    if country::is_military(aircraft.icao) {
+        name = format!("{name} MIL");
+    }
+    let position = aircraft
+        .position
+        .filter(|position| !position.source.is_self_reported());
+    if let Some(position) = position {
+        name = format!("{name} ({})", position.source.name());
+    }
+
+    let on_ground = aircraft.on_ground.is_some_and(|on_ground| on_ground.value);
+    match aircraft.altitude {
+        _ if on_ground => format!("{name}\nGND"),
+        Some(altitude) => format!("{name}\n{} ft", altitude.value),
+        None => name,
+    }
+}
+
+/// Shown when hovering an aircraft.
+fn tooltip(aircraft: &Aircraft) -> String {
+    let mut lines = vec![aircraft.icao.to_string()];
+    if let Some(callsign) = &aircraft.callsign {
+        lines.push(callsign.value.clone());
+    }
+    lines.push(country_text(aircraft.icao).unwrap_or_else(|| "Unallocated address".into()));
+    lines.join("\n")
+}
+
+/// How far positions are extrapolated past the last received one.
+pub(crate) const PREDICTION_LIMIT: Duration = Duration::from_secs(30);
+
+/// Positions whose containment radius is larger than this, in meters, get an uncertainty
+/// circle. It is the radius of NIC 7, below which positions are not good enough for radar-like
+/// separation.
+const POOR_INTEGRITY_RADIUS: f64 = 0.2 * geo::METERS_PER_NAUTICAL_MILE;
+
+/// Draws every tracked aircraft with a known position.
+pub struct AircraftPlugin {
+    tracker: ArcRwLock<Tracker>,
+    icons: AircraftIcons,
+    /// Size of the icon relative to the texture.
+    pub scale: f32,
+    /// Move aircraft between updates according to their ground speed and track.
+    pub predict: bool,
+    /// Length of the speed vectors, as the time it takes the aircraft to fly them. Zero hides
+    /// the vectors.
+    pub speed_vector: Duration,
+    /// Circle the aircraft whose positions have a poor integrity with their containment radius.
+    pub uncertainty_circles: bool,
+    /// Aircraft drawn with a ring around them, e.g. the ones raising an alert.
+    pub highlighted: HashSet<Icao>,
+    /// Selected aircraft, set by clicking on them and cleared by clicking elsewhere on the map.
+    /// Aircraft can only be hovered without it.
+    pub selection: Option<ArcRwLock<Option<Icao>>>,
+    /// Only the aircraft matching it are drawn.
+    pub filter: Option<Filter>,
+    /// The aircraft matching it are drawn with a ring around them.
+    pub highlight: Option<Filter>,
+}
+
+impl AircraftPlugin {
+    pub fn new(tracker: ArcRwLock<Tracker>, icons: AircraftIcons) -> Self {
+        Self {
+            tracker,
+            icons,
+            scale: 0.6,
+            predict: true,
+            speed_vector: Duration::ZERO,
+            uncertainty_circles: true,
+            highlighted: HashSet::new(),
+            selection: None,
+            filter: None,
+            highlight: None,
+        }
+    }
+
+    fn draw_uncertainty_circle(
+        &self,
+        ui: &Ui,
+        projector: &Projector,
+        aircraft: &Aircraft,
+        position: Position,
+    ) {
+        let Some(radius) = aircraft
+            .integrity()
+            .and_then(|integrity| integrity.containment_radius)
+            .filter(|radius| *radius > POOR_INTEGRITY_RADIUS)
+        else {
+            return;
+        };
+
+        let color = ui.visuals().warn_fg_color;
+        ui.painter().circle(
+            projector.project(position).to_pos2(),
+            radius as f32 * projector.scale_pixel_per_meter(position),
+            color.gamma_multiply(0.1),
+            Stroke::new(1.0, color),
+        );
+    }
+
+    fn draw_speed_vector(
+        &self,
+        ui: &Ui,
+        projector: &Projector,
+        aircraft: &Aircraft,
+        from: Position,
+    ) {
+        let (Some(ground_speed), Some(track)) = (aircraft.ground_speed, aircraft.track) else {
+            return;
+        };
+
+        let distance = ground_speed.value * geo::METERS_PER_NAUTICAL_MILE / 3600.0
+            * self.speed_vector.as_secs_f64();
+        let to = geo::destination(from, track.value, distance);
+
+        Polyline::new(
+            vec![from, to],
+            Stroke::new(1.5, ui.visuals().strong_text_color()),
+        )
+        .draw(ui, projector);
+    }
+}
+
+impl Plugin for AircraftPlugin {
+    fn run(
+        &mut self,
+        ui: &mut Ui,
+        response: &Response,
+        projector: &Projector,
+        _map_memory: &MapMemory,
+    ) {
+        profiling::scope!("AircraftPlugin::run");
+        let tracker = self.tracker.read().unwrap();
+        let painter = ui.painter();
+        let now = Instant::now();
+        let selected = self
+            .selection
+            .as_ref()
+            .and_then(|selection| *selection.read().unwrap());
+        let mut clicked = None;
+
+        for aircraft in tracker.iter() {
+            if self
+                .filter
+                .as_ref()
+                .is_some_and(|filter| !filter.matches(aircraft))
+            {
+                continue;
+            }
+
+            let position = if self.predict {
+                aircraft.predicted_position(now, PREDICTION_LIMIT)
+            } else {
+                aircraft.position.map(|position| position.value)
+            };
+            let Some(position) = position else {
+                continue;
+            };
+
+            if self.uncertainty_circles {
+                self.draw_uncertainty_circle(ui, projector, aircraft, position);
+            }
+
+            if !self.speed_vector.is_zero() {
+                self.draw_speed_vector(ui, projector, aircraft, position);
+            }
+
+            let scale =
+                self.scale * category_scale(aircraft.category.map(|category| category.value));
+            let source = aircraft
+                .position
+                .map_or(Source::Unknown, |position| position.source);
+            let mut image = Image::new(self.icons.get(source).clone(), position);
+            image.scale(scale, scale);
+            if let Some(track) = aircraft.track {
+                image.angle(track.value.to_radians() as f32);
+            }
+            image.draw(ui, projector);
+
+            let size = ICON_SIZE as f32 * scale;
+            if selected == Some(aircraft.icao) {
+                painter.rect_stroke(
+                    Rect::from_center_size(
+                        projector.project(position).to_pos2(),
+                        Vec2::splat(size),
+                    ),
+                    4.0,
+                    ui.visuals().selection.stroke,
+                    StrokeKind::Outside,
+                );
+            }
+
+            if projector.is_in_view(position) {
+                let rect = Rect::from_center_size(
+                    projector.project(position).to_pos2(),
+                    Vec2::splat(size),
+                );
+                let response = ui
+                    .interact(rect, ui.id().with(aircraft.icao), Sense::click())
+                    .on_hover_text(tooltip(aircraft));
+                if self.selection.is_some() && response.clicked() {
+                    clicked = Some(aircraft.icao);
+                }
+            }
+
+            if self
+                .highlight
+                .as_ref()
+                .is_some_and(|filter| filter.matches(aircraft))
+            {
+                painter.circle_stroke(
+                    projector.project(position).to_pos2(),
+                    ICON_SIZE as f32 * scale * 0.6,
+                    Stroke::new(2.0, MATCH_COLOR),
+                );
+            }
+
+            let highlighted = self.highlighted.contains(&aircraft.icao);
+            if highlighted {
+                painter.circle_stroke(
+                    projector.project(position).to_pos2(),
+                    ICON_SIZE as f32 * scale * 0.7,
+                    Stroke::new(3.0, ui.visuals().error_fg_color),
+                );
+            }
+
+            if projector.is_in_view(position) {
+                let offset = vec2(0.0, ICON_SIZE as f32 * scale / 2.0 + 2.0);
+                painter.text(
+                    projector.project(position).to_pos2() + offset,
+                    Align2::CENTER_TOP,
+                    label(aircraft),
+                    FontId::proportional(11.0),
+                    if highlighted {
+                        ui.visuals().error_fg_color
+                    } else {
+                        ui.visuals().strong_text_color()
+                    },
+                );
+            }
+        }
+
+        if let Some(selection) = &self.selection {
+            if clicked.is_some() {
+                *selection.write().unwrap() = clicked;
+            } else if response.clicked() {
+                *selection.write().unwrap() = None;
+            }
+        }
+    }
+}
+
+const PREDICT_KEY: &str = "viewer.aircraft.predict_positions";
+const SPEED_VECTOR_KEY: &str = "viewer.aircraft.speed_vector_seconds";
+const UNCERTAINTY_CIRCLES_KEY: &str = "viewer.aircraft.uncertainty_circles";
+
+/// Aircraft display settings, persisted in the key-value store.
+pub struct AircraftSettings {
+    pub predict: bool,
+    /// Length of the speed vectors in seconds of flight.
+    pub speed_vector_seconds: i64,
+    pub uncertainty_circles: bool,
+}
+
+impl AircraftSettings {
+    pub fn load(store: &kv_sys::KVStore) -> Self {
+        Self {
+            predict: store.get_as_bool_or_default(PREDICT_KEY, true),
+            speed_vector_seconds: store.get_as_integer(SPEED_VECTOR_KEY).unwrap_or(60),
+            uncertainty_circles: store.get_as_bool_or_default(UNCERTAINTY_CIRCLES_KEY, true),
+        }
+    }
+
+    pub fn save(&self, store: &mut kv_sys::KVStore) {
+        store.set(PREDICT_KEY, self.predict);
+        store.set(SPEED_VECTOR_KEY, self.speed_vector_seconds);
+        store.set(UNCERTAINTY_CIRCLES_KEY, self.uncertainty_circles);
+    }
+
+    pub fn speed_vector(&self) -> Duration {
+        Duration::from_secs(self.speed_vector_seconds.max(0) as u64)
+    }
+}
@@ -0,0 +1,105 @@
+use crate::ArcRwLock;
+use egui::{Color32, Mesh, Pos2, Response, Shape, Stroke, Ui};
+use mode_s::geo;
+use receiver::coverage::{AltitudeBand, BEARING_BUCKETS, Coverage, bucket_bearing};
+use walkers::{MapMemory, Plugin, Projector};
+
+pub fn band_color(band: AltitudeBand) -> Color32 {
+    match band {
+        AltitudeBand::Low => Color32::from_rgb(255, 140, 0),
+        AltitudeBand::Medium => Color32::from_rgb(80, 200, 60),
+        AltitudeBand::High => Color32::from_rgb(40, 120, 255),
+    }
+}
+
+/// Farthest range of every bucket of `band`, in meters, zero where nothing was received.
+pub fn bucket_ranges(coverage: &Coverage, band: AltitudeBand) -> [f64; BEARING_BUCKETS] {
+    coverage
+        .points(band)
+        .map(|point| point.map_or(0.0, |point| point.range))
+}
+
+/// Draws the coverage of `band` as a polygon around `center`, filled like a fan so that it
+/// may be concave.
+pub(crate) fn draw_coverage(ui: &Ui, center: Pos2, points: Vec<Pos2>, band: AltitudeBand) {
+    let color = band_color(band);
+
+    let mut mesh = Mesh::default();
+    let fill = color.gamma_multiply(0.15);
+    mesh.colored_vertex(center, fill);
+    for point in &points {
+        mesh.colored_vertex(*point, fill);
+    }
+    let count = points.len() as u32;
+    for i in 0..count {
+        mesh.add_triangle(0, i + 1, (i + 1) % count + 1);
+    }
+
+    let painter = ui.painter();
+    painter.add(Shape::mesh(mesh));
+    painter.add(Shape::closed_line(points, Stroke::new(1.5, color)));
+}
+
+/// Draws the coverage polygons of the receiver on the map.
+pub struct CoveragePlugin {
+    coverage: ArcRwLock<Coverage>,
+}
+
+impl CoveragePlugin {
+    pub fn new(coverage: ArcRwLock<Coverage>) -> Self {
+        Self { coverage }
+    }
+}
+
+impl Plugin for CoveragePlugin {
+    fn run(
+        &mut self,
+        ui: &mut Ui,
+        _response: &Response,
+        projector: &Projector,
+        _map_memory: &MapMemory,
+    ) {
+        profiling::scope!("CoveragePlugin::run");
+        let coverage = self.coverage.read().unwrap();
+        let Some(receiver) = coverage.receiver() else {
+            return;
+        };
+        let center = projector.project(receiver).to_pos2();
+
+        // Higher bands reach farther, so they are drawn first.
+        for band in AltitudeBand::ALL.into_iter().rev() {
+            if coverage.max_range(band).is_none() {
+                continue;
+            }
+
+            let points = bucket_ranges(&coverage, band)
+                .iter()
+                .enumerate()
+                .map(|(bucket, range)| {
+                    let position = geo::destination(receiver, bucket_bearing(bucket), *range);
+                    projector.project(position).to_pos2()
+                })
+                .collect();
+            draw_coverage(ui, center, points, band);
+        }
+    }
+}
+
+const SHOW_ON_MAP_KEY: &str = "viewer.coverage.show_on_map";
+
+/// Coverage settings, persisted in the key-value store.
+pub struct CoverageSettings {
+    pub show_on_map: bool,
+}
+
+impl CoverageSettings {
+    pub fn load(store: &kv_sys::KVStore) -> Self {
+        Self {
+            show_on_map: store.get_as_bool_or_default(SHOW_ON_MAP_KEY, false),
+        }
+    }
+
+    pub fn save(&self, store: &mut kv_sys::KVStore) {
+        store.set(SHOW_ON_MAP_KEY, self.show_on_map);
+    }
+}
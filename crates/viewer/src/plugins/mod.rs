@@ -0,0 +1,4 @@
+pub mod aircraft;
+pub mod coverage;
+pub mod range_rings;
+pub mod trails;
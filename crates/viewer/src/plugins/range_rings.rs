@@ -0,0 +1,192 @@
+use egui::{Align2, Color32, FontId, Response, Shape, Stroke, Ui};
+use mode_s::geo;
+use walkers::{MapMemory, Plugin, Position, Projector};
+
+/// Angle between bearing lines, in degrees.
+const BEARING_STEP: u32 = 30;
+
+/// Rings smaller than this on the screen, in points, are not drawn.
+const MIN_RING_RADIUS: f32 = 4.0;
+
+/// Length of each straight segment approximating a ring, in points.
+const SEGMENT_LENGTH: f32 = 6.0;
+
+/// Draws range rings around a position, like the receiver, and optionally bearing lines.
+///
+/// The rings are geodesic circles: every vertex is the destination at the ring's distance, so
+/// they turn into ovals on the Mercator map at high latitudes instead of staying round.
+pub struct RangeRingsPlugin {
+    center: Position,
+    /// Distance between rings in nautical miles.
+    interval: f64,
+    count: u32,
+    /// Draw lines every 30° from the center to the outermost ring.
+    pub bearing_lines: bool,
+    pub color: Color32,
+}
+
+impl RangeRingsPlugin {
+    pub fn new(center: Position, interval: f64, count: u32) -> Self {
+        Self {
+            center,
+            interval,
+            count,
+            bearing_lines: true,
+            color: Color32::from_rgba_unmultiplied(120, 120, 120, 180),
+        }
+    }
+
+    fn radius(&self, ring: u32) -> f64 {
+        ring as f64 * self.interval * geo::METERS_PER_NAUTICAL_MILE
+    }
+
+    fn draw_ring(&self, ui: &Ui, projector: &Projector, ring: u32) {
+        let radius = self.radius(ring);
+        // Only an estimate, as the scale changes with the latitude across the ring.
+        let screen_radius = radius as f32 * projector.scale_pixel_per_meter(self.center);
+        if screen_radius < MIN_RING_RADIUS {
+            return;
+        }
+
+        let vertices =
+            (std::f32::consts::TAU * screen_radius / SEGMENT_LENGTH).clamp(36.0, 720.0) as usize;
+        let points = (0..vertices)
+            .map(|vertex| {
+                let bearing = vertex as f64 * 360.0 / vertices as f64;
+                let position = geo::destination(self.center, bearing, radius);
+                projector.project(position).to_pos2()
+            })
+            .collect();
+
+        let painter = ui.painter();
+        painter.add(Shape::closed_line(points, Stroke::new(1.0, self.color)));
+
+        let north = geo::destination(self.center, 0.0, radius);
+        if projector.is_in_view(north) {
+            painter.text(
+                projector.project(north).to_pos2(),
+                Align2::LEFT_BOTTOM,
+                format!("{} NM", format_distance(ring as f64 * self.interval)),
+                FontId::proportional(11.0),
+                self.color,
+            );
+        }
+    }
+
+    fn draw_bearing_lines(&self, ui: &Ui, projector: &Projector) {
+        let radius = self.radius(self.count);
+        if (radius as f32 * projector.scale_pixel_per_meter(self.center)) < MIN_RING_RADIUS {
+            return;
+        }
+
+        let painter = ui.painter();
+        let stroke = Stroke::new(1.0, self.color.gamma_multiply(0.6));
+        let center = projector.project(self.center).to_pos2();
+
+        for bearing in (0..360).step_by(BEARING_STEP as usize) {
+            // Great circles are not straight on the map, so the line is drawn through the
+            // intermediate rings.
+            let mut points = vec![center];
+            points.extend((1..=self.count).map(|ring| {
+                let position = geo::destination(self.center, bearing as f64, self.radius(ring));
+                projector.project(position).to_pos2()
+            }));
+            let end = *points.last().unwrap();
+            painter.add(Shape::line(points, stroke));
+
+            painter.text(
+                end,
+                label_anchor(bearing),
+                format!("{bearing:03}°"),
+                FontId::proportional(11.0),
+                self.color,
+            );
+        }
+    }
+}
+
+impl Plugin for RangeRingsPlugin {
+    fn run(
+        &mut self,
+        ui: &mut Ui,
+        _response: &Response,
+        projector: &Projector,
+        _map_memory: &MapMemory,
+    ) {
+        profiling::scope!("RangeRingsPlugin::run");
+        if self.interval <= 0.0 {
+            return;
+        }
+
+        if self.bearing_lines {
+            self.draw_bearing_lines(ui, projector);
+        }
+
+        for ring in 1..=self.count {
+            self.draw_ring(ui, projector, ring);
+        }
+    }
+}
+
+fn format_distance(nautical_miles: f64) -> String {
+    if nautical_miles.fract() == 0.0 {
+        format!("{nautical_miles:.0}")
+    } else {
+        format!("{nautical_miles:.1}")
+    }
+}
+
+/// Places the bearing labels outside the outermost ring.
+fn label_anchor(bearing: u32) -> Align2 {
+    match bearing {
+        0 => Align2::CENTER_BOTTOM,
+        1..90 => Align2::LEFT_BOTTOM,
+        90 => Align2::LEFT_CENTER,
+        91..180 => Align2::LEFT_TOP,
+        180 => Align2::CENTER_TOP,
+        181..270 => Align2::RIGHT_TOP,
+        270 => Align2::RIGHT_CENTER,
+        _ => Align2::RIGHT_BOTTOM,
+    }
+}
+
+const ENABLED_KEY: &str = "viewer.range_rings.enabled";
+const INTERVAL_KEY: &str = "viewer.range_rings.interval_nm";
+const COUNT_KEY: &str = "viewer.range_rings.count";
+const BEARING_LINES_KEY: &str = "viewer.range_rings.bearing_lines";
+
+/// Range ring settings, persisted in the key-value store.
+pub struct RangeRingSettings {
+    pub enabled: bool,
+    pub interval_nm: i64,
+    pub count: i64,
+    pub bearing_lines: bool,
+}
+
+impl RangeRingSettings {
+    pub fn load(store: &kv_sys::KVStore) -> Self {
+        Self {
+            enabled: store.get_as_bool_or_default(ENABLED_KEY, true),
+            interval_nm: store.get_as_integer(INTERVAL_KEY).unwrap_or(50),
+            count: store.get_as_integer(COUNT_KEY).unwrap_or(5),
+            bearing_lines: store.get_as_bool_or_default(BEARING_LINES_KEY, false),
+        }
+    }
+
+    pub fn save(&self, store: &mut kv_sys::KVStore) {
+        store.set(ENABLED_KEY, self.enabled);
+        store.set(INTERVAL_KEY, self.interval_nm);
+        store.set(COUNT_KEY, self.count);
+        store.set(BEARING_LINES_KEY, self.bearing_lines);
+    }
+
+    pub fn plugin(&self, center: Position) -> RangeRingsPlugin {
+        let mut plugin = RangeRingsPlugin::new(
+            center,
+            self.interval_nm.max(1) as f64,
+            self.count.max(0) as u32,
+        );
+        plugin.bearing_lines = self.bearing_lines;
+        plugin
+    }
+}
@@ -0,0 +1,234 @@
+use crate::ArcRwLock;
+use egui::{Color32, Response, Ui};
+use receiver::Tracker;
+use receiver::filter::Filter;
+use receiver::tracker::TrailPoint;
+use std::time::Duration;
+use walkers::extras::Polyline;
+use walkers::{MapMemory, Plugin, Projector};
+
+/// Color of trail segments driven on the ground.
+const GROUND_COLOR: Color32 = Color32::from_rgb(128, 128, 128);
+
+/// Maps altitudes to colors, interpolating between a number of stops.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AltitudeGradient {
+    /// Altitude in feet and its color, sorted by altitude.
+    stops: Vec<(i32, Color32)>,
+}
+
+impl Default for AltitudeGradient {
+    fn default() -> Self {
+        Self::new(vec![
+            (0, Color32::from_rgb(255, 96, 0)),
+            (4000, Color32::from_rgb(255, 200, 0)),
+            (10000, Color32::from_rgb(120, 220, 40)),
+            (20000, Color32::from_rgb(0, 200, 200)),
+            (30000, Color32::from_rgb(40, 90, 255)),
+            (40000, Color32::from_rgb(200, 40, 255)),
+        ])
+    }
+}
+
+impl AltitudeGradient {
+    pub fn new(mut stops: Vec<(i32, Color32)>) -> Self {
+        stops.sort_by_key(|(altitude, _)| *altitude);
+        Self { stops }
+    }
+
+    /// Parse stops written like `0:#FF6000,10000:#78DC28`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let stops = s
+            .split(',')
+            .map(|stop| {
+                let (altitude, color) = stop.trim().split_once(':')?;
+                Some((
+                    altitude.trim().parse().ok()?,
+                    Color32::from_hex(color.trim()).ok()?,
+                ))
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        (!stops.is_empty()).then(|| Self::new(stops))
+    }
+
+    pub fn stops_mut(&mut self) -> &mut [(i32, Color32)] {
+        &mut self.stops
+    }
+
+    pub fn color(&self, altitude: i32) -> Color32 {
+        let Some(upper) = self.stops.iter().position(|(stop, _)| *stop > altitude) else {
+            return self
+                .stops
+                .last()
+                .map_or(Color32::WHITE, |(_, color)| *color);
+        };
+        if upper == 0 {
+            return self.stops[0].1;
+        }
+
+        let (low, low_color) = self.stops[upper - 1];
+        let (high, high_color) = self.stops[upper];
+        let t = (altitude - low) as f32 / (high - low) as f32;
+        low_color.lerp_to_gamma(high_color, t)
+    }
+
+    fn point_color(&self, point: &TrailPoint) -> Color32 {
+        match point.altitude {
+            _ if point.on_ground => GROUND_COLOR,
+            Some(altitude) => self.color(altitude),
+            None => GROUND_COLOR,
+        }
+    }
+}
+
+impl std::fmt::Display for AltitudeGradient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let stops: Vec<_> = self
+            .stops
+            .iter()
+            .map(|(altitude, color)| format!("{altitude}:{}", color.to_hex()))
+            .collect();
+        write!(f, "{}", stops.join(","))
+    }
+}
+
+/// Draws the recent positions of every tracked aircraft, colored by altitude.
+pub struct TrailsPlugin {
+    tracker: ArcRwLock<Tracker>,
+    gradient: AltitudeGradient,
+    /// Line width in points.
+    pub width: f32,
+    /// Only the trails of the aircraft matching it are drawn.
+    pub filter: Option<Filter>,
+}
+
+impl TrailsPlugin {
+    pub fn new(tracker: ArcRwLock<Tracker>, gradient: AltitudeGradient) -> Self {
+        Self {
+            tracker,
+            gradient,
+            width: 2.0,
+            filter: None,
+        }
+    }
+}
+
+impl Plugin for TrailsPlugin {
+    fn run(
+        &mut self,
+        ui: &mut Ui,
+        _response: &Response,
+        projector: &Projector,
+        _map_memory: &MapMemory,
+    ) {
+        profiling::scope!("TrailsPlugin::run");
+        let tracker = self.tracker.read().unwrap();
+
+        for aircraft in tracker.iter() {
+            if aircraft.trail.len() < 2
+                || self
+                    .filter
+                    .as_ref()
+                    .is_some_and(|filter| !filter.matches(aircraft))
+            {
+                continue;
+            }
+
+            let positions = aircraft.trail.iter().map(|point| point.position).collect();
+            // Each segment takes the color of the altitude it ends at.
+            let colors = aircraft
+                .trail
+                .iter()
+                .skip(1)
+                .map(|point| self.gradient.point_color(point))
+                .collect();
+
+            Polyline::with_segment_colors(positions, colors, self.width).draw(ui, projector);
+        }
+    }
+}
+
+const ENABLED_KEY: &str = "viewer.trails.enabled";
+const MINUTES_KEY: &str = "viewer.trails.minutes";
+const GRADIENT_KEY: &str = "viewer.trails.gradient";
+
+/// Trail settings, persisted in the key-value store.
+pub struct TrailSettings {
+    pub enabled: bool,
+    pub minutes: i64,
+    pub gradient: AltitudeGradient,
+}
+
+impl TrailSettings {
+    pub fn load(store: &kv_sys::KVStore) -> Self {
+        Self {
+            enabled: store.get_as_bool_or_default(ENABLED_KEY, true),
+            minutes: store
+                .get_as_integer(MINUTES_KEY)
+                .unwrap_or(receiver::tracker::DEFAULT_TRAIL_LENGTH.as_secs() as i64 / 60),
+            gradient: store
+                .get_as_str(GRADIENT_KEY)
+                .and_then(AltitudeGradient::parse)
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn save(&self, store: &mut kv_sys::KVStore) {
+        store.set(ENABLED_KEY, self.enabled);
+        store.set(MINUTES_KEY, self.minutes);
+        store.set(GRADIENT_KEY, self.gradient.to_string());
+    }
+
+    pub fn length(&self) -> Duration {
+        Duration::from_secs(self.minutes.max(0) as u64 * 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gradient() -> AltitudeGradient {
+        AltitudeGradient::parse("10000:#0000FF, 0:#FF0000").unwrap()
+    }
+
+    #[test]
+    fn parsing_gradients() {
+        let gradient = gradient();
+        assert_eq!(
+            gradient.stops,
+            vec![(0, Color32::RED), (10000, Color32::BLUE)]
+        );
+        assert_eq!(
+            AltitudeGradient::parse(&gradient.to_string()),
+            Some(gradient)
+        );
+
+        let default = AltitudeGradient::default();
+        assert_eq!(AltitudeGradient::parse(&default.to_string()), Some(default));
+
+        assert_eq!(AltitudeGradient::parse(""), None);
+        assert_eq!(AltitudeGradient::parse("0:#FF0000,high:#0000FF"), None);
+        assert_eq!(AltitudeGradient::parse("0:red"), None);
+    }
+
+    #[test]
+    fn interpolating_colors() {
+        let gradient = gradient();
+        assert_eq!(gradient.color(0), Color32::RED);
+        assert_eq!(gradient.color(10000), Color32::BLUE);
+        assert_eq!(
+            gradient.color(5000),
+            Color32::RED.lerp_to_gamma(Color32::BLUE, 0.5)
+        );
+        assert_ne!(gradient.color(2500), gradient.color(7500));
+    }
+
+    #[test]
+    fn clamping_colors() {
+        let gradient = gradient();
+        assert_eq!(gradient.color(-1000), Color32::RED);
+        assert_eq!(gradient.color(45000), Color32::BLUE);
+    }
+}
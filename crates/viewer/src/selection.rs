@@ -0,0 +1,21 @@
+//! What the viewer does with the selected aircraft.
+
+const FOLLOW_KEY: &str = "viewer.selection.follow";
+
+/// Selection settings, persisted in the key-value store.
+pub struct SelectionSettings {
+    /// Keep the map centered on the selected aircraft.
+    pub follow: bool,
+}
+
+impl SelectionSettings {
+    pub fn load(store: &kv_sys::KVStore) -> Self {
+        Self {
+            follow: store.get_as_bool_or_default(FOLLOW_KEY, false),
+        }
+    }
+
+    pub fn save(&self, store: &mut kv_sys::KVStore) {
+        store.set(FOLLOW_KEY, self.follow);
+    }
+}
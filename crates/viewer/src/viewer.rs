@@ -1,12 +1,20 @@
+use crate::alerts::AlertSettings;
 use crate::components::Component;
+use crate::filter::FilterSettings;
+use crate::location::{ReceiverLocation, map_center};
+use crate::plugins::aircraft::{AircraftIcons, AircraftPlugin, AircraftSettings, PREDICTION_LIMIT};
+use crate::plugins::coverage::{CoveragePlugin, CoverageSettings};
+use crate::plugins::range_rings::RangeRingSettings;
+use crate::plugins::trails::{TrailSettings, TrailsPlugin};
+use crate::selection::SelectionSettings;
 use crate::tiles::{Provider, TilesKind};
 use crate::{AppState, airport_plugin};
-use egui::{CentralPanel, DragPanButtons, Frame, Id, TopBottomPanel, Window};
+use egui::{CentralPanel, DragPanButtons, Frame, Id, SidePanel, TopBottomPanel, Window};
 use rayon::ThreadPool;
 use std::collections::BTreeMap;
 use std::sync::{Arc, RwLock};
-use std::time::Instant;
-use walkers::{Map, MapMemory, lat_lon};
+use std::time::{Duration, Instant};
+use walkers::{Map, MapMemory};
 
 pub struct ViewerMapInfo {
     pub(crate) providers: BTreeMap<Provider, Vec<TilesKind>>,
@@ -36,8 +44,11 @@ pub struct Viewer {
     pub app_state: Arc<RwLock<AppState>>,
     pub components: Vec<Box<dyn Component>>,
     pub thread_pool: ThreadPool,
+    pub aircraft_icons: AircraftIcons,
     pub last_frame_time: std::time::Instant,
     pub last_fps: f32,
+    /// Newest alert the map was centered on, so that each one only moves the map once.
+    pub last_centered_alert: Option<u64>,
 }
 
 impl Viewer {
@@ -46,6 +57,7 @@ impl Viewer {
         app_state: Arc<RwLock<AppState>>,
         components: Vec<Box<dyn Component>>,
     ) -> Self {
+        let aircraft_icons = AircraftIcons::new(&egui_ctx);
         let map_info = ViewerMapInfo::new(egui_ctx);
 
         Self {
@@ -56,8 +68,10 @@ impl Viewer {
                 .num_threads(8) // You can adjust the number of threads as needed
                 .build()
                 .expect("Failed to create thread pool"),
+            aircraft_icons,
             last_frame_time: Instant::now(),
             last_fps: 0.0,
+            last_centered_alert: None,
         }
     }
 
@@ -114,14 +128,197 @@ impl eframe::App for Viewer {
                         .collect();
 
                     crate::frames::controls(ui, &mut self.map_info, http_stats, self.last_fps);
+                    crate::frames::aircraft_controls(ui, &mut store_write);
+                    crate::frames::trail_controls(ui, &mut store_write);
+                    crate::frames::range_ring_controls(ui, &mut store_write);
+                    crate::frames::coverage_controls(ui, &mut store_write);
                 });
 
             store_write.set("viewer.windows.map_controls_open", map_controls_open);
+
+            let mut receiver_open =
+                store_write.get_as_bool_or_default("viewer.windows.receiver_open", false);
+            let center = self
+                .map_info
+                .map_memory
+                .read()
+                .unwrap()
+                .detached()
+                .unwrap_or_else(|| map_center(ReceiverLocation::load(&store_write)));
+
+            Window::new("Receiver Settings")
+                .resizable(false)
+                .collapsible(true)
+                .open(&mut receiver_open)
+                .show(ctx, |ui| {
+                    if crate::frames::receiver_controls(ui, &mut store_write, center) {
+                        *app_state_write.receiver_location.write().unwrap() =
+                            ReceiverLocation::load(&store_write)
+                                .map(|location| location.position());
+                    }
+                });
+
+            store_write.set("viewer.windows.receiver_open", receiver_open);
+
+            let mut coverage_open =
+                store_write.get_as_bool_or_default("viewer.windows.coverage_open", false);
+
+            Window::new("Coverage")
+                .resizable(true)
+                .collapsible(true)
+                .default_size([360., 420.])
+                .open(&mut coverage_open)
+                .show(ctx, |ui| {
+                    let mut coverage = app_state_write.coverage.write().unwrap();
+                    crate::frames::coverage_chart(ui, &mut coverage);
+                });
+
+            store_write.set("viewer.windows.coverage_open", coverage_open);
+
+            let mut filter_open =
+                store_write.get_as_bool_or_default("viewer.windows.filter_open", false);
+
+            Window::new("Filter")
+                .resizable(false)
+                .collapsible(true)
+                .open(&mut filter_open)
+                .show(ctx, |ui| {
+                    crate::frames::filter_controls(ui, &mut store_write);
+                });
+
+            store_write.set("viewer.windows.filter_open", filter_open);
+        }
+
+        {
+            let app_state = self.app_state.read().unwrap();
+            let mode_ac_open = app_state
+                .store
+                .read()
+                .unwrap()
+                .get_as_bool_or_default("viewer.windows.mode_ac_open", true);
+
+            let mut targets: Vec<_> = app_state
+                .mode_ac
+                .read()
+                .unwrap()
+                .unassociated()
+                .cloned()
+                .collect();
+
+            if mode_ac_open && !targets.is_empty() {
+                targets.sort_by_key(|target| target.reply.code);
+                SidePanel::right(Id::new("mode_ac_panel"))
+                    .resizable(true)
+                    .show(ctx, |ui| crate::frames::mode_ac_panel(ui, &targets));
+            }
+        }
+
+        {
+            let app_state = self.app_state.read().unwrap();
+            let mut store = app_state.store.write().unwrap();
+            let receiver = *app_state.receiver_location.read().unwrap();
+            let mut alerts = app_state.alerts.write().unwrap();
+            let settings = AlertSettings::load(&store);
+            alerts.set_emergencies_only(settings.emergencies_only);
+            // Hidden aircraft don't raise alerts, highlighted ones do like any other.
+            alerts.set_filter(FilterSettings::load(&store).hiding(receiver));
+
+            let mut center_on = None;
+            if settings.center_on_emergency {
+                let emergency = alerts
+                    .unacknowledged()
+                    .find(|alert| alert.active && alert.kind.is_emergency())
+                    .filter(|alert| self.last_centered_alert.is_none_or(|id| alert.id > id));
+                if let Some(alert) = emergency {
+                    self.last_centered_alert = Some(alert.id);
+                    center_on = Some(alert.icao);
+                }
+            }
+
+            // The window can't be closed until every alert is acknowledged.
+            let unacknowledged = alerts.unacknowledged().next().is_some();
+            let mut alerts_open = store.get_as_bool_or_default("viewer.windows.alerts_open", false);
+            let show = unacknowledged || alerts_open;
+
+            let mut window = Window::new("Alerts").resizable(true).collapsible(true);
+            if !unacknowledged {
+                window = window.open(&mut alerts_open);
+            }
+            if show {
+                window.show(ctx, |ui| {
+                    if let Some(icao) = crate::frames::alerts_panel(ui, &mut alerts, &mut store) {
+                        center_on = Some(icao);
+                    }
+                });
+            }
+
+            store.set("viewer.windows.alerts_open", alerts_open);
+            // The alert watcher locks the tracker before the alerts.
+            drop(alerts);
+
+            let position = center_on.and_then(|icao| {
+                app_state
+                    .tracker
+                    .read()
+                    .unwrap()
+                    .get(icao)
+                    .and_then(|aircraft| aircraft.position)
+            });
+            if let Some(position) = position {
+                self.map_info
+                    .map_memory
+                    .write()
+                    .unwrap()
+                    .center_at(position.value);
+            }
+        }
+
+        {
+            let app_state = self.app_state.read().unwrap();
+            let selected = *app_state.selection.read().unwrap();
+            let aircraft =
+                selected.and_then(|icao| app_state.tracker.read().unwrap().get(icao).cloned());
+
+            match aircraft {
+                Some(aircraft) => {
+                    let mut open = true;
+                    let title = aircraft.callsign.as_ref().map_or_else(
+                        || aircraft.icao.to_string(),
+                        |callsign| callsign.value.clone(),
+                    );
+                    let receiver = *app_state.receiver_location.read().unwrap();
+                    let registry = app_state.registry.cached(aircraft.icao);
+
+                    Window::new(title)
+                        .id(Id::new("aircraft_details"))
+                        .resizable(false)
+                        .collapsible(true)
+                        .open(&mut open)
+                        .show(ctx, |ui| {
+                            let mut store = app_state.store.write().unwrap();
+                            crate::frames::aircraft_details(
+                                ui,
+                                &aircraft,
+                                registry.clone(),
+                                receiver,
+                                &mut store,
+                            );
+                        });
+
+                    if !open {
+                        *app_state.selection.write().unwrap() = None;
+                    }
+                }
+                // The selected aircraft is gone.
+                None if selected.is_some() => *app_state.selection.write().unwrap() = None,
+                None => {}
+            }
         }
 
         CentralPanel::default().frame(Frame::NONE).show(ctx, |ui| {
             profiling::scope!("Viewer::CentralPanel");
-            let gps_position = lat_lon(36.67680681137922, -4.49391784930664);
+            let receiver_location =
+                ReceiverLocation::load(&self.app_state.read().unwrap().store.read().unwrap());
 
             let tiles = self
                 .map_info
@@ -136,7 +333,33 @@ impl eframe::App for Viewer {
 
             {
                 let mut write_guard = self.map_info.map_memory.write().unwrap();
-                let mut map = Map::new(None, &mut write_guard, gps_position, &mut self.thread_pool);
+
+                {
+                    let app_state = self.app_state.read().unwrap();
+                    let store = app_state.store.read().unwrap();
+                    let selected = *app_state.selection.read().unwrap();
+
+                    let following = selected.filter(|_| SelectionSettings::load(&store).follow);
+                    if let Some(icao) = following {
+                        if let Some(aircraft) = app_state.tracker.read().unwrap().get(icao) {
+                            let position = if AircraftSettings::load(&store).predict {
+                                aircraft.predicted_position(Instant::now(), PREDICTION_LIMIT)
+                            } else {
+                                aircraft.position.map(|position| position.value)
+                            };
+                            if let Some(position) = position {
+                                write_guard.center_at(position);
+                            }
+                        }
+                    }
+                }
+
+                let mut map = Map::new(
+                    None,
+                    &mut write_guard,
+                    map_center(receiver_location),
+                    &mut self.thread_pool,
+                );
 
                 let airports = &self.app_state.read().unwrap().airports;
 
@@ -154,6 +377,54 @@ impl eframe::App for Viewer {
                     }
                 }
 
+                {
+                    let app_state = self.app_state.read().unwrap();
+                    let store = app_state.store.read().unwrap();
+                    if CoverageSettings::load(&store).show_on_map {
+                        map = map.with_plugin(CoveragePlugin::new(Arc::clone(&app_state.coverage)));
+                    }
+
+                    let range_rings = RangeRingSettings::load(&store);
+                    if let (true, Some(location)) = (range_rings.enabled, receiver_location) {
+                        map = map.with_plugin(range_rings.plugin(location.position()));
+                    }
+
+                    let trails = TrailSettings::load(&store);
+                    let aircraft = AircraftSettings::load(&store);
+                    let filter = FilterSettings::load(&store);
+                    let receiver = *app_state.receiver_location.read().unwrap();
+                    let tracker = Arc::clone(&app_state.tracker);
+
+                    if tracker.read().unwrap().trail_length() != trails.length() {
+                        tracker.write().unwrap().set_trail_length(trails.length());
+                    }
+
+                    if trails.enabled {
+                        let mut trails_plugin =
+                            TrailsPlugin::new(Arc::clone(&tracker), trails.gradient);
+                        trails_plugin.filter = filter.hiding(receiver);
+                        map = map.with_plugin(trails_plugin);
+                    }
+
+                    let mut aircraft_plugin =
+                        AircraftPlugin::new(tracker, self.aircraft_icons.clone());
+                    aircraft_plugin.predict = aircraft.predict;
+                    aircraft_plugin.speed_vector = aircraft.speed_vector();
+                    aircraft_plugin.uncertainty_circles = aircraft.uncertainty_circles;
+                    aircraft_plugin.selection = Some(Arc::clone(&app_state.selection));
+                    aircraft_plugin.filter = filter.hiding(receiver);
+                    aircraft_plugin.highlight = filter.highlighting(receiver);
+                    aircraft_plugin.highlighted = app_state
+                        .alerts
+                        .read()
+                        .unwrap()
+                        .iter()
+                        .filter(|alert| alert.active)
+                        .map(|alert| alert.icao)
+                        .collect();
+                    map = map.with_plugin(aircraft_plugin);
+                }
+
                 for (n, tiles) in tiles.iter_mut().enumerate() {
                     let transparency = if n == 0 { 1.0 } else { 0.25 };
                     map = map.with_layer(tiles.as_mut(), transparency);
@@ -173,10 +444,20 @@ impl eframe::App for Viewer {
 
             {
                 let mut write_guard = self.map_info.map_memory.write().unwrap();
-                crate::frames::go_to_my_position(ui, &mut write_guard);
+                crate::frames::go_to_my_position(
+                    ui,
+                    &mut write_guard,
+                    receiver_location.map(|location| location.position()),
+                );
             }
         });
 
+        // Aircraft move even when nobody touches the map.
+        let tracker = Arc::clone(&self.app_state.read().unwrap().tracker);
+        if !tracker.read().unwrap().is_empty() {
+            ctx.request_repaint_after(Duration::from_millis(250));
+        }
+
         profiling::finish_frame!();
     }
 
@@ -3,9 +3,11 @@
 mod image;
 mod labeled_symbol;
 mod places;
+mod polyline;
 pub use crate::tiles::Texture;
 pub use image::Image;
 pub use labeled_symbol::{
     LabeledSymbol, LabeledSymbolGroup, LabeledSymbolGroupStyle, LabeledSymbolStyle, Symbol,
 };
 pub use places::{Group, GroupedPlaces, Place, Places};
+pub use polyline::Polyline;
@@ -0,0 +1,72 @@
+use crate::{MapMemory, Plugin, Position, Projector};
+use egui::{Color32, Response, Stroke, Ui};
+
+/// A line going through a sequence of positions, to be drawn on the map. Each segment can have
+/// its own color.
+#[derive(Clone)]
+pub struct Polyline {
+    positions: Vec<Position>,
+    colors: Vec<Color32>,
+    width: f32,
+}
+
+impl Polyline {
+    /// Line of a single color.
+    pub fn new(positions: Vec<Position>, stroke: Stroke) -> Self {
+        let colors = vec![stroke.color; positions.len().saturating_sub(1)];
+        Self {
+            positions,
+            colors,
+            width: stroke.width,
+        }
+    }
+
+    /// Line where each segment has its own color. `colors` holds one color per segment, that is
+    /// one less than there are positions. Segments without a color are not drawn.
+    pub fn with_segment_colors(positions: Vec<Position>, colors: Vec<Color32>, width: f32) -> Self {
+        Self {
+            positions,
+            colors,
+            width,
+        }
+    }
+
+    pub fn positions(&self) -> &[Position] {
+        &self.positions
+    }
+
+    /// Draw the line.
+    pub fn draw(&self, ui: &Ui, projector: &Projector) {
+        let painter = ui.painter();
+        let clip_rect = painter.clip_rect();
+
+        let points: Vec<_> = self
+            .positions
+            .iter()
+            .map(|position| projector.project(*position).to_pos2())
+            .collect();
+
+        for (segment, color) in points.windows(2).zip(&self.colors) {
+            let (start, end) = (segment[0], segment[1]);
+
+            // Skip segments which are entirely outside of the view.
+            if !clip_rect.intersects(egui::Rect::from_two_pos(start, end)) {
+                continue;
+            }
+
+            painter.line_segment([start, end], Stroke::new(self.width, *color));
+        }
+    }
+}
+
+impl Plugin for Polyline {
+    fn run(
+        &mut self,
+        ui: &mut Ui,
+        _response: &Response,
+        projector: &Projector,
+        _map_memory: &MapMemory,
+    ) {
+        self.draw(ui, projector);
+    }
+}